@@ -0,0 +1,152 @@
+//! Automatic expansion of abbreviations configured in `book.toml`, e.g.
+//!
+//! ```toml
+//! [preprocessor.ai-pocket-reference.abbreviations]
+//! RLHF = "Reinforcement Learning from Human Feedback"
+//! ```
+//!
+//! The first occurrence of each abbreviation in a chapter is wrapped in an
+//! `<abbr>` tag so readers get its expansion as a tooltip.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+
+/// An occurrence of an abbreviation to expand.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Abbreviation<'a> {
+    #[serde(skip)]
+    pub range: Range<usize>,
+    pub abbr: &'a str,
+    pub title: &'a str,
+}
+
+/// Byte ranges of Markdown where an `<abbr>` tag can't be inserted: HTML
+/// tags and comments, headings, link destinations, link reference
+/// definitions and bare URLs.
+fn find_markup_ranges(content: &str) -> Vec<Range<usize>> {
+    static MARKUP_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"(?xsm)
+            <!--.*?-->                      # html comment
+            | </?[a-zA-Z][^>]*>             # html tag
+            | ^\x20{0,3}\#{1,6}(?:[\x20\t][^\n]*)?$   # atx heading
+            | \]\([^)]*\)                   # link destination
+            | ^\x20{0,3}\[[^\]\n]+\]:[^\n]*$    # link reference definition
+            | https?://[^\s)>\]]+           # bare url
+            ",
+        )
+        .unwrap()
+    });
+
+    MARKUP_RE
+        .find_iter(content)
+        .map(|mat| mat.range())
+        .collect()
+}
+
+/// Finds the first occurrence of each of `abbreviations` in `content` as a
+/// whole word, outside of `excluded` ranges and Markdown markup, ordered by
+/// position.
+pub(crate) fn find_abbreviations<'a>(
+    content: &str,
+    abbreviations: &'a BTreeMap<String, String>,
+    excluded: &[Range<usize>],
+) -> Vec<Abbreviation<'a>> {
+    if abbreviations.is_empty() {
+        return vec![];
+    }
+
+    // longest first, so that `GPT-4` wins over `GPT`
+    let mut keys = abbreviations
+        .keys()
+        .filter(|abbr| !abbr.is_empty())
+        .collect::<Vec<_>>();
+    keys.sort_by_key(|abbr| std::cmp::Reverse(abbr.len()));
+    let pattern = keys
+        .iter()
+        .map(|abbr| regex::escape(abbr))
+        .collect::<Vec<_>>()
+        .join("|");
+    let Ok(re) = Regex::new(&pattern) else {
+        return vec![];
+    };
+
+    let markup = find_markup_ranges(content);
+    let is_excluded = |index: usize| {
+        excluded
+            .iter()
+            .chain(markup.iter())
+            .any(|range| range.contains(&index))
+    };
+    let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+
+    let mut seen = HashSet::new();
+    let mut found = vec![];
+    for mat in re.find_iter(content) {
+        let before = content[..mat.start()].chars().next_back();
+        let after = content[mat.end()..].chars().next();
+        if is_word_char(before) || is_word_char(after) || is_excluded(mat.start()) {
+            continue;
+        }
+        let Some((abbr, title)) = abbreviations.get_key_value(mat.as_str()) else {
+            continue;
+        };
+        if seen.insert(abbr) {
+            found.push(Abbreviation {
+                range: mat.range(),
+                abbr,
+                title,
+            });
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_find_abbreviations() -> Result<()> {
+        let abbreviations = BTreeMap::from([
+            (
+                "RLHF".to_string(),
+                "Reinforcement Learning from Human Feedback".to_string(),
+            ),
+            (
+                "GPT".to_string(),
+                "Generative Pre-trained Transformer".to_string(),
+            ),
+            (
+                "GPT-4".to_string(),
+                "Generative Pre-trained Transformer 4".to_string(),
+            ),
+        ]);
+        let content = "# RLHF\n\n\
+            `RLHF` and [RLHFs](https://example.com/RLHF) <img alt=\"RLHF\"/>\n\
+            GPT-4 uses RLHF, as does RLHF-tuned GPT.\n";
+        let code_start = content.find('`').unwrap();
+        let code_span = code_start..code_start + 6;
+        let excluded = [code_span];
+
+        let found = find_abbreviations(content, &abbreviations, &excluded)
+            .into_iter()
+            .map(|abbr| (&content[abbr.range], abbr.abbr))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![("GPT-4", "GPT-4"), ("RLHF", "RLHF"), ("GPT", "GPT")]
+        );
+
+        let uses = content.find("uses").unwrap();
+        let found = find_abbreviations(content, &abbreviations, &excluded);
+        assert_eq!(found[1].range.start, uses + 5);
+
+        Ok(())
+    }
+}
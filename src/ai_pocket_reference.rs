@@ -1,563 +1,4958 @@
-use handlebars::{to_json, Handlebars};
-use mdbook::book::{Book, BookItem};
+use crate::abbreviations::{find_abbreviations, Abbreviation};
+use crate::bibliography::Bibliography;
+use crate::config::{self, AIPRConfig};
+use crate::feed::{self, FeedEntry};
+use crate::generated::{self, write_generated_file};
+use crate::git;
+use crate::glossary::Glossary;
+use crate::helpers::cite::render_references;
+use crate::helpers::footer::AIPRFooterSettings;
+use crate::helpers::header::{AIPRHeaderSettings, ReadingTimeOptions};
+use crate::helpers::params::{_parse_list, find_param_problems};
+use crate::helpers::{AIPRLinkType, HelperContext, HelperHandler, HelperRegistry};
+use crate::i18n::{self, Catalog};
+use crate::link_check::{self, LinkCache, LinkStatus};
+use crate::notebooks::{check_notebook, NotebookStatus};
+use crate::tags::TagRegistry;
+use crate::word_count::{self, WordCount};
+use anyhow::{Context, Result};
+use handlebars::{
+    handlebars_helper, to_json, Handlebars, Helper, HelperDef, HelperResult, Output,
+    RenderErrorReason,
+};
+use mdbook::book::{Book, BookItem, Chapter};
 use mdbook::preprocess::{Preprocessor, PreprocessorContext};
+use mdbook::{Config, MDBook};
 use once_cell::sync::Lazy;
+use pulldown_cmark::{CowStr, Event, LinkType, Parser, Tag, TagEnd};
+use rayon::prelude::*;
 use regex::{CaptureMatches, Captures, Regex};
 use serde::Serialize;
 use serde_json::value::Map;
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
 const AIPR_HEADER_TEMPLATE: &str = include_str!("./templates/header.hbs");
-const AIPR_FOOTER_HTML: &str = include_str!("./templates/footer.html");
+const AIPR_FOOTER_TEMPLATE: &str = include_str!("./templates/footer.hbs");
+const AIPR_AUTHORS_TEMPLATE: &str = include_str!("./templates/authors.hbs");
+const AIPR_CITATION_TEMPLATE: &str = include_str!("./templates/citation.hbs");
+const AIPR_LAST_UPDATED_TEMPLATE: &str = include_str!("./templates/last_updated.hbs");
+const AIPR_CONTRIBUTORS_TEMPLATE: &str = include_str!("./templates/contributors.hbs");
+const AIPR_TOC_TEMPLATE: &str = include_str!("./templates/toc.hbs");
+const AIPR_RELATED_TEMPLATE: &str = include_str!("./templates/related.hbs");
+const AIPR_PREREQS_TEMPLATE: &str = include_str!("./templates/prereqs.hbs");
+const AIPR_TAGS_TEMPLATE: &str = include_str!("./templates/tags.hbs");
+const AIPR_TERM_TEMPLATE: &str = include_str!("./templates/term.hbs");
+const AIPR_ABBR_TEMPLATE: &str = include_str!("./templates/abbr.hbs");
+const AIPR_CALLOUT_TEMPLATE: &str = include_str!("./templates/callout.hbs");
+const AIPR_FIGURE_TEMPLATE: &str = include_str!("./templates/figure.hbs");
+const AIPR_REF_TEMPLATE: &str = include_str!("./templates/ref.hbs");
+const AIPR_EQUATION_TEMPLATE: &str = include_str!("./templates/equation.hbs");
+const AIPR_EQREF_TEMPLATE: &str = include_str!("./templates/eqref.hbs");
+const AIPR_CHAPTER_LINK_TEMPLATE: &str = include_str!("./templates/chapter_link.hbs");
+const AIPR_CITE_TEMPLATE: &str = include_str!("./templates/cite.hbs");
+const AIPR_REFERENCES_TEMPLATE: &str = include_str!("./templates/references.hbs");
+const AIPR_VIDEO_TEMPLATE: &str = include_str!("./templates/video.hbs");
+const AIPR_CODE_TEMPLATE: &str = include_str!("./templates/code.hbs");
+const AIPR_HF_TEMPLATE: &str = include_str!("./templates/hf.hbs");
+const AIPR_ARXIV_TEMPLATE: &str = include_str!("./templates/arxiv.hbs");
+const AIPR_ARXIV_LINK_TEMPLATE: &str = include_str!("./templates/arxiv_link.hbs");
+const AIPR_DOI_TEMPLATE: &str = include_str!("./templates/doi.hbs");
+const AIPR_DATASET_CARD_TEMPLATE: &str = include_str!("./templates/dataset_card.hbs");
+const AIPR_MODEL_CARD_TEMPLATE: &str = include_str!("./templates/model_card.hbs");
+const AIPR_BENCHMARK_TEMPLATE: &str = include_str!("./templates/benchmark.hbs");
+const AIPR_SERIES_NAV_TEMPLATE: &str = include_str!("./templates/series_nav.hbs");
+const AIPR_LICENSE_TEMPLATE: &str = include_str!("./templates/license.hbs");
+const AIPR_CHANGELOG_TEMPLATE: &str = include_str!("./templates/changelog.hbs");
+const AIPR_TOTAL_READING_TIME_TEMPLATE: &str = include_str!("./templates/total_reading_time.hbs");
+const AIPR_META_TEMPLATE: &str = include_str!("./templates/meta.hbs");
+const AIPR_COMMENTS_TEMPLATE: &str = include_str!("./templates/comments.hbs");
+const AIPR_FEEDBACK_TEMPLATE: &str = include_str!("./templates/feedback.hbs");
+pub(crate) const AIPR_FEEDBACK_SCRIPT: &str = include_str!("./assets/feedback.js");
+const AIPR_REDIRECT_TEMPLATE: &str = include_str!("./templates/redirect.hbs");
+const AIPR_VERSION_BANNER_TEMPLATE: &str = include_str!("./templates/version_banner.hbs");
+const AIPR_STALE_NOTICE_TEMPLATE: &str = include_str!("./templates/stale_notice.hbs");
+const AIPR_QUIZ_TEMPLATE: &str = include_str!("./templates/quiz.hbs");
+const AIPR_QUIZ_SCRIPT: &str = include_str!("./assets/quiz.js");
+const AIPR_QUIZ_STYLE: &str = include_str!("./assets/quiz.css");
 const MDLINK_TEMPLATE: &str = include_str!("./templates/md_link.hbs");
-const WORDS_PER_MINUTE: usize = 200;
+pub(crate) const WORDS_PER_MINUTE: usize = 200;
+/// Path, relative to the book's `src`, of the chapter metadata export.
+const METADATA_PATH: &str = "aipr-metadata.json";
+
+/// Built-in templates by name.
+const TEMPLATE_SOURCES: &[(&str, &str)] = &[
+    ("aipr_header", AIPR_HEADER_TEMPLATE),
+    ("aipr_footer", AIPR_FOOTER_TEMPLATE),
+    ("aipr_authors", AIPR_AUTHORS_TEMPLATE),
+    ("aipr_citation", AIPR_CITATION_TEMPLATE),
+    ("aipr_last_updated", AIPR_LAST_UPDATED_TEMPLATE),
+    ("aipr_contributors", AIPR_CONTRIBUTORS_TEMPLATE),
+    ("aipr_toc", AIPR_TOC_TEMPLATE),
+    ("aipr_related", AIPR_RELATED_TEMPLATE),
+    ("aipr_prereqs", AIPR_PREREQS_TEMPLATE),
+    ("aipr_tags", AIPR_TAGS_TEMPLATE),
+    ("aipr_term", AIPR_TERM_TEMPLATE),
+    ("aipr_abbr", AIPR_ABBR_TEMPLATE),
+    ("aipr_callout", AIPR_CALLOUT_TEMPLATE),
+    ("aipr_figure", AIPR_FIGURE_TEMPLATE),
+    ("aipr_ref", AIPR_REF_TEMPLATE),
+    ("aipr_eq", AIPR_EQUATION_TEMPLATE),
+    ("aipr_eqref", AIPR_EQREF_TEMPLATE),
+    ("aipr_chapter", AIPR_CHAPTER_LINK_TEMPLATE),
+    ("aipr_cite", AIPR_CITE_TEMPLATE),
+    ("aipr_references", AIPR_REFERENCES_TEMPLATE),
+    ("aipr_video", AIPR_VIDEO_TEMPLATE),
+    ("aipr_quiz", AIPR_QUIZ_TEMPLATE),
+    ("aipr_code", AIPR_CODE_TEMPLATE),
+    ("aipr_hf", AIPR_HF_TEMPLATE),
+    ("aipr_arxiv", AIPR_ARXIV_TEMPLATE),
+    ("aipr_arxiv_link", AIPR_ARXIV_LINK_TEMPLATE),
+    ("aipr_doi", AIPR_DOI_TEMPLATE),
+    ("aipr_dataset", AIPR_DATASET_CARD_TEMPLATE),
+    ("aipr_model_card", AIPR_MODEL_CARD_TEMPLATE),
+    ("aipr_benchmark", AIPR_BENCHMARK_TEMPLATE),
+    ("aipr_series_nav", AIPR_SERIES_NAV_TEMPLATE),
+    ("aipr_license", AIPR_LICENSE_TEMPLATE),
+    ("aipr_changelog", AIPR_CHANGELOG_TEMPLATE),
+    ("aipr_total_reading_time", AIPR_TOTAL_READING_TIME_TEMPLATE),
+    ("aipr_meta", AIPR_META_TEMPLATE),
+    ("aipr_comments", AIPR_COMMENTS_TEMPLATE),
+    ("aipr_feedback", AIPR_FEEDBACK_TEMPLATE),
+    ("aipr_redirect", AIPR_REDIRECT_TEMPLATE),
+    ("aipr_version_banner", AIPR_VERSION_BANNER_TEMPLATE),
+    ("aipr_stale_notice", AIPR_STALE_NOTICE_TEMPLATE),
+    ("md_link_expansion", MDLINK_TEMPLATE.trim_ascii()),
+];
+
+/// Handlebars registry with every built-in template registered once, shared
+/// by all renders.
+static TEMPLATES: Lazy<Handlebars<'static>> = Lazy::new(|| {
+    let mut handlebars = Handlebars::new();
+    handlebars.register_helper("t", Box::new(TranslateHelper(Catalog::default())));
+    handlebars.register_helper("uppercase", Box::new(uppercase));
+    handlebars.register_helper("lowercase", Box::new(lowercase));
+    handlebars.register_helper("format_date", Box::new(format_date_helper));
+    for (name, template) in TEMPLATE_SOURCES {
+        handlebars
+            .register_template_string(name, template)
+            .unwrap_or_else(|e| panic!("invalid built-in template `{name}`: {e}"));
+    }
+    handlebars
+});
+
+/// `{{t "Part {part} of {total}" part=part total=total}}` writes a message
+/// translated by the catalog, with its `{name}` placeholders replaced by the
+/// helper's hash params.
+struct TranslateHelper(Catalog);
+
+impl HelperDef for TranslateHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc handlebars::Context,
+        _: &mut handlebars::RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let msgid = h
+            .param(0)
+            .and_then(|param| param.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("t", 0))?;
+        let mut text = self.0.get(msgid).to_string();
+        for (name, value) in h.hash() {
+            let value = match value.value() {
+                serde_json::Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            text = text.replace(&format!("{{{name}}}"), &value);
+        }
+        let escaped = text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&#39;");
+        out.write(&escaped)?;
+        Ok(())
+    }
+}
+
+/// The built-in templates with the partials `<name>.hbs` of `dir`.
+fn load_partials(dir: &Path) -> Result<Handlebars<'static>> {
+    let mut templates = TEMPLATES.clone();
+    let mut paths = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read partials directory {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+    for path in paths {
+        let Some(name) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .filter(|_| path.extension().is_some_and(|ext| ext == "hbs"))
+        else {
+            continue;
+        };
+        anyhow::ensure!(
+            !TEMPLATES.has_template(name),
+            "Partial {} has the name of a built-in template",
+            path.display()
+        );
+        let source = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read partial {}", path.display()))?;
+        templates
+            .register_partial(name, source)
+            .with_context(|| format!("Failed to parse partial {}", path.display()))?;
+    }
+    Ok(templates)
+}
+
+handlebars_helper!(uppercase: |text: str| text.to_uppercase());
+handlebars_helper!(lowercase: |text: str| text.to_lowercase());
+
+/// `{{format_date date "%B %-d, %Y"}}` writes the date `date`, `YYYY-MM-DD`,
+/// RFC 3339 or `today`, in a chrono strftime format, `%Y-%m-%d` by default.
+fn format_date_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &handlebars::Context,
+    _: &mut handlebars::RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let value = h
+        .param(0)
+        .and_then(|param| param.value().as_str())
+        .ok_or(RenderErrorReason::ParamNotFoundForIndex("format_date", 0))?;
+    let format = h
+        .param(1)
+        .and_then(|param| param.value().as_str())
+        .unwrap_or("%Y-%m-%d");
+    let date = match value {
+        "today" => chrono::Local::now().date_naive(),
+        value => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .or_else(|_| chrono::DateTime::parse_from_rfc3339(value).map(|date| date.date_naive()))
+            .map_err(|_| {
+                RenderErrorReason::Other(format!("`{value}` is not a date of `format_date`"))
+            })?,
+    };
+    let mut formatted = String::new();
+    write!(formatted, "{}", date.format(format)).map_err(|_| {
+        RenderErrorReason::Other(format!("`{format}` is not a date format of `format_date`"))
+    })?;
+    out.write(&formatted)?;
+    Ok(())
+}
+
+/// Messages of the built-in templates and Rust code, for translators.
+pub(crate) fn messages() -> Vec<String> {
+    static MESSAGE_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"\{\{t "((?:[^"\\]|\\.)*)""#).unwrap());
+
+    let mut messages = TEMPLATE_SOURCES
+        .iter()
+        .flat_map(|(_, template)| MESSAGE_RE.captures_iter(template))
+        .map(|caps| caps[1].replace("\\\"", "\""))
+        .chain(i18n::MESSAGES.iter().map(|msgid| msgid.to_string()))
+        .collect::<Vec<_>>();
+    messages.sort();
+    messages.dedup();
+    messages
+}
 
 #[derive(Default)]
-pub struct AIPRPreprocessor;
+pub struct AIPRPreprocessor {
+    /// Helpers added with [`AIPRPreprocessor::with_helper`].
+    helpers: HelperRegistry,
+}
 
 /// A preprocessor for expanding AI-Pocket-Reference helpers.
 ///
 /// Supported helpers are:
 ///
 /// - `{{#aipr_header <param-str>}}` - Adds the ai-pocket-reference header (optional param-str)
+/// - `{{#aipr_footer <param-str>}}` - Adds the ai-pocket-reference footer (optional param-str).
+///   Chapters without this helper get the default footer appended.
+/// - `{{#aipr_no_footer}}` - Leaves the default footer out of the chapter
+/// - `{{#aipr_license <spdx-id>}}` - Adds a content license line with a badge, e.g. for
+///   `CC-BY-4.0`. Footers show the `license` configured in `book.toml`.
+/// - `{{#aipr_authors authors=<user-1>,<user-2>}}` - Adds a byline with GitHub avatars
+/// - `{{#aipr_citation <param-str>}}` - Adds a "Cite this chapter" block (optional param-str)
+/// - `{{#aipr_last_updated}}` - Adds the date of the last commit touching the chapter
+/// - `{{#aipr_contributors <param-str>}}` - Adds avatars of the chapter's git contributors
+///   (optional param-str)
+/// - `{{#aipr_version_banner}}` - Adds a dismissible banner linking the version of the book
+///   configured as `current_version` to the latest one at `latest_url`
+/// - `{{#aipr_comments}}` - Adds the giscus or utterances comments configured under `comments`
+/// - `{{#aipr_changelog <param-str>}}` - Adds a collapsible list of the chapter's most recent
+///   commits (optional param-str)
+/// - `{{#aipr_toc <param-str>}}` - Adds an "On this page" table of contents (optional param-str)
+/// - `{{#aipr_total_reading_time <param-str>}}` - Adds the summed reading time of the book's
+///   chapters, or of a part's, optionally with a subtotal per part (optional param-str)
+/// - `{{#aipr_related related=<path-1>,<path-2>}}` - Adds cards linking to related chapters
+/// - `{{#aipr_prereqs prereqs=<path-1>,<path-2>}}` - Adds a "Before you read this" list
+/// - `{{#aipr_series_nav <series>}}` - Adds "Part 3 of 5" with links to the previous and
+///   next chapters of a series configured in `book.toml` (optional series, defaulting to
+///   the first series containing the chapter)
+/// - `{{#aipr_chapter <path>}}` - Adds a link to a chapter, titled with the chapter's name
+/// - `{{#aipr_tags <tag-1>,<tag-2>}}` - Adds tag chips linking to generated "Tag: X" chapters
+/// - `{{#aipr_term <term>}}` - Adds a glossary term with its definition as a tooltip
+/// - `{{#aipr_link <name> "<text>"}}` - Adds an external link to a URL configured under
+///   `links` in `book.toml` (optional text, defaulting to the URL)
+/// - `{{#aipr_callout <param-str>}} ... {{/aipr_callout}}` - Wraps content in a note, tip,
+///   warning or danger box (optional param-str)
+/// - `{{#aipr_figure src=<path>,caption=<text>}}` - Adds a figure numbered within the chapter
+/// - `{{#aipr_ref <figure-id>}}` - Adds a link to a figure by its number, e.g. "Figure 2"
+/// - `{{#aipr_eq label=<label>}} ... {{/aipr_eq}}` - Numbers display math (optional param-str)
+/// - `{{#aipr_eqref <label>}}` - Adds a link to an equation by its number, e.g. "(Eq. 3)"
+/// - `{{#aipr_video youtube=<id>}}` or `{{#aipr_video vimeo=<id>}}` - Embeds a video
+/// - `{{#aipr_code <path> lines=<start>:<end>}}` - Adds a code block with a file of the
+///   companion code repository (optional lines)
+/// - `{{#aipr_hf model=<id>}}`, `dataset=<id>` or `space=<id>` - Adds a Hugging Face Hub
+///   badge, with a card of the repository's downloads, likes and license if `card=true`
+/// - `{{#aipr_arxiv <id>}}` - Adds a card of an arXiv paper, or a plain link to it when its
+///   metadata can't be fetched
+/// - `{{#aipr_doi <doi>}}` - Adds a formatted citation of a DOI, or a plain link to it when
+///   its metadata can't be fetched
+/// - `{{#aipr_dataset <param-str>}}` - Adds a dataset card from inline params, or from a
+///   TOML file under `cards_dir` with `file=<path>`
+/// - `{{#aipr_model_card <param-str>}}` - Adds a model card from inline params, or from a
+///   TOML or YAML file under `cards_dir` with `file=<path>`
+/// - `{{#aipr_benchmark <path> <param-str>}}` - Adds a table of results from a CSV or JSON
+///   file, optionally highlighting the best values (optional param-str)
+/// - `{{#aipr_quiz}} ... {{/aipr_quiz}}` or `{{#aipr_quiz file=<path>}}` - Adds
+///   multiple-choice questions written in TOML. Chapters with quizzes get the quiz
+///   script and styles appended.
+/// - `{{#aipr_cite <key-1>,<key-2>}}` - Adds numbered citations of `references.bib` entries.
+///   Chapters citing anything get a "References" list appended.
+///
+/// More helpers can be added with [`AIPRPreprocessor::with_helper`].
 impl AIPRPreprocessor {
     pub(crate) const NAME: &'static str = "ai-pocket-reference";
 
     /// Create a new `AIPRPreprocessor`.
     pub fn new() -> Self {
-        AIPRPreprocessor
+        Self::default()
     }
-}
 
-impl Preprocessor for AIPRPreprocessor {
-    fn name(&self) -> &str {
-        Self::NAME
+    /// Adds the helper `H`, expanding `{{#<name> <params>}}` in chapters.
+    ///
+    /// # Panics
+    ///
+    /// If the name of `H` doesn't start with `aipr_`, or is a built-in
+    /// helper's.
+    pub fn with_helper<H: HelperHandler + 'static>(mut self) -> Self {
+        let name = H::name();
+        assert!(
+            name.starts_with("aipr_") && self.helpers.built_in(name).is_none(),
+            "helper `{name}` doesn't start with `aipr_` or is built in"
+        );
+        self.helpers.register::<H>();
+        self
     }
 
-    fn run(&self, _ctx: &PreprocessorContext, mut book: Book) -> anyhow::Result<Book> {
-        // This run method's implementation follows the implementation of
-        // mdbook::preprocess::links::LinkPreprocessor.run().
-        book.for_each_mut(|section: &mut BookItem| {
-            if let BookItem::Chapter(ref mut ch) = *section {
-                let word_count = words_count::count(&ch.content);
-                let mut content = replace_all(&ch.content, word_count.words);
+    /// Whether helpers are expanded for `renderer`, i.e. whether it's one of
+    /// the `expand_renderers` of the book at `dir`. A missing `book.toml` is
+    /// the default config.
+    pub fn supports(&self, dir: &Path, renderer: &str) -> Result<bool> {
+        let book_toml = dir.join("book.toml");
+        let config = match book_toml.exists() {
+            true => Config::from_disk(&book_toml)
+                .with_context(|| format!("Failed to read {}", book_toml.display()))?,
+            false => Config::default(),
+        };
+        let config = AIPRConfig::from_config(&config)?;
+        Ok(config.expand_renderers.iter().any(|r| r == renderer))
+    }
 
-                // add footer with logo
-                content.push_str(AIPR_FOOTER_HTML);
+    /// Runs every validation of the book at `dir` without building it: helper
+    /// syntax and params, notebook paths and references to other chapters.
+    /// Problems are returned in book order, whether or not `strict` is set.
+    pub fn check(&self, dir: &Path) -> Result<Vec<Problem>> {
+        let (config, book_info, chapters) = load_book(dir)?;
 
-                // mutate chapter content
-                ch.content = content;
-            }
-        });
-        Ok(book)
+        let mut problems = vec![];
+        for ch in &chapters {
+            problems.extend(
+                find_helper_problems(&ch.content, &config, &self.helpers)
+                    .into_iter()
+                    .map(|message| Problem::new(&ch.name, message)),
+            );
+        }
+        problems.extend(
+            find_missing_notebooks(&chapters, &config, &book_info.root)
+                .into_iter()
+                .map(|(chapter, path)| {
+                    let message = format!("notebook `{path}` is not in {}", config.colab_repo);
+                    Problem::new(chapter, message)
+                }),
+        );
+        problems.extend(
+            find_missing_chapter_refs(&chapters, &book_info)
+                .into_iter()
+                .map(|(chapter, path)| {
+                    Problem::new(chapter, format!("chapter `{path}` is not in the book"))
+                }),
+        );
+        let order = chapters
+            .iter()
+            .enumerate()
+            .map(|(index, ch)| (ch.name.as_str(), index))
+            .collect::<HashMap<_, _>>();
+        problems.sort_by_key(|problem| order.get(problem.chapter.as_str()).copied());
+        Ok(problems)
     }
-}
 
-fn replace_all(s: &str, num_words: usize) -> String {
-    // First replace all AIPR links
-    let aipr_replaced = replace_all_aipr_links(s, num_words);
+    /// Gettext template (`.pot`) of the text helpers add to chapters, for
+    /// translators to start or update the catalogs in `po_dir` from.
+    pub fn messages_template(&self) -> String {
+        i18n::pot(&messages())
+    }
 
-    // Then replace all Markdown links
-    replace_all_md_links(&aipr_replaced)
+    /// Statistics of every chapter of the book at `dir`, in book order,
+    /// without building it.
+    pub fn stats(&self, dir: &Path) -> Result<Vec<ChapterStats>> {
+        let (config, book_info, chapters) = load_book(dir)?;
+        let reading_time = ReadingTimeOptions::from_config(&config);
+        let stats = chapters
+            .iter()
+            .filter_map(|ch| {
+                let path = ch.path.as_ref()?;
+                let summary = book_info.chapters.get(path)?;
+                Some(ChapterStats {
+                    path: generated::markdown_path(path),
+                    title: summary.name.clone(),
+                    word_count: summary.num_words,
+                    reading_time: reading_time.minutes(&summary.word_count),
+                    code_lines: summary.word_count.code_lines,
+                    equations: summary.word_count.equations,
+                    cjk_chars: summary.word_count.cjk_chars,
+                    images: summary.word_count.images,
+                    external_links: find_md_links(&ch.content)
+                        .filter(|link| is_external_url(&link.url))
+                        .count(),
+                    notebook: summary.colab.clone(),
+                })
+            })
+            .collect();
+        Ok(stats)
+    }
 }
 
-fn replace_all_aipr_links(s: &str, num_words: usize) -> String {
-    // This implementation follows closely to the implementation of
-    // mdbook::preprocess::links::replace_all.
-    let mut previous_end_index = 0;
-    let mut replaced = String::new();
-
-    for link in find_aipr_links(s) {
-        replaced.push_str(&s[previous_end_index..link.start_index]);
-        let new_content = link.render(num_words).unwrap(); // todo: better error handling
-        replaced.push_str(&new_content);
-        previous_end_index = link.end_index;
+/// Loads the book at `dir`, with its chapters detached and indexed.
+fn load_book(dir: &Path) -> Result<(AIPRConfig, BookInfo, Vec<ChapterSource>)> {
+    let md = MDBook::load(dir)
+        .with_context(|| format!("Failed to load the book at {}", dir.display()))?;
+    let config = AIPRConfig::from_config(&md.config)?;
+    let mut book_info = BookInfo::new(&md.root, &md.config);
+    // in book order, unlike `for_each_mut` which visits sub-chapters first
+    let mut chapters = vec![];
+    for section in md.iter() {
+        match section {
+            BookItem::Chapter(ch) => chapters.push(ChapterSource {
+                part: book_info.parts.last().cloned(),
+                ..ChapterSource::take_from(&mut ch.clone())
+            }),
+            BookItem::PartTitle(title) => book_info.parts.push(title.clone()),
+            BookItem::Separator => {}
+        }
     }
-
-    replaced.push_str(&s[previous_end_index..]);
-    replaced
+    book_info.chapters = index_chapters(&chapters, &config);
+    Ok((config, book_info, chapters))
 }
 
-fn replace_all_md_links(s: &str) -> String {
-    let mut previous_end_index = 0;
-    let mut replaced = String::new();
-
-    for link in find_md_links(s) {
-        // Add text up to the current link
-        let prefix = &s[previous_end_index..link.start_index];
-        replaced.push_str(prefix);
-
-        // Check if the prefix ends with a backslash or exclamation mark
-        let last_char = prefix.chars().last();
-        let is_escaped = last_char == Some('\\') || last_char == Some('!');
+/// Statistics of a chapter, as reported by [`AIPRPreprocessor::stats`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChapterStats {
+    /// Path of the chapter, relative to the book's `src` directory.
+    pub path: String,
+    pub title: String,
+    pub word_count: usize,
+    /// Reading time in minutes.
+    pub reading_time: usize,
+    pub code_lines: usize,
+    /// Display-math blocks.
+    pub equations: usize,
+    /// Chinese, Japanese and Korean characters, counted in `word_count`.
+    pub cjk_chars: usize,
+    pub images: usize,
+    pub external_links: usize,
+    /// Notebook of the chapter's header, relative to `colab_root`.
+    pub notebook: Option<String>,
+}
 
-        if is_escaped {
-            // For escaped links, just add the original link text
-            replaced.push_str(&s[link.start_index..link.end_index]);
-        } else {
-            // For normal links, render as HTML
-            let new_content = link.render().unwrap();
-            replaced.push_str(&new_content);
+impl ChapterStats {
+    /// Breakdown of the chapter, as far as its statistics tell. Code words are
+    /// counted as prose, which they are in `word_count` with `count_code`.
+    fn counts(&self) -> WordCount {
+        WordCount {
+            prose_words: self.word_count.saturating_sub(self.cjk_chars),
+            cjk_chars: self.cjk_chars,
+            code_lines: self.code_lines,
+            equations: self.equations,
+            images: self.images,
+            ..Default::default()
         }
-
-        previous_end_index = link.end_index;
     }
-
-    replaced.push_str(&s[previous_end_index..]);
-    replaced
 }
 
-#[derive(PartialEq, Debug, Clone)]
-enum AIPRLinkType {
-    Header(AIPRHeaderSettings),
+/// Renders the `{{#aipr_header}}` of the chapter `stats` are of, as the
+/// preprocessor does with the book's `config`, for tooling producing pages of
+/// a book outside of mdBook. Text is translated into the configured
+/// `language` by its built-in and configured translations, and the date of
+/// the chapter's last commit isn't shown.
+pub fn render_header(
+    settings: &AIPRHeaderSettings,
+    stats: &ChapterStats,
+    config: &AIPRConfig,
+) -> Result<String> {
+    let language = config.language.as_deref().unwrap_or("en");
+    let mut book = BookInfo::default();
+    book.set_catalog(Catalog::new(
+        language,
+        &messages(),
+        None,
+        &config.translations,
+    ));
+    let chapter = ChapterInfo {
+        name: stats.title.clone(),
+        path: Some(PathBuf::from(&stats.path)),
+        source_path: Some(PathBuf::from(&stats.path)),
+        num_words: stats.word_count,
+        word_count: stats.counts(),
+        ..Default::default()
+    };
+    let ctx = HelperContext {
+        config,
+        book: &book,
+        chapter: &chapter,
+    };
+    settings.render(&ctx)
 }
 
+/// A problem found by [`AIPRPreprocessor::check`].
 #[derive(Debug, Clone, PartialEq)]
-struct AIPRHeaderSettings {
-    reading_time: bool,
-    submit_issue: bool,
-    colab: Option<String>,
+pub struct Problem {
+    /// Name of the chapter the problem is in.
+    pub chapter: String,
+    pub message: String,
 }
 
-impl Default for AIPRHeaderSettings {
-    fn default() -> Self {
+impl Problem {
+    fn new(chapter: &str, message: String) -> Self {
         Self {
-            reading_time: true,
-            submit_issue: true,
-            colab: None,
+            chapter: chapter.to_string(),
+            message,
         }
     }
 }
 
-fn _parse_param_str(param_str: &str) -> HashMap<String, String> {
-    param_str
-        .split(',')
-        .filter_map(|pair| {
-            pair.split_once('=')
-                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
-        })
-        .collect()
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.chapter, self.message)
+    }
 }
 
-impl AIPRHeaderSettings {
-    fn from_param_str(param_str: &str) -> Self {
-        let param_map = _parse_param_str(param_str);
-        let colab = param_map.get("colab").map(|s| s.to_owned());
-        let reading_time =
-            !matches!(param_map.get("reading_time"), Some(bool_str) if (bool_str == "false"));
-        let submit_issue =
-            !matches!(param_map.get("submit_issue"), Some(bool_str) if (bool_str == "false"));
+impl Preprocessor for AIPRPreprocessor {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn run(&self, ctx: &PreprocessorContext, mut book: Book) -> anyhow::Result<Book> {
+        let config = AIPRConfig::from_config(&ctx.config)?;
+        let mut book_info = BookInfo::new(&ctx.root, &ctx.config);
 
-        Self {
-            reading_time,
-            submit_issue,
-            colab,
+        // Non-HTML renderers get the helpers stripped rather than expanded into HTML.
+        if !config.expand_renderers.contains(&ctx.renderer) {
+            log::info!(
+                "Renderer `{}` not in `expand_renderers`, stripping AIPR helpers.",
+                ctx.renderer
+            );
+            book.for_each_mut(|section: &mut BookItem| {
+                if let BookItem::Chapter(ref mut ch) = *section {
+                    ch.content = strip_all_aipr_links(&ch.content);
+                }
+            });
+            return Ok(book);
+        }
+
+        // Chapters are detached from the book so they can be processed in
+        // parallel, then written back in the same `for_each_mut` order. Part
+        // titles are top-level, so are visited before the chapters under them.
+        let mut chapters = vec![];
+        book.for_each_mut(|section: &mut BookItem| match section {
+            BookItem::Chapter(ch) => chapters.push(ChapterSource {
+                part: book_info.parts.last().cloned(),
+                ..ChapterSource::take_from(ch)
+            }),
+            BookItem::PartTitle(title) => book_info.parts.push(title.clone()),
+            BookItem::Separator => {}
+        });
+        book_info.chapters = index_chapters(&chapters, &config);
+        book_info.helpers = self.helpers.clone();
+        check_helpers(&chapters, &config, &self.helpers)?;
+        if config.validate_notebooks {
+            validate_notebooks(&chapters, &config, &ctx.root)?;
+        }
+        if config.check_links {
+            check_links(&chapters, &config, &ctx.root)?;
+        }
+        let tag_registry = tag_chapters(&chapters, &book_info);
+        book_info.bibliography = Bibliography::from_file(&ctx.root.join(&config.bibliography))?;
+        let mut glossary = Glossary::from_file(&ctx.root.join(&config.glossary))?;
+        record_term_usage(&chapters, &book_info, &mut glossary);
+        book_info.glossary = glossary;
+        let language = config
+            .language
+            .as_deref()
+            .or(ctx.config.book.language.as_deref())
+            .unwrap_or("en");
+        let po_path = ctx.root.join(&config.po_dir).join(format!("{language}.po"));
+        let po = match po_path.exists() {
+            true => Some(
+                std::fs::read_to_string(&po_path)
+                    .with_context(|| format!("Failed to read catalog {}", po_path.display()))?,
+            ),
+            false => None,
+        };
+        if let Some(dir) = &config.partials_dir {
+            book_info.templates = Some(load_partials(&ctx.root.join(dir))?);
+        }
+        book_info.set_catalog(Catalog::new(
+            language,
+            &messages(),
+            po.as_deref(),
+            &config.translations,
+        ));
+        book_info.language = Some(language.to_string());
+        if let Some(path) = &config.footer_template {
+            let path = ctx.root.join(path);
+            let template = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read footer template {}", path.display()))?;
+            book_info.footer_template = Some(template);
+        }
+        // generated files only reach the rendered book through the HTML
+        // renderer's copy of `src`
+        if ctx.renderer == "html" {
+            if config.feed {
+                let dates = git::last_modified_under(&book_info.src_dir).unwrap_or_default();
+                write_feed(&chapters, &config, &book_info, &dates)?;
+            }
+            if config.metadata {
+                write_metadata(&chapters, &config, &book_info)?;
+            }
+            write_redirects(&config, &book_info)?;
+        }
+
+        let contents = chapters
+            .par_iter()
+            .map(|ch| {
+                process_chapter(ch, &config, &book_info).with_context(|| {
+                    let source_path = ch
+                        .source_path
+                        .as_ref()
+                        .map(|path| path.display().to_string())
+                        .unwrap_or_else(|| "<draft>".to_string());
+                    format!("Failed to process chapter `{}` ({source_path})", ch.name)
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut contents = contents.into_iter();
+        book.for_each_mut(|section: &mut BookItem| {
+            if let BookItem::Chapter(ref mut ch) = *section {
+                ch.content = contents.next().unwrap_or_default();
+            }
+        });
+        if config.sidebar_reading_time {
+            append_reading_times(&mut book, &config, &book_info);
+        }
+
+        if !book_info.glossary.is_empty() {
+            book.push_item(book_info.glossary.to_chapter());
+        }
+        if !tag_registry.is_empty() {
+            book.push_item(tag_registry.to_chapter());
         }
+
+        Ok(book)
+    }
+
+    /// Only the `expand_renderers` of the book in the working directory, the
+    /// book root mdBook runs in, are supported. mdBook doesn't ask for the
+    /// renderers listed in its own `renderers` key, which get the helpers
+    /// stripped instead unless they're also `expand_renderers`.
+    fn supports_renderer(&self, renderer: &str) -> bool {
+        self.supports(Path::new("."), renderer).unwrap_or_else(|e| {
+            log::warn!("Failed to read the book config, supporting HTML only: {e:#}");
+            renderer == "html"
+        })
     }
 }
 
-#[derive(PartialEq, Debug, Clone)]
-struct AIPRLink<'a> {
-    start_index: usize,
-    end_index: usize,
-    link_type: AIPRLinkType,
-    link_text: &'a str,
+/// The parts of a `Chapter` needed for processing, detached from the `Book`
+/// so that chapters can be processed in parallel.
+#[derive(Debug, Clone, Default)]
+struct ChapterSource {
+    name: String,
+    path: Option<PathBuf>,
+    source_path: Option<PathBuf>,
+    content: String,
+    /// Title of the part the chapter is in, if any.
+    part: Option<String>,
 }
 
-impl<'a> AIPRLink<'a> {
-    #[allow(dead_code)]
-    fn from_capture(cap: Captures<'a>) -> Option<AIPRLink<'a>> {
-        let link_type = match (cap.get(0), cap.get(1), cap.get(2)) {
-            (_, Some(typ), None) if typ.as_str() == "aipr_header" => {
-                Some(AIPRLinkType::Header(AIPRHeaderSettings::default()))
+impl ChapterSource {
+    /// Detach `ch`, moving its content out of the book.
+    fn take_from(ch: &mut Chapter) -> Self {
+        Self {
+            name: ch.name.clone(),
+            path: ch.path.clone(),
+            source_path: ch.source_path.clone(),
+            content: std::mem::take(&mut ch.content),
+            part: None,
+        }
+    }
+}
+
+/// Summarizes every chapter with a path, so helpers can refer to chapters
+/// other than the one being processed.
+fn index_chapters(
+    chapters: &[ChapterSource],
+    config: &AIPRConfig,
+) -> HashMap<PathBuf, ChapterSummary> {
+    chapters
+        .par_iter()
+        .filter_map(|ch| {
+            let front_matter = parse_front_matter(&ch.content);
+            let mut tags = front_matter
+                .get("tags")
+                .map(|tags| _parse_list(tags))
+                .unwrap_or_default();
+            let mut terms = vec![];
+            let mut colab = None;
+            let mut difficulty = None;
+            let mut has_header = false;
+            for link in find_all_aipr_links(&ch.content) {
+                match link.link_type {
+                    AIPRLinkType::Tags(settings) => tags.extend(settings.tags),
+                    AIPRLinkType::Term(settings) => terms.push(settings.term),
+                    AIPRLinkType::Header(settings) => {
+                        colab = colab.or(settings.colab);
+                        difficulty = difficulty.or(settings.difficulty);
+                        has_header = true;
+                    }
+                    _ => {}
+                }
             }
-            (_, Some(typ), Some(param_str)) if typ.as_str() == "aipr_header" => {
-                Some(AIPRLinkType::Header(AIPRHeaderSettings::from_param_str(
-                    param_str.as_str().trim(),
-                )))
+            let mut seen = std::collections::HashSet::new();
+            tags.retain(|tag| seen.insert(tag.clone()));
+            let mut header = AIPRHeaderSettings {
+                colab,
+                difficulty,
+                ..Default::default()
             }
-            _ => None,
-        };
+            .with_front_matter(&front_matter);
+            // only headers get the notebook of `colab_auto`
+            if has_header {
+                header = header.with_colab_auto(ch.source_path.as_deref(), config);
+            }
+            let colab = header
+                .colab_notebooks()
+                .first()
+                .map(|path| path.to_string());
+            let difficulty = header.difficulty;
+            let word_count = chapter_word_count(&ch.content);
+            let summary = ChapterSummary {
+                name: ch.name.clone(),
+                num_words: word_count.words(config.count_code),
+                word_count,
+                tags,
+                terms,
+                colab,
+                difficulty,
+                part: ch.part.clone(),
+            };
+            Some((ch.path.clone()?, summary))
+        })
+        .collect()
+}
 
-        link_type.and_then(|lnk_type| {
-            cap.get(0).map(|mat| AIPRLink {
-                start_index: mat.start(),
-                end_index: mat.end(),
-                link_type: lnk_type,
-                link_text: mat.as_str(),
+/// Writes the Atom feed of the `feed_max` most recently updated chapters, by
+/// date of their last commit in `dates`, to the `src` directory.
+fn write_feed(
+    chapters: &[ChapterSource],
+    config: &AIPRConfig,
+    book_info: &BookInfo,
+    dates: &HashMap<PathBuf, chrono::NaiveDate>,
+) -> Result<()> {
+    let Some(site_url) = &config.site_url else {
+        log::warn!("`feed` requires `site_url`, skipping the feed.");
+        return Ok(());
+    };
+    let mut entries = chapters
+        .iter()
+        .filter_map(|ch| {
+            let summary = parse_front_matter(&ch.content)
+                .remove("description")
+                .or_else(|| feed::summary(&ch.content));
+            Some(FeedEntry {
+                title: ch.name.clone(),
+                url: config.chapter_url(ch.path.as_ref()?)?,
+                summary,
+                updated: *dates.get(ch.source_path.as_ref()?)?,
             })
         })
-    }
-
-    fn render(&self, num_words: usize) -> anyhow::Result<String> {
-        match &self.link_type {
-            AIPRLinkType::Header(settings) => {
-                let mut handlebars = Handlebars::new();
-                // register template from const str and assign a name to it
-                handlebars
-                    .register_template_string("aipr_header", AIPR_HEADER_TEMPLATE)
-                    .unwrap();
-
-                // create data for rendering handlebar
-                let mut data = Map::new();
-                if let Some(colab_path) = &settings.colab {
-                    let colab_nb = ColabNB {
-                        path: colab_path.to_owned(),
-                    };
-                    data.insert("colab_nb".to_string(), to_json(colab_nb));
-                }
-                data.insert("submit_issue".to_string(), to_json(settings.submit_issue));
-                if settings.reading_time {
-                    let rt_in_mins = (num_words as f32 / WORDS_PER_MINUTE as f32).round();
-                    let rt = ReadingTime {
-                        value: format!("{:.0} min", rt_in_mins),
-                    };
-                    data.insert("reading_time".to_string(), to_json(rt));
-                }
+        .collect::<Vec<_>>();
+    // stable sort keeps book order among chapters updated the same day
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.updated));
+    entries.truncate(config.feed_max);
 
-                // render
-                let html_string = handlebars.render("aipr_header", &data)?;
+    let title = book_info.title.as_deref().unwrap_or("AI Pocket References");
+    let xml = feed::to_atom(title, site_url, &book_info.authors, &entries);
+    write_generated_file(&book_info.src_dir.join(feed::FEED_PATH), &xml)
+}
 
-                Ok(html_string)
-            }
-        }
+/// Checks that the notebooks linked from chapter headers exist in
+/// `colab_repo`. Missing notebooks are an error in strict mode, and a warning
+/// otherwise.
+fn validate_notebooks(chapters: &[ChapterSource], config: &AIPRConfig, root: &Path) -> Result<()> {
+    let missing = find_missing_notebooks(chapters, config, root);
+    for (chapter, path) in &missing {
+        log::warn!(
+            "Notebook `{path}` of `{chapter}` is not in {}.",
+            config.colab_repo
+        );
     }
+    if let Some((chapter, path)) = missing.first().filter(|_| config.strict) {
+        anyhow::bail!(
+            "Notebook `{path}` of `{chapter}` is not in {}",
+            config.colab_repo
+        );
+    }
+    Ok(())
 }
 
-#[derive(PartialEq, Debug, Clone, Serialize)]
-pub struct ColabNB {
-    path: String,
-}
+/// Names of the chapters linking to notebooks missing from `colab_repo`, and
+/// the notebooks' paths. Notebooks that couldn't be checked aren't missing.
+fn find_missing_notebooks<'a>(
+    chapters: &'a [ChapterSource],
+    config: &AIPRConfig,
+    root: &Path,
+) -> Vec<(&'a str, String)> {
+    let notebooks = chapters
+        .iter()
+        .flat_map(|ch| {
+            let front_matter = parse_front_matter(&ch.content);
+            find_all_aipr_links(&ch.content)
+                .into_iter()
+                .filter_map(|link| match link.link_type {
+                    AIPRLinkType::Header(settings) => Some(
+                        settings
+                            .with_front_matter(&front_matter)
+                            .with_colab_auto(ch.source_path.as_deref(), config),
+                    ),
+                    _ => None,
+                })
+                .flat_map(|settings| {
+                    let mut paths = settings
+                        .colab_notebooks()
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect::<Vec<_>>();
+                    paths.extend(
+                        [settings.kaggle, settings.binder, settings.sagemaker]
+                            .into_iter()
+                            .flatten(),
+                    );
+                    paths
+                })
+                // notebooks given by URL aren't in `colab_repo`
+                .filter(|path| !config::is_notebook_url(path))
+                .map(|path| (ch.name.as_str(), path))
+                .collect::<std::collections::BTreeSet<_>>()
+        })
+        .collect::<Vec<_>>();
 
-#[derive(PartialEq, Debug, Clone, Serialize)]
-pub struct ReadingTime {
-    value: String,
+    notebooks
+        .into_par_iter()
+        .filter(|(chapter, path)| match check_notebook(config, root, path) {
+            NotebookStatus::Found => false,
+            NotebookStatus::Missing => true,
+            NotebookStatus::Unknown(reason) => {
+                log::debug!("Couldn't check notebook `{path}` of `{chapter}`: {reason}");
+                false
+            }
+            NotebookStatus::Denied(reason) => {
+                log::warn!("Couldn't check notebook `{path}` of `{chapter}`: {reason}");
+                false
+            }
+        })
+        .collect()
 }
 
-struct AIPRLinkIter<'a>(CaptureMatches<'a, 'a>);
+/// Names of the chapters referencing chapters missing from the book, with
+/// `aipr_chapter`, `aipr_prereqs` or `aipr_related`, and the missing paths.
+fn find_missing_chapter_refs<'a>(
+    chapters: &'a [ChapterSource],
+    book_info: &BookInfo,
+) -> Vec<(&'a str, String)> {
+    let mut missing = vec![];
+    for ch in chapters {
+        for link in find_all_aipr_links(&ch.content) {
+            let paths = match link.link_type {
+                AIPRLinkType::Chapter(settings) => match settings.path.split_once('#') {
+                    Some((path, _)) => vec![path.to_string()],
+                    None => vec![settings.path],
+                },
+                AIPRLinkType::Prereqs(settings) => settings.prereqs,
+                AIPRLinkType::Related(settings) => settings.related,
+                _ => continue,
+            };
+            missing.extend(
+                paths
+                    .into_iter()
+                    .filter(|path| book_info.chapter(path).is_none())
+                    .map(|path| (ch.name.as_str(), path)),
+            );
+        }
+    }
+    missing
+}
 
-impl<'a> Iterator for AIPRLinkIter<'a> {
-    type Item = AIPRLink<'a>;
-    fn next(&mut self) -> Option<AIPRLink<'a>> {
-        for cap in &mut self.0 {
-            if let Some(inc) = AIPRLink::from_capture(cap) {
-                return Some(inc);
+/// Reports invalid helpers, such as unknown `aipr_*` names that would
+/// otherwise be left in the output, and malformed or unknown params that would
+/// otherwise be ignored. Invalid helpers are an error in strict mode, and a
+/// warning otherwise.
+fn check_helpers(
+    chapters: &[ChapterSource],
+    config: &AIPRConfig,
+    helpers: &HelperRegistry,
+) -> Result<()> {
+    for ch in chapters {
+        for problem in find_helper_problems(&ch.content, config, helpers) {
+            let message = format!("Invalid helper in `{}`: {problem}", ch.name);
+            if config.strict {
+                anyhow::bail!(message);
             }
+            log::warn!("{message}");
         }
-        None
     }
+    Ok(())
 }
 
-fn find_aipr_links(contents: &str) -> AIPRLinkIter<'_> {
-    // lazily compute following regex
-    // r"\\\{\{#.*\}\}|\{\{#([a-zA-Z0-9]+)\s*([^}]+)\}\}")?;
-    static RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(
-            r"(?x)              # insignificant whitespace mode
-        \\\{\{\#.*\}\}      # match escaped link
-        |                   # or
-        \{\{\s*             # link opening parens and whitespace
-        \#([a-zA-Z0-9_]+)   # link type
-        \s+                 # separating whitespace
-        ([^}]+)?            # link target path and space separated properties (optional)
-        \}\}                # link closing parens",
-        )
-        .unwrap()
-    });
+/// Requests the external links of every chapter and reports the dead ones per
+/// chapter. Dead links are an error in strict mode, and a warning otherwise.
+fn check_links(chapters: &[ChapterSource], config: &AIPRConfig, root: &Path) -> Result<()> {
+    if config.offline {
+        log::info!("`offline` is set, skipping link checks.");
+        return Ok(());
+    }
+    let today = chrono::Local::now().date_naive();
+    let mut cache = LinkCache::load(&root.join(&config.cache_dir));
+    let links = chapters
+        .iter()
+        .map(|ch| {
+            let urls = find_md_links(&ch.content)
+                // only web links with a scheme curl can request as is
+                .filter(|link| link.url.starts_with("https://") || link.url.starts_with("http://"))
+                .map(|link| link.url.trim().to_string())
+                .filter(|url| !link_check::host_matches(url, &config.check_links_exclude))
+                .collect::<std::collections::BTreeSet<_>>();
+            (ch.name.as_str(), urls)
+        })
+        .collect::<Vec<_>>();
 
-    AIPRLinkIter(RE.captures_iter(contents))
-}
+    let mut urls = links
+        .iter()
+        .flat_map(|(_, urls)| urls.iter().map(String::as_str))
+        .filter(|url| !cache.is_fresh(url, today, config.check_links_max_age))
+        .collect::<Vec<_>>();
+    urls.sort_unstable();
+    urls.dedup();
+    log::info!("Checking {} external links.", urls.len());
+    let statuses = urls
+        .par_iter()
+        .map(|url| (*url, link_check::check_url(url, config.check_links_timeout)))
+        .collect::<HashMap<_, _>>();
+    for (url, status) in &statuses {
+        if status.is_alive() {
+            cache.insert(url, today);
+        }
+    }
+    if let Err(e) = cache.save() {
+        log::warn!("Failed to cache checked links: {e:#}");
+    }
 
-#[derive(PartialEq, Debug, Clone)]
-struct MDLink<'a> {
-    start_index: usize,
-    end_index: usize,
-    text: &'a str,
-    url: &'a str,
+    let mut num_dead = 0;
+    for (chapter, urls) in &links {
+        let dead = urls
+            .iter()
+            .filter_map(|url| match statuses.get(url.as_str())? {
+                LinkStatus::Alive => None,
+                LinkStatus::Dead(code) => Some(format!("{url} ({code})")),
+                LinkStatus::Unreachable => Some(format!("{url} (unreachable)")),
+            })
+            .collect::<Vec<_>>();
+        if !dead.is_empty() {
+            log::warn!("Dead links in `{chapter}`: {}", dead.join(", "));
+            num_dead += dead.len();
+        }
+    }
+    anyhow::ensure!(
+        num_dead == 0 || !config.strict,
+        "Found {num_dead} dead external links"
+    );
+    Ok(())
 }
 
-impl<'a> MDLink<'a> {
-    #[allow(dead_code)]
-    fn from_capture(cap: Captures<'a>) -> Option<MDLink<'a>> {
-        let md_tuple = match (cap.get(0), cap.get(1), cap.get(2)) {
-            (_, Some(text_str), Some(url_str))
-                if (url_str.as_str().starts_with("https://")
-                    || url_str.as_str().starts_with("http://")) =>
-            {
-                Some((text_str.as_str(), url_str.as_str()))
-            }
-            _ => None,
-        };
+/// Metadata of a chapter, as exported to `aipr-metadata.json`.
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct ChapterMetadata {
+    path: String,
+    title: String,
+    url: Option<String>,
+    word_count: usize,
+    reading_time: usize,
+    code_lines: usize,
+    images: usize,
+    tags: Vec<String>,
+    colab: Option<String>,
+    difficulty: Option<String>,
+}
 
-        md_tuple.and_then(|(text, url)| {
-            cap.get(0).map(|mat| MDLink {
-                start_index: mat.start(),
-                end_index: mat.end(),
-                text,
-                url,
+/// Writes the metadata of every indexed chapter, in book order, to the `src`
+/// directory.
+fn write_metadata(
+    chapters: &[ChapterSource],
+    config: &AIPRConfig,
+    book_info: &BookInfo,
+) -> Result<()> {
+    let reading_time = ReadingTimeOptions::from_config(config);
+    let chapters = chapters
+        .iter()
+        .filter_map(|ch| {
+            let path = ch.path.as_ref()?;
+            let summary = book_info.chapters.get(path)?;
+            Some(ChapterMetadata {
+                path: generated::markdown_path(path),
+                title: summary.name.clone(),
+                url: config.chapter_url(path),
+                word_count: summary.num_words,
+                reading_time: reading_time.minutes(&summary.word_count),
+                code_lines: summary.word_count.code_lines,
+                images: summary.word_count.images,
+                tags: summary.tags.clone(),
+                colab: summary.colab.clone(),
+                difficulty: summary.difficulty.clone(),
             })
         })
-    }
-
-    #[allow(dead_code)]
-    fn render(&self) -> anyhow::Result<String> {
-        let mut handlebars = Handlebars::new();
+        .collect::<Vec<_>>();
+    let json = serde_json::to_string_pretty(&serde_json::json!({ "chapters": chapters }))?;
+    write_generated_file(&book_info.src_dir.join(METADATA_PATH), &format!("{json}\n"))
+}
 
-        // register template
-        handlebars
-            .register_template_string("md_link_expansion", MDLINK_TEMPLATE.trim())
-            .unwrap();
+/// Writes a page redirecting to the new chapter of every old path of
+/// `redirects` to the `src` directory, as `.html` files which mdBook copies
+/// to the rendered book, so links to renamed chapters keep working. The
+/// preprocessor runs before the HTML renderer clears its output directory,
+/// so can't write there.
+fn write_redirects(config: &AIPRConfig, book_info: &BookInfo) -> Result<()> {
+    for (old, new) in &config.redirects {
+        let old_path = Path::new(old.trim().trim_start_matches("./"));
+        anyhow::ensure!(
+            old_path
+                .components()
+                .all(|c| matches!(c, std::path::Component::Normal(_))),
+            "Redirected path `{old}` isn't relative to the `src` directory"
+        );
+        let Some(summary) = book_info.chapter(new) else {
+            anyhow::ensure!(
+                !config.strict,
+                "Chapter `{new}` redirected to from `{old}` is not in the book"
+            );
+            log::warn!("Chapter `{new}` redirected to from `{old}` is not in the book, skipping.");
+            continue;
+        };
+        if book_info.chapters.contains_key(old_path) {
+            anyhow::ensure!(
+                !config.strict,
+                "Redirected chapter `{}` is still in the book",
+                old_path.display()
+            );
+            log::warn!(
+                "Redirected chapter `{}` is still in the book, skipping its redirect.",
+                old_path.display()
+            );
+            continue;
+        }
 
         // create data for rendering handlebar
+        let new_html = Path::new(new.trim().trim_start_matches("./")).with_extension("html");
+        let url = format!(
+            "{}{}",
+            mdbook::utils::fs::path_to_root(old_path),
+            generated::markdown_path(&new_html)
+        );
         let mut data = Map::new();
-        data.insert("text".to_string(), to_json(self.text));
-        data.insert("url".to_string(), to_json(self.url));
+        data.insert("title".to_string(), to_json(&summary.name));
+        data.insert("url".to_string(), to_json(url));
 
         // render
-        let html_string = handlebars.render("md_link_expansion", &data)?;
-
-        Ok(html_string)
+        let html_string = book_info.templates().render("aipr_redirect", &data)?;
+        let path = book_info.src_dir.join(old_path.with_extension("html"));
+        write_generated_file(&path, &html_string)?;
     }
+    Ok(())
 }
 
-struct MDLinkIter<'a>(CaptureMatches<'a, 'a>);
+/// Appends the reading time of every indexed chapter to its name, e.g.
+/// `LoRA · 5 min`, so the sidebar shows it.
+fn append_reading_times(book: &mut Book, config: &AIPRConfig, book_info: &BookInfo) {
+    let reading_time = ReadingTimeOptions::from_config(config);
+    book.for_each_mut(|section: &mut BookItem| {
+        if let BookItem::Chapter(ref mut ch) = *section {
+            let Some(summary) = ch
+                .path
+                .as_ref()
+                .and_then(|path| book_info.chapters.get(path))
+            else {
+                return;
+            };
+            ch.name = format!("{} · {}", ch.name, reading_time.format(&summary.word_count));
+        }
+    });
+}
 
-impl<'a> Iterator for MDLinkIter<'a> {
-    type Item = MDLink<'a>;
-    fn next(&mut self) -> Option<MDLink<'a>> {
-        for cap in &mut self.0 {
-            if let Some(inc) = MDLink::from_capture(cap) {
-                return Some(inc);
-            }
+/// Registers the tags of every indexed chapter, in book order.
+fn tag_chapters(chapters: &[ChapterSource], book_info: &BookInfo) -> TagRegistry {
+    let mut registry = TagRegistry::default();
+    for ch in chapters {
+        let Some(path) = ch.path.as_ref() else {
+            continue;
+        };
+        let Some(summary) = book_info.chapters.get(path) else {
+            continue;
+        };
+        for tag in &summary.tags {
+            registry.insert(tag, &summary.name, path);
         }
-        None
     }
+    registry
 }
 
-fn find_md_links(contents: &str) -> MDLinkIter<'_> {
-    static RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(
-            r"(?x)
-            \[([^\]]*(?:\\.[^\]]*)*)\]    # link text in square brackets
-            \(([^)]*(?:\\.[^)]*)*)\)      # link URL in parentheses
-            ",
-        )
-        .unwrap()
-    });
-
-    MDLinkIter(RE.captures_iter(contents))
+/// Records the glossary terms used by every indexed chapter, in book order.
+fn record_term_usage(chapters: &[ChapterSource], book_info: &BookInfo, glossary: &mut Glossary) {
+    for ch in chapters {
+        let Some(path) = ch.path.as_ref() else {
+            continue;
+        };
+        let Some(summary) = book_info.chapters.get(path) else {
+            continue;
+        };
+        for term in &summary.terms {
+            glossary.record_usage(term, &summary.name, path);
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use anyhow::Result;
-    use rstest::*;
+fn process_chapter(
+    ch: &ChapterSource,
+    config: &AIPRConfig,
+    book_info: &BookInfo,
+) -> Result<String> {
+    // reuse the word count from the book index, drafts aren't indexed
+    let word_count = ch
+        .path
+        .as_ref()
+        .and_then(|path| book_info.chapters.get(path))
+        .map(|summary| summary.word_count)
+        .unwrap_or_else(|| chapter_word_count(&ch.content));
+    let links = find_all_aipr_links(&ch.content);
+    let front_matter = parse_front_matter(&ch.content);
+    // chapters composing their own footer, or opting out, get no default one
+    let default_footer = config.footer.enabled
+        && front_matter.get("footer").map(String::as_str) != Some("false")
+        && !links.iter().any(|link| {
+            matches!(
+                link.link_type,
+                AIPRLinkType::Footer(_) | AIPRLinkType::NoFooter(_)
+            )
+        });
+    let expand_abbreviations = !links.iter().any(
+        |link| matches!(&link.link_type, AIPRLinkType::Header(header) if !header.abbreviations),
+    );
+    let chapter_info = ChapterInfo {
+        name: ch.name.clone(),
+        path: ch.path.clone(),
+        source_path: ch.source_path.clone(),
+        num_words: word_count.words(config.count_code),
+        word_count,
+        headings: find_headings(&ch.content),
+        front_matter,
+        expand_abbreviations,
+        figures: number_figures(&links),
+        equations: number_equations(&links),
+        citations: number_citations(&links),
+        ..Default::default()
+    };
+    let render_ctx = HelperContext {
+        config,
+        book: book_info,
+        chapter: &chapter_info,
+    };
+    // front matter is metadata, not part of the rendered chapter
+    let body_start = find_front_matter_range(&ch.content).map_or(0, |range| range.end);
+    let mut content = replace_all(&ch.content[body_start..], &render_ctx)?;
 
-    #[fixture]
-    fn simple_book_content() -> String {
-        "{{ #aipr_header }} {{ #aipr_header colab=nlp/lora.ipynb }} Some random [text with](https://fake.io) and more text ..."
-            .to_string()
+    // list the references of chapters citing anything
+    if !chapter_info.citations.is_empty() {
+        content.push_str("\n\n");
+        content.push_str(&render_references(&render_ctx)?);
     }
 
-    #[rstest]
-    fn test_find_links_no_author_links() -> Result<()> {
-        let s = "Some random text without link...";
-        assert!(find_aipr_links(s).collect::<Vec<_>>() == vec![]);
-        assert!(find_md_links(s).collect::<Vec<_>>() == vec![]);
-        Ok(())
+    // add the quiz script and styles once, to chapters with quizzes
+    if links
+        .iter()
+        .any(|link| matches!(link.link_type, AIPRLinkType::Quiz(_)))
+    {
+        content.push_str(&format!(
+            "\n\n<style>\n{AIPR_QUIZ_STYLE}</style>\n<script>\n{AIPR_QUIZ_SCRIPT}</script>\n"
+        ));
     }
 
-    #[rstest]
-    fn test_find_links_empty_link() -> Result<()> {
-        let s = "Some random text with {{#colab  }} and {{}} {{#}}...";
-        println!("{:?}", find_aipr_links(s).collect::<Vec<_>>());
-        assert!(find_aipr_links(s).collect::<Vec<_>>() == vec![]);
-        Ok(())
+    // describe the chapter to search, with its tags as keywords
+    if config.meta_tags {
+        let header = links
+            .iter()
+            .find_map(|link| match &link.link_type {
+                AIPRLinkType::Header(settings) => Some(settings.as_ref().clone()),
+                _ => None,
+            })
+            .unwrap_or_default()
+            .with_front_matter(&chapter_info.front_matter);
+        let tags = ch
+            .path
+            .as_ref()
+            .and_then(|path| book_info.chapters.get(path))
+            .map(|summary| summary.tags.as_slice())
+            .unwrap_or_default();
+        let meta_tags = render_meta_tags(&render_ctx, tags, header.description.as_deref())?;
+        if !meta_tags.is_empty() {
+            content.push_str("\n\n");
+            content.push_str(&meta_tags);
+        }
     }
 
-    #[rstest]
-    fn test_find_links_unknown_link_type() -> Result<()> {
-        let s = "Some random \\[text with\\](test) {{#my_author ar.rs}} and {{#auth}} {{baz}} {{#bar}}...";
-        assert!(find_aipr_links(s).collect::<Vec<_>>() == vec![]);
-        assert!(find_md_links(s).collect::<Vec<_>>() == vec![]);
-        Ok(())
+    // describe the chapter to search engines
+    if config.structured_data {
+        let front_matter_authors = chapter_info
+            .front_matter
+            .get("authors")
+            .map(|authors| _parse_list(authors))
+            .unwrap_or_default();
+        let authors = links
+            .iter()
+            .find_map(|link| match &link.link_type {
+                AIPRLinkType::Authors(settings) if !settings.authors.is_empty() => {
+                    Some(settings.authors.as_slice())
+                }
+                _ => None,
+            })
+            .or_else(|| {
+                links.iter().find_map(|link| match &link.link_type {
+                    AIPRLinkType::Header(settings) if !settings.author.is_empty() => {
+                        Some(settings.author.as_slice())
+                    }
+                    _ => None,
+                })
+            })
+            .or((!front_matter_authors.is_empty()).then_some(front_matter_authors.as_slice()))
+            .unwrap_or(&book_info.authors);
+        content.push_str("\n\n");
+        content.push_str(&render_structured_data(&render_ctx, authors)?);
     }
 
-    #[rstest]
-    fn test_find_links_simple_author_links(simple_book_content: String) -> Result<()> {
-        let res = find_aipr_links(&simple_book_content[..]).collect::<Vec<_>>();
+    // add default footer with logo
+    if default_footer {
+        let footer = AIPRFooterSettings::default().render(&render_ctx)?;
+        content.push_str(&footer);
+    }
+
+    Ok(content)
+}
+
+/// Words, code and images of `content`, excluding its front matter and
+/// helper markup.
+fn chapter_word_count(content: &str) -> WordCount {
+    let mut excluded = find_all_aipr_links(content)
+        .iter()
+        .flat_map(AIPRLink::markup_ranges)
+        .collect::<Vec<_>>();
+    excluded.extend(find_front_matter_range(content));
+    word_count::count(content, &excluded)
+}
+
+/// Returns the byte range of a `---` delimited front matter block at the
+/// very start of `content`, if any.
+fn find_front_matter_range(content: &str) -> Option<Range<usize>> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let mut offset = content.len() - rest.len();
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if matches!(line.trim_end(), "---" | "...") {
+            return Some(0..offset);
+        }
+    }
+    None
+}
+
+/// Top-level `key: value` pairs of the chapter's front matter, with quotes
+/// stripped from values. Lists, either `[a, b]` or `- a` items, are joined
+/// with commas like helper list params. Nested mappings are skipped.
+pub(crate) fn parse_front_matter(content: &str) -> HashMap<String, String> {
+    let Some(range) = find_front_matter_range(content) else {
+        return HashMap::new();
+    };
+    let unquote = |value: &str| {
+        let value = value.trim();
+        value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .or_else(|| {
+                value
+                    .strip_prefix('\'')
+                    .and_then(|value| value.strip_suffix('\''))
+            })
+            .unwrap_or(value)
+            .to_string()
+    };
+
+    let mut front_matter = HashMap::new();
+    // key of the block list whose items are being read
+    let mut list_key: Option<String> = None;
+    for line in content[range].lines().skip(1) {
+        if let Some(item) = line.trim_start().strip_prefix("- ") {
+            if let Some(key) = &list_key {
+                let items: &mut String = front_matter.entry(key.clone()).or_default();
+                if !items.is_empty() {
+                    items.push(',');
+                }
+                items.push_str(&unquote(item));
+            }
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        list_key = None;
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_string();
+        let value = value.trim();
+        if value.is_empty() {
+            list_key = Some(key);
+            continue;
+        }
+        let value = match value
+            .strip_prefix('[')
+            .and_then(|value| value.strip_suffix(']'))
+        {
+            Some(items) => items
+                .split(',')
+                .map(unquote)
+                .filter(|item| !item.is_empty())
+                .collect::<Vec<_>>()
+                .join(","),
+            None => unquote(value),
+        };
+        front_matter.insert(key, value);
+    }
+    front_matter
+}
+
+/// Book-level metadata available to helpers.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BookInfo {
+    pub(crate) title: Option<String>,
+    pub(crate) authors: Vec<String>,
+    /// The book root, that configured paths are relative to.
+    pub(crate) root: PathBuf,
+    /// The book's `src` directory, that chapter source paths are relative to.
+    pub(crate) src_dir: PathBuf,
+    /// Summaries of the book's chapters, keyed by path relative to `src_dir`.
+    pub(crate) chapters: HashMap<PathBuf, ChapterSummary>,
+    /// Titles of the book's parts, in book order.
+    pub(crate) parts: Vec<String>,
+    pub(crate) glossary: Glossary,
+    pub(crate) bibliography: Bibliography,
+    /// Source of the configured `footer_template`.
+    pub(crate) footer_template: Option<String>,
+    /// The built-in templates with the partials of the configured
+    /// `partials_dir`, if any.
+    pub(crate) templates: Option<Handlebars<'static>>,
+    /// Translations of the text helpers add into the book's language.
+    pub(crate) catalog: Catalog,
+    /// Language of the text helpers add, e.g. `fr`.
+    pub(crate) language: Option<String>,
+    /// Helpers added to the preprocessor.
+    pub(crate) helpers: HelperRegistry,
+}
+
+impl BookInfo {
+    fn new(root: &Path, config: &Config) -> Self {
+        Self {
+            title: config.book.title.clone(),
+            authors: config.book.authors.clone(),
+            root: root.to_path_buf(),
+            src_dir: root.join(&config.book.src),
+            chapters: HashMap::new(),
+            parts: vec![],
+            glossary: Glossary::default(),
+            bibliography: Bibliography::default(),
+            footer_template: None,
+            templates: None,
+            catalog: Catalog::default(),
+            language: None,
+            helpers: HelperRegistry::default(),
+        }
+    }
+
+    /// Templates chapters are rendered with.
+    pub(crate) fn templates(&self) -> &Handlebars<'static> {
+        self.templates.as_ref().unwrap_or(&TEMPLATES)
+    }
+
+    /// Sets the book's catalog, also translating the `t` helper of its
+    /// templates.
+    pub(crate) fn set_catalog(&mut self, catalog: Catalog) {
+        let mut templates = self.templates.take().unwrap_or_else(|| TEMPLATES.clone());
+        templates.register_helper("t", Box::new(TranslateHelper(catalog.clone())));
+        self.templates = Some(templates);
+        self.catalog = catalog;
+    }
+
+    /// Summary of the chapter at `path`, relative to the `src` directory.
+    pub(crate) fn chapter(&self, path: &str) -> Option<&ChapterSummary> {
+        self.chapters
+            .get(Path::new(path.trim().trim_start_matches("./")))
+    }
+}
+
+/// What helpers know about chapters other than the one being processed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChapterSummary {
+    pub(crate) name: String,
+    pub(crate) num_words: usize,
+    pub(crate) word_count: WordCount,
+    pub(crate) tags: Vec<String>,
+    pub(crate) terms: Vec<String>,
+    /// Notebook path of the chapter's header, relative to `colab_root`.
+    pub(crate) colab: Option<String>,
+    /// Difficulty level of the chapter's header.
+    pub(crate) difficulty: Option<String>,
+    /// Title of the part the chapter is in, if any.
+    pub(crate) part: Option<String>,
+}
+
+/// Chapter-level metadata available to helpers.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChapterInfo {
+    pub(crate) name: String,
+    pub(crate) path: Option<PathBuf>,
+    pub(crate) source_path: Option<PathBuf>,
+    pub(crate) num_words: usize,
+    pub(crate) word_count: WordCount,
+    pub(crate) headings: Vec<Heading>,
+    /// Top-level values of the chapter's front matter.
+    pub(crate) front_matter: HashMap<String, String>,
+    /// Whether configured abbreviations are wrapped in `<abbr>` tags.
+    pub(crate) expand_abbreviations: bool,
+    /// Figure ids mapped to their number within the chapter.
+    pub(crate) figures: HashMap<String, usize>,
+    /// Equation labels mapped to their number within the chapter.
+    pub(crate) equations: HashMap<String, usize>,
+    /// Number of equations rendered so far. Equations are rendered in order
+    /// of appearance, so this numbers them as `number_equations` does.
+    pub(crate) equation_counter: Cell<usize>,
+    /// Cited keys, in order of first citation.
+    pub(crate) citations: Vec<String>,
+    /// Number of quizzes rendered so far, used to give their radio buttons
+    /// unique names.
+    pub(crate) quiz_counter: Cell<usize>,
+}
+
+/// Numbers the chapter's equations in order of appearance, mapping the
+/// labelled ones to their number for `aipr_eqref`.
+fn number_equations(links: &[AIPRLink]) -> HashMap<String, usize> {
+    let equations = links.iter().filter_map(|link| match &link.link_type {
+        AIPRLinkType::Equation(equation) => Some(equation),
+        _ => None,
+    });
+    let mut labels = HashMap::new();
+    for (index, equation) in equations.enumerate() {
+        let Some(label) = &equation.label else {
+            continue;
+        };
+        if labels.insert(label.clone(), index + 1).is_some() {
+            log::warn!("Equation label `{label}` is used more than once.");
+        }
+    }
+    labels
+}
+
+/// Orders the chapter's cited keys by first citation, which numbers them.
+fn number_citations(links: &[AIPRLink]) -> Vec<String> {
+    let mut citations: Vec<String> = vec![];
+    for link in links {
+        let AIPRLinkType::Cite(cite) = &link.link_type else {
+            continue;
+        };
+        for key in &cite.keys {
+            if !citations.contains(key) {
+                citations.push(key.clone());
+            }
+        }
+    }
+    citations
+}
+
+/// Numbers the chapter's figures in order of appearance, so that references
+/// can precede the figure they point to.
+fn number_figures(links: &[AIPRLink]) -> HashMap<String, usize> {
+    let mut figures = HashMap::new();
+    for link in links {
+        let AIPRLinkType::Figure(figure) = &link.link_type else {
+            continue;
+        };
+        let number = figures.len() + 1;
+        if figures.insert(figure.id.clone(), number).is_some() {
+            log::warn!("Figure id `{}` is used more than once.", figure.id);
+        }
+    }
+    figures
+}
+
+/// An ATX heading of a chapter, with the anchor id mdBook gives it.
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct Heading {
+    pub(crate) level: usize,
+    text: String,
+    id: String,
+}
+
+/// Extracts the ATX headings outside of fenced code blocks in `content`.
+/// Ids follow mdBook's scheme, including custom `{#id}` attributes and the
+/// numeric suffixes given to duplicate headings.
+pub(crate) fn find_headings(content: &str) -> Vec<Heading> {
+    static CUSTOM_ID_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"\s*\{#([^\s}]+)[^}]*\}\s*$").unwrap());
+
+    let code_ranges = find_code_block_ranges(content);
+    let mut id_counter = HashMap::new();
+    let mut headings = vec![];
+    let mut line_start = 0;
+
+    for line in content.split_inclusive('\n') {
+        let index = line_start;
+        line_start += line.len();
+        if in_ranges(&code_ranges, index) {
+            continue;
+        }
+
+        let trimmed = line.trim_end().trim_start_matches(' ');
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        let rest = &trimmed[level..];
+        if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+            continue;
+        }
+
+        // strip the optional closing sequence of `#`s
+        let text = rest.trim();
+        let text = match text.trim_end_matches('#') {
+            stripped if stripped.is_empty() || stripped.ends_with(' ') => stripped.trim_end(),
+            _ => text,
+        };
+        let (text, id) = match CUSTOM_ID_RE.captures(text) {
+            Some(cap) => {
+                let id = cap[1].to_string();
+                (text[..cap.get(0).unwrap().start()].to_string(), id)
+            }
+            None => {
+                let id = mdbook::utils::unique_id_from_content(text, &mut id_counter);
+                (text.to_string(), id)
+            }
+        };
+        let text = text.replace(['`', '*'], "");
+
+        headings.push(Heading { level, text, id });
+    }
+    headings
+}
+
+/// A helper or Markdown link found in chapter content.
+#[derive(PartialEq, Debug, Clone)]
+enum ContentMatch<'a> {
+    // boxed, as helper settings are much larger than a link
+    Helper(Box<AIPRLink<'a>>),
+    Link(MDLink<'a>),
+}
+
+impl ContentMatch<'_> {
+    fn start_index(&self) -> usize {
+        match self {
+            Self::Helper(link) => link.start_index,
+            Self::Link(link) => link.start_index,
+        }
+    }
+
+    fn end_index(&self) -> usize {
+        match self {
+            Self::Helper(link) => link.end_index,
+            Self::Link(link) => link.end_index,
+        }
+    }
+}
+
+/// Finds helpers and Markdown links in `contents`, ordered by position.
+/// Matches are located against the original content, so their offsets are
+/// consistent with one another.
+fn find_content_matches(contents: &str) -> impl Iterator<Item = ContentMatch<'_>> {
+    let mut helpers = find_aipr_links(contents).peekable();
+    let mut links = find_md_links(contents).peekable();
+
+    std::iter::from_fn(move || match (helpers.peek(), links.peek()) {
+        (Some(helper), Some(link)) if link.start_index < helper.start_index => {
+            links.next().map(ContentMatch::Link)
+        }
+        (Some(_), _) => helpers
+            .next()
+            .map(|helper| ContentMatch::Helper(Box::new(helper))),
+        (None, _) => links.next().map(ContentMatch::Link),
+    })
+}
+
+pub(crate) fn replace_all(s: &str, ctx: &HelperContext) -> Result<String> {
+    // This implementation follows closely to the implementation of
+    // mdbook::preprocess::links::replace_all, replacing helpers and
+    // Markdown links in a single pass.
+    let mut previous_end_index = 0;
+    let mut replaced = String::with_capacity(s.len());
+
+    let content_matches = find_content_matches(s).collect::<Vec<_>>();
+    let mut abbreviations = match ctx.chapter.expand_abbreviations {
+        true => {
+            let mut excluded = find_code_ranges(s);
+            excluded.extend(find_front_matter_range(s));
+            excluded.extend(
+                content_matches
+                    .iter()
+                    .map(|m| m.start_index()..m.end_index()),
+            );
+            find_abbreviations(s, &ctx.config.abbreviations, &excluded)
+        }
+        false => vec![],
+    }
+    .into_iter()
+    .peekable();
+
+    for content_match in content_matches {
+        let (start_index, end_index) = (content_match.start_index(), content_match.end_index());
+        if start_index < previous_end_index {
+            // overlaps the previous replacement
+            continue;
+        }
+        push_text(
+            &mut replaced,
+            s,
+            previous_end_index..start_index,
+            &mut abbreviations,
+            ctx,
+        )?;
+
+        match content_match {
+            ContentMatch::Helper(link) => {
+                let new_content = link.render(ctx).with_context(|| {
+                    format!(
+                        "Failed to render `{}` at byte offset {}",
+                        link.link_text, link.start_index
+                    )
+                })?;
+                replaced.push_str(&new_content);
+            }
+            ContentMatch::Link(link) => {
+                let is_excluded = link_check::host_matches(&link.url, &ctx.config.new_tab_exclude)
+                    || (!link.is_external() && !ctx.config.rewrite_mailto);
+
+                if is_excluded {
+                    // For links excluded from rewriting, just add the
+                    // original link text
+                    replaced.push_str(&s[link.start_index..link.end_index]);
+                } else {
+                    // For normal links, render as HTML
+                    let new_content = link.render(ctx).with_context(|| {
+                        format!(
+                            "Failed to render link `{}` at byte offset {}",
+                            &s[link.start_index..link.end_index],
+                            link.start_index
+                        )
+                    })?;
+                    replaced.push_str(&new_content);
+                }
+            }
+        }
+
+        previous_end_index = end_index;
+    }
+
+    push_text(
+        &mut replaced,
+        s,
+        previous_end_index..s.len(),
+        &mut abbreviations,
+        ctx,
+    )?;
+    Ok(replaced)
+}
+
+/// Pushes the text of `s` in `range` to `replaced`, wrapping the
+/// abbreviations that fall within it in `<abbr>` tags.
+fn push_text<'a>(
+    replaced: &mut String,
+    s: &str,
+    range: Range<usize>,
+    abbreviations: &mut std::iter::Peekable<impl Iterator<Item = Abbreviation<'a>>>,
+    ctx: &HelperContext,
+) -> Result<()> {
+    let mut start = range.start;
+    while let Some(abbr) = abbreviations.next_if(|abbr| abbr.range.end <= range.end) {
+        if abbr.range.start < start {
+            continue;
+        }
+        replaced.push_str(&s[start..abbr.range.start]);
+        replaced.push_str(&ctx.render("aipr_abbr", &abbr)?);
+        start = abbr.range.end;
+    }
+    replaced.push_str(&s[start..range.end]);
+    Ok(())
+}
+
+fn strip_all_aipr_links(s: &str) -> String {
+    let mut previous_end_index = 0;
+    let mut stripped = String::new();
+
+    for link in find_aipr_links(s) {
+        stripped.push_str(&s[previous_end_index..link.start_index]);
+        // block helpers keep their content, except for quiz questions
+        match link.body {
+            Some(_) if matches!(link.link_type, AIPRLinkType::Quiz(_)) => {}
+            Some(body) => stripped.push_str(&strip_all_aipr_links(&s[body])),
+            None if link.link_type == AIPRLinkType::Escaped => {
+                stripped.push_str(&link.link_text[1..]);
+            }
+            None => {}
+        }
+        previous_end_index = link.end_index;
+    }
+
+    stripped.push_str(&s[previous_end_index..]);
+    stripped
+}
+
+/// A schema.org `TechArticle` JSON-LD block describing the chapter, written by
+/// `authors`. Dates come from the git history of the chapter's source file.
+fn render_structured_data(ctx: &HelperContext, authors: &[String]) -> anyhow::Result<String> {
+    let mut article = Map::new();
+    article.insert("@context".to_string(), to_json("https://schema.org"));
+    article.insert("@type".to_string(), to_json("TechArticle"));
+    article.insert("headline".to_string(), to_json(&ctx.chapter.name));
+    if !authors.is_empty() {
+        let authors = authors
+            .iter()
+            .map(|name| serde_json::json!({ "@type": "Person", "name": name }))
+            .collect::<Vec<_>>();
+        article.insert("author".to_string(), to_json(authors));
+    }
+    let source_file = ctx.chapter_source_file();
+    let dates = [
+        (
+            "datePublished",
+            source_file.as_deref().and_then(git::first_committed),
+        ),
+        (
+            "dateModified",
+            source_file.as_deref().and_then(git::last_modified),
+        ),
+    ];
+    for (key, date) in dates {
+        if let Some(date) = date {
+            article.insert(
+                key.to_string(),
+                to_json(date.format("%Y-%m-%d").to_string()),
+            );
+        }
+    }
+    article.insert("wordCount".to_string(), to_json(ctx.chapter.num_words));
+    if let Some(url) = ctx
+        .chapter
+        .path
+        .as_ref()
+        .and_then(|path| ctx.config.chapter_url(path))
+    {
+        article.insert("url".to_string(), to_json(url));
+    }
+    if let Some(title) = &ctx.book.title {
+        article.insert(
+            "isPartOf".to_string(),
+            serde_json::json!({ "@type": "Book", "name": title }),
+        );
+    }
+    if !ctx.config.publisher.is_empty() {
+        article.insert(
+            "publisher".to_string(),
+            serde_json::json!({ "@type": "Organization", "name": ctx.config.publisher }),
+        );
+    }
+
+    // `</` would close the script element early
+    let json = serde_json::to_string(&article)?.replace("</", "<\\/");
+    Ok(format!(
+        "<script type=\"application/ld+json\">{json}</script>\n"
+    ))
+}
+
+/// `keywords` and `description` meta tags of the chapter, empty if it has
+/// neither tags nor a description.
+fn render_meta_tags(
+    ctx: &HelperContext,
+    tags: &[String],
+    description: Option<&str>,
+) -> anyhow::Result<String> {
+    let mut data = Map::new();
+    data.insert("keywords".to_string(), to_json(tags.join(", ")));
+    data.insert("description".to_string(), to_json(description));
+    ctx.render("aipr_meta", &data)
+}
+
+#[derive(PartialEq, Debug, Clone)]
+struct AIPRLink<'a> {
+    start_index: usize,
+    end_index: usize,
+    link_type: AIPRLinkType,
+    link_text: &'a str,
+    /// Range of the content wrapped by a block helper.
+    body: Option<Range<usize>>,
+}
+
+impl<'a> AIPRLink<'a> {
+    #[allow(dead_code)]
+    fn from_capture(cap: Captures<'a>) -> Option<AIPRLink<'a>> {
+        let link_type = match (cap.get(0), cap.get(1), cap.get(2)) {
+            // other preprocessors' escaped helpers are theirs to unescape
+            (Some(mat), None, None) if mat.as_str().starts_with("\\{{#aipr_") => {
+                Some(AIPRLinkType::Escaped)
+            }
+            (_, Some(typ), param_str) => {
+                let params = param_str.map(|p| p.as_str().trim());
+                match HelperRegistry::built_ins().built_in(typ.as_str()) {
+                    Some(helper) => (helper.parse)(params.unwrap_or_default()),
+                    None if typ.as_str().starts_with("aipr_") => Some(AIPRLinkType::Custom {
+                        name: typ.as_str().to_string(),
+                        params: params.unwrap_or_default().to_string(),
+                    }),
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+
+        link_type.and_then(|lnk_type| {
+            cap.get(0).map(|mat| AIPRLink {
+                start_index: mat.start(),
+                end_index: mat.end(),
+                link_type: lnk_type,
+                link_text: mat.as_str(),
+                body: None,
+            })
+        })
+    }
+
+    /// Ranges of the helper's own markup, i.e. excluding a block's content.
+    #[allow(clippy::single_range_in_vec_init)]
+    fn markup_ranges(&self) -> Vec<Range<usize>> {
+        match &self.body {
+            Some(body) => vec![self.start_index..body.start, body.end..self.end_index],
+            None => vec![self.start_index..self.end_index],
+        }
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        match &self.link_type {
+            AIPRLinkType::Header(settings) => settings
+                .clone()
+                .with_front_matter(&ctx.chapter.front_matter)
+                .with_colab_auto(ctx.chapter.source_path.as_deref(), ctx.config)
+                .render(ctx),
+            AIPRLinkType::Footer(settings) => settings.render(ctx),
+            AIPRLinkType::Authors(settings) => settings.render(ctx),
+            AIPRLinkType::Citation(settings) => settings.render(ctx),
+            AIPRLinkType::Contributors(settings) => settings.render(ctx),
+            AIPRLinkType::Toc(settings) => settings.render(ctx),
+            AIPRLinkType::Related(settings) => settings.render(ctx),
+            AIPRLinkType::Prereqs(settings) => settings.render(ctx),
+            AIPRLinkType::Tags(settings) => settings.render(ctx),
+            AIPRLinkType::Term(settings) => settings.render(ctx),
+            AIPRLinkType::Link(settings) => settings.render(ctx),
+            AIPRLinkType::Callout(settings) => settings.render(ctx),
+            AIPRLinkType::Figure(settings) => settings.render(ctx),
+            AIPRLinkType::Ref(settings) => settings.render(ctx),
+            AIPRLinkType::Equation(settings) => settings.render(ctx),
+            AIPRLinkType::EqRef(settings) => settings.render(ctx),
+            AIPRLinkType::Chapter(settings) => settings.render(ctx),
+            AIPRLinkType::Cite(settings) => settings.render(ctx),
+            AIPRLinkType::Video(settings) => settings.render(ctx),
+            AIPRLinkType::Quiz(settings) => settings.render(ctx),
+            AIPRLinkType::Code(settings) => settings.render(ctx),
+            AIPRLinkType::Hf(settings) => settings.render(ctx),
+            AIPRLinkType::Arxiv(settings) => settings.render(ctx),
+            AIPRLinkType::Doi(settings) => settings.render(ctx),
+            AIPRLinkType::Dataset(settings) => settings.render(ctx),
+            AIPRLinkType::ModelCard(settings) => settings.render(ctx),
+            AIPRLinkType::Benchmark(settings) => settings.render(ctx),
+            AIPRLinkType::SeriesNav(settings) => settings.render(ctx),
+            AIPRLinkType::License(settings) => settings.render(ctx),
+            AIPRLinkType::Changelog(settings) => settings.render(ctx),
+            AIPRLinkType::TotalReadingTime(settings) => settings.render(ctx),
+            AIPRLinkType::NoFooter(settings) => settings.render(ctx),
+            AIPRLinkType::Comments(settings) => settings.render(ctx),
+            AIPRLinkType::VersionBanner(settings) => settings.render(ctx),
+            AIPRLinkType::LastUpdated(settings) => settings.render(ctx),
+            AIPRLinkType::Escaped => Ok(self.link_text[1..].to_string()),
+            AIPRLinkType::Custom { name, params } => match ctx.book.helpers.parse(name, params) {
+                Some(helper) => helper?.render(ctx),
+                // helpers that weren't added are left as they are
+                None => Ok(self.link_text.to_string()),
+            },
+        }
+    }
+}
+
+struct AIPRLinkIter<'a> {
+    contents: &'a str,
+    captures: CaptureMatches<'a, 'a>,
+    code_ranges: Vec<Range<usize>>,
+    /// End of the last block helper; helpers within it belong to its content.
+    block_end: usize,
+}
+
+impl<'a> Iterator for AIPRLinkIter<'a> {
+    type Item = AIPRLink<'a>;
+    fn next(&mut self) -> Option<AIPRLink<'a>> {
+        for cap in &mut self.captures {
+            if let Some(mut inc) = AIPRLink::from_capture(cap) {
+                if inc.start_index < self.block_end || in_ranges(&self.code_ranges, inc.start_index)
+                {
+                    continue;
+                }
+                if let Some(name) = inc.link_type.block_name() {
+                    match find_block_close(self.contents, name, inc.end_index, &self.code_ranges) {
+                        Some(close) => {
+                            let body = inc.end_index..close.start;
+                            inc.link_type.set_body(&self.contents[body.clone()]);
+                            inc.body = Some(body);
+                            inc.end_index = close.end;
+                            self.block_end = close.end;
+                        }
+                        None => {
+                            log::warn!("`{}` is never closed by `{{{{/{name}}}}}`.", inc.link_text)
+                        }
+                    }
+                }
+                return Some(inc);
+            }
+        }
+        None
+    }
+}
+
+/// Finds the `{{/name}}` tag closing a block helper opened before `from`,
+/// skipping nested blocks of the same name and tags in code.
+fn find_block_close(
+    contents: &str,
+    name: &str,
+    from: usize,
+    code_ranges: &[Range<usize>],
+) -> Option<Range<usize>> {
+    static TAG_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"\{\{\s*([#/])\s*([a-zA-Z0-9_]+)[^}]*\}\}").unwrap());
+
+    let mut depth = 0;
+    for cap in TAG_RE.captures_iter(&contents[from..]) {
+        let mat = cap.get(0)?;
+        let range = from + mat.start()..from + mat.end();
+        if &cap[2] != name
+            || in_ranges(code_ranges, range.start)
+            || contents[..range.start].ends_with('\\')
+        {
+            continue;
+        }
+        match &cap[1] {
+            "#" => depth += 1,
+            _ if depth == 0 => return Some(range),
+            _ => depth -= 1,
+        }
+    }
+    None
+}
+
+fn find_aipr_links(contents: &str) -> AIPRLinkIter<'_> {
+    find_aipr_links_excluding(contents, find_code_ranges(contents))
+}
+
+/// Finds helpers in `contents` including those nested in block helpers,
+/// ordered by position.
+fn find_all_aipr_links(contents: &str) -> Vec<AIPRLink<'_>> {
+    let mut links = vec![];
+    for link in find_aipr_links(contents) {
+        let body = link.body.clone();
+        links.push(link);
+        if let Some(body) = body {
+            links.extend(
+                find_all_aipr_links(&contents[body.clone()])
+                    .into_iter()
+                    .map(|mut nested| {
+                        nested.start_index += body.start;
+                        nested.end_index += body.start;
+                        nested.body = nested
+                            .body
+                            .map(|range| range.start + body.start..range.end + body.start);
+                        nested
+                    }),
+            );
+        }
+    }
+    links
+}
+
+/// Problems with the `aipr_*` helpers in `contents`, outside code and escaped
+/// helpers, in order: unknown names, with the closest known name, unclosed
+/// helpers, and malformed or unknown params.
+fn find_helper_problems(
+    contents: &str,
+    config: &AIPRConfig,
+    helpers: &HelperRegistry,
+) -> Vec<String> {
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"(?x)
+            (\\)?                             # escape
+            \{\{\s*\#(aipr_[a-zA-Z0-9_]*)   # helper name
+            ([^}]*)                             # params
+            (\}\})?                           # closing parens",
+        )
+        .unwrap()
+    });
+
+    let code_ranges = find_code_ranges(contents);
+    let mut problems = vec![];
+    for cap in RE.captures_iter(contents) {
+        let (Some(name), Some(params)) = (cap.get(2), cap.get(3)) else {
+            continue;
+        };
+        let in_code = code_ranges
+            .iter()
+            .any(|range| range.contains(&name.start()));
+        if cap.get(1).is_some() || in_code {
+            continue;
+        }
+        let name = name.as_str();
+        if !helpers.contains(name) {
+            problems.push(format!(
+                "unknown helper `{name}`, did you mean `{}`?",
+                closest_helper(name, helpers)
+            ));
+        } else if cap.get(4).is_none() {
+            problems.push(format!("unclosed helper `{name}`"));
+        } else if let Some(helper) = helpers.built_in(name) {
+            problems.extend(find_param_problems(helper, params.as_str(), config));
+        }
+    }
+    problems
+}
+
+/// Number of single-character insertions, deletions and substitutions
+/// turning `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_char != *b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// The known helper name closest to `name`, built in or added.
+fn closest_helper(name: &str, helpers: &HelperRegistry) -> &'static str {
+    helpers
+        .names()
+        .min_by_key(|known| edit_distance(name, known))
+        .unwrap_or("aipr_header")
+}
+
+fn find_aipr_links_excluding(contents: &str, code_ranges: Vec<Range<usize>>) -> AIPRLinkIter<'_> {
+    // lazily compute following regex
+    // r"\\\{\{#[^}]*\}\}|\{\{#([a-zA-Z0-9]+)\s*([^}]+)\}\}")?;
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"(?x)              # insignificant whitespace mode
+        \\\{\{\#[^}]*\}\}   # match escaped link
+        |                   # or
+        \{\{\s*             # link opening parens and whitespace
+        \#([a-zA-Z0-9_]+)   # link type
+        \s*                 # separating whitespace
+        ([^}]+)?            # link target path and space separated properties (optional)
+        \}\}                # link closing parens",
+        )
+        .unwrap()
+    });
+
+    AIPRLinkIter {
+        contents,
+        captures: RE.captures_iter(contents),
+        code_ranges,
+        block_end: 0,
+    }
+}
+
+/// An external or `mailto:` Markdown link: inline, reference-style or an
+/// autolink.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub(crate) struct MDLink<'a> {
+    pub(crate) start_index: usize,
+    pub(crate) end_index: usize,
+    /// Markdown source of the link text.
+    pub(crate) text: &'a str,
+    pub(crate) url: Cow<'a, str>,
+    pub(crate) title: Cow<'a, str>,
+}
+
+impl<'a> MDLink<'a> {
+    /// Whether the link leaves the site, rather than e.g. opening a mail client.
+    fn is_external(&self) -> bool {
+        is_external_url(&self.url)
+    }
+
+    pub(crate) fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        // create data for rendering handlebar
+        let mut data = Map::new();
+        data.insert("text".to_string(), to_json(self.text));
+        data.insert("url".to_string(), to_json(&self.url));
+        if !self.title.is_empty() {
+            data.insert("title".to_string(), to_json(&self.title));
+        }
+        if self.is_external() {
+            data.insert("new_tab".to_string(), to_json(true));
+            data.insert("rel".to_string(), to_json(ctx.config.link_rel(&self.url)));
+            data.insert("icon".to_string(), to_json(ctx.config.external_link_icon));
+        }
+
+        // render
+        let html_string = ctx.render("md_link_expansion", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+/// Whether `url` is an absolute `http`, `https` or `ftp` URL, or a
+/// protocol-relative `//host/path` one.
+fn is_external_url(url: &str) -> bool {
+    ["https://", "http://", "ftp://", "//"]
+        .iter()
+        .any(|prefix| url.starts_with(prefix))
+}
+
+fn cow_str(s: CowStr<'_>) -> Cow<'_, str> {
+    match s {
+        CowStr::Borrowed(s) => Cow::Borrowed(s),
+        s => Cow::Owned(s.to_string()),
+    }
+}
+
+/// Finds the external and `mailto:` Markdown links in `contents`, in order,
+/// by parsing it with the options mdBook renders it with. Code, HTML and
+/// images aren't links, so their contents are never rewritten.
+fn find_md_links(contents: &str) -> std::vec::IntoIter<MDLink<'_>> {
+    let mut links = vec![];
+    // the link being read, and the end of its text so far
+    let mut open: Option<(MDLink, usize)> = None;
+    let mut image_depth = 0;
+    for (event, range) in Parser::new_ext(contents, word_count::parser_options()).into_offset_iter()
+    {
+        match event {
+            Event::Start(Tag::Image { .. }) => image_depth += 1,
+            Event::End(TagEnd::Image) => image_depth -= 1,
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                ..
+            }) if image_depth == 0
+                && matches!(
+                    link_type,
+                    LinkType::Inline
+                        | LinkType::Reference
+                        | LinkType::Collapsed
+                        | LinkType::Shortcut
+                        | LinkType::Autolink
+                ) =>
+            {
+                // the parser's range of `[text][]` stops before the `[]`
+                let end_index = match link_type {
+                    LinkType::Collapsed if contents[range.end..].starts_with("[]") => range.end + 2,
+                    _ => range.end,
+                };
+                let link = MDLink {
+                    start_index: range.start,
+                    end_index,
+                    text: "",
+                    url: cow_str(dest_url),
+                    title: cow_str(title),
+                };
+                // past the opening `[` or `<`
+                open = Some((link, range.start + 1));
+                continue;
+            }
+            Event::End(TagEnd::Link) => {
+                if let Some((mut link, text_end)) = open.take() {
+                    link.text = &contents[link.start_index + 1..text_end];
+                    if link.is_external() || link.url.starts_with("mailto:") {
+                        links.push(link);
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if let Some((_, text_end)) = open.as_mut() {
+            *text_end = (*text_end).max(range.end);
+        }
+    }
+    links.into_iter()
+}
+
+/// Returns the byte ranges of fenced code blocks and inline code spans in
+/// `contents`. Helpers and links within these ranges are left untouched.
+fn find_code_ranges(contents: &str) -> Vec<Range<usize>> {
+    let mut ranges = find_code_block_ranges(contents);
+    let span_ranges = find_code_span_ranges(contents, &ranges);
+    ranges.extend(span_ranges);
+    ranges
+}
+
+/// Returns the byte ranges of all fenced code blocks (```` ``` ```` or `~~~`)
+/// in `contents`, including the fence lines. An unclosed fence runs to the
+/// end of the content, as in CommonMark.
+fn find_code_block_ranges(contents: &str) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    // (fence char, fence length, start index) of the currently open fence
+    let mut open_fence: Option<(char, usize, usize)> = None;
+    let mut line_start = 0;
+
+    for line in contents.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        let trimmed = line.trim_start_matches(' ');
+        let indent = line.len() - trimmed.len();
+        let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+
+        if let Some(fence_char) = fence_char.filter(|_| indent < 4) {
+            let fence_len = trimmed.chars().take_while(|c| *c == fence_char).count();
+            match open_fence {
+                None if fence_len >= 3 => {
+                    open_fence = Some((fence_char, fence_len, line_start));
+                }
+                Some((open_char, open_len, start))
+                    if open_char == fence_char
+                        && fence_len >= open_len
+                        && trimmed[fence_len..].trim().is_empty() =>
+                {
+                    ranges.push(start..line_end);
+                    open_fence = None;
+                }
+                _ => {}
+            }
+        }
+        line_start = line_end;
+    }
+
+    if let Some((_, _, start)) = open_fence {
+        ranges.push(start..contents.len());
+    }
+    ranges
+}
+
+/// Returns the byte ranges of inline code spans outside of `block_ranges`.
+/// A span opens with a run of backticks and closes with a run of the same
+/// length; an unmatched opening run is literal text.
+fn find_code_span_ranges(contents: &str, block_ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+    let bytes = contents.as_bytes();
+    let mut ranges = vec![];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if let Some(block) = block_ranges.iter().find(|block| block.contains(&i)) {
+            i = block.end;
+            continue;
+        }
+        if bytes[i] != b'`' || (i > 0 && bytes[i - 1] == b'\\') {
+            i += 1;
+            continue;
+        }
+
+        let run_len = count_backticks(&bytes[i..]);
+        let mut j = i + run_len;
+        let mut span_end = None;
+        while j < bytes.len() && !in_ranges(block_ranges, j) {
+            if bytes[j] == b'`' {
+                let len = count_backticks(&bytes[j..]);
+                if len == run_len {
+                    span_end = Some(j + len);
+                    break;
+                }
+                j += len;
+            } else {
+                j += 1;
+            }
+        }
+
+        match span_end {
+            Some(end) => {
+                ranges.push(i..end);
+                i = end;
+            }
+            None => i += run_len,
+        }
+    }
+    ranges
+}
+
+fn count_backticks(bytes: &[u8]) -> usize {
+    bytes.iter().take_while(|b| **b == b'`').count()
+}
+
+fn in_ranges(ranges: &[Range<usize>], index: usize) -> bool {
+    ranges.iter().any(|range| range.contains(&index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::citations::CitationStyle;
+    use crate::config::ReadingTimeFormat;
+    use crate::helpers::authors::AIPRAuthorsSettings;
+    use crate::helpers::cite::AIPRCiteSettings;
+    use crate::helpers::figure::AIPRRefSettings;
+    use crate::helpers::last_updated::AIPRLastUpdatedSettings;
+    use rstest::*;
+    use std::collections::BTreeMap;
+
+    #[rstest]
+    fn test_templates_register() -> Result<()> {
+        for name in [
+            "aipr_header",
+            "aipr_footer",
+            "aipr_authors",
+            "aipr_citation",
+            "aipr_last_updated",
+            "aipr_contributors",
+            "aipr_toc",
+            "aipr_related",
+            "aipr_prereqs",
+            "aipr_tags",
+            "aipr_term",
+            "aipr_abbr",
+            "aipr_callout",
+            "aipr_figure",
+            "aipr_ref",
+            "aipr_eq",
+            "aipr_eqref",
+            "aipr_chapter",
+            "aipr_cite",
+            "aipr_references",
+            "aipr_video",
+            "aipr_quiz",
+            "aipr_code",
+            "aipr_hf",
+            "aipr_arxiv",
+            "aipr_arxiv_link",
+            "aipr_doi",
+            "aipr_dataset",
+            "aipr_model_card",
+            "aipr_benchmark",
+            "aipr_series_nav",
+            "aipr_license",
+            "aipr_changelog",
+            "aipr_total_reading_time",
+            "aipr_meta",
+            "aipr_comments",
+            "aipr_feedback",
+            "aipr_redirect",
+            "aipr_version_banner",
+            "aipr_stale_notice",
+            "md_link_expansion",
+        ] {
+            assert!(TEMPLATES.has_template(name));
+        }
+        Ok(())
+    }
+
+    #[fixture]
+    fn simple_book_content() -> String {
+        "{{ #aipr_header }} {{ #aipr_header colab=nlp/lora.ipynb }} Some random [text with](https://fake.io) and more text ..."
+            .to_string()
+    }
+
+    #[rstest]
+    fn test_find_links_no_author_links() -> Result<()> {
+        let s = "Some random text without link...";
+        assert!(find_aipr_links(s).collect::<Vec<_>>() == vec![]);
+        assert!(find_md_links(s).collect::<Vec<_>>() == vec![]);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_links_empty_link() -> Result<()> {
+        let s = "Some random text with {{#colab  }} and {{}} {{#}}...";
+        println!("{:?}", find_aipr_links(s).collect::<Vec<_>>());
+        assert!(find_aipr_links(s).collect::<Vec<_>>() == vec![]);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_links_unknown_link_type() -> Result<()> {
+        let s = "Some random \\[text with\\](test) {{#my_author ar.rs}} and {{#auth}} {{baz}} {{#bar}}...";
+        assert!(find_aipr_links(s).collect::<Vec<_>>() == vec![]);
+        assert!(find_md_links(s).collect::<Vec<_>>() == vec![]);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_links_simple_author_links(simple_book_content: String) -> Result<()> {
+        let res = find_aipr_links(&simple_book_content[..]).collect::<Vec<_>>();
+        println!("\nOUTPUT: {res:?}\n");
+
+        assert_eq!(
+            res,
+            vec![
+                AIPRLink {
+                    start_index: 0,
+                    end_index: 18,
+                    link_type: AIPRLinkType::Header(Box::default()),
+                    link_text: "{{ #aipr_header }}",
+                    body: None,
+                },
+                AIPRLink {
+                    start_index: 19,
+                    end_index: 58,
+                    link_type: AIPRLinkType::Header(Box::new(AIPRHeaderSettings::from_param_str(
+                        "colab=nlp/lora.ipynb"
+                    ))),
+                    link_text: "{{ #aipr_header colab=nlp/lora.ipynb }}",
+                    body: None,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_links_footer() -> Result<()> {
+        let s = "Some text {{#aipr_footer}} and {{ #aipr_footer logo=false,links=github }}";
+        let res = find_aipr_links(s).collect::<Vec<_>>();
+
+        assert_eq!(
+            res,
+            vec![
+                AIPRLink {
+                    start_index: 10,
+                    end_index: 26,
+                    link_type: AIPRLinkType::Footer(AIPRFooterSettings::default()),
+                    link_text: "{{#aipr_footer}}",
+                    body: None,
+                },
+                AIPRLink {
+                    start_index: 31,
+                    end_index: 73,
+                    link_type: AIPRLinkType::Footer(AIPRFooterSettings {
+                        logo: false,
+                        links: vec!["github".to_string()],
+                        ..Default::default()
+                    }),
+                    link_text: "{{ #aipr_footer logo=false,links=github }}",
+                    body: None,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_footer_render_partials() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("aipr-partials-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("footer_extra.hbs"),
+            "<p>{{uppercase tagline}} {{format_date \"2024-03-09\" \"%B %-d, %Y\"}}</p>\n",
+        )?;
+        std::fs::write(dir.join("notes.txt"), "not a partial")?;
+        let config = AIPRConfig {
+            footer: config::FooterConfig {
+                enabled: true,
+                variables: config::FooterVariables {
+                    tagline: Some("Pocket refs".to_string()),
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        };
+        let book = BookInfo {
+            templates: Some(load_partials(&dir)?),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &book,
+            chapter: &ChapterInfo::default(),
+        };
+
+        let html_string = AIPRFooterSettings::from_param_str("logo=false").render(&ctx)?;
+        assert_eq!(
+            html_string,
+            "<p class=\"aipr-footer-tagline\">Pocket refs</p>\n<p>POCKET REFS March 9, 2024</p>\n"
+        );
+
+        // partials can't replace the built-in templates
+        std::fs::write(dir.join("aipr_footer.hbs"), "")?;
+        assert!(load_partials(&dir).is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("{{lowercase \"LoRA\"}}", "lora")]
+    #[case("{{format_date \"2024-03-09\"}}", "2024-03-09")]
+    #[case(
+        "{{format_date \"2024-03-09T10:00:00+02:00\" \"%d/%m/%Y\"}}",
+        "09/03/2024"
+    )]
+    fn test_template_helpers(#[case] template: &str, #[case] expected: &str) -> Result<()> {
+        assert_eq!(TEMPLATES.render_template(template, &())?, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("{{format_date \"March 2024\"}}")]
+    #[case("{{format_date \"2024-03-09\" \"%Q\"}}")]
+    fn test_template_helpers_errors(#[case] template: &str) -> Result<()> {
+        assert!(TEMPLATES.render_template(template, &()).is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        "{{#aipr_license CC-BY-SA-4.0}}",
+        "<img src=\"https://img.shields.io/badge/License-CC_BY--SA_4.0-lightgrey\" alt=\"License: CC BY-SA 4.0\"/>"
+    )]
+    #[case(
+        "{{#aipr_license Apache-2.0}}",
+        "<a href=\"https://www.apache.org/licenses/LICENSE-2.0\" target=\"_blank\" \
+        rel=\"license noopener noreferrer\">the Apache License 2.0</a>"
+    )]
+    #[case(
+        "{{#aipr_license Proprietary}}",
+        "<span>This work is licensed under Proprietary.</span>"
+    )]
+    fn test_license_render(#[case] helper: &str, #[case] expected: &str) -> Result<()> {
+        let links = find_aipr_links(helper).collect::<Vec<_>>();
+        let AIPRLinkType::License(settings) = &links[0].link_type else {
+            panic!("expected license helper");
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let html_string = settings.render(&ctx)?;
+        assert!(html_string.contains(expected), "{html_string}");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_links_authors() -> Result<()> {
+        let s = "{{#aipr_authors}} {{#aipr_authors authors=octocat, @nerdai}}";
+        let res = find_aipr_links(s).collect::<Vec<_>>();
+
+        assert_eq!(
+            res,
+            vec![AIPRLink {
+                start_index: 18,
+                end_index: 60,
+                link_type: AIPRLinkType::Authors(AIPRAuthorsSettings {
+                    authors: vec!["octocat".to_string(), "@nerdai".to_string()],
+                }),
+                link_text: "{{#aipr_authors authors=octocat, @nerdai}}",
+                body: None,
+            }]
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_last_updated_render() -> Result<()> {
+        let src_dir =
+            std::env::temp_dir().join(format!("aipr-last-updated-{}", std::process::id()));
+        git::init_test_repo(&src_dir)?;
+        let book = BookInfo {
+            src_dir: src_dir.clone(),
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            source_path: Some(PathBuf::from("nlp/lora.md")),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book,
+            chapter: &chapter,
+        };
+        let link = find_aipr_links("{{#aipr_last_updated}}").next().unwrap();
+
+        let html_string = link.render(&ctx)?;
+
+        assert_eq!(html_string, "<p class=\"aipr-last-updated\" style=\"margin: 0;\"><small>Last updated: 2024-05-06</small></p>\n");
+
+        std::fs::remove_dir_all(&src_dir)?;
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_last_updated_render_without_history() -> Result<()> {
+        let link = find_aipr_links("{{#aipr_last_updated}}").next().unwrap();
+
+        let html_string = link.render(&HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        })?;
+
+        assert_eq!(html_string, "");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_meta_tags() -> Result<()> {
+        let config = AIPRConfig::default();
+        let book = BookInfo::default();
+        let chapter = ChapterInfo::default();
+        let ctx = HelperContext {
+            config: &config,
+            book: &book,
+            chapter: &chapter,
+        };
+        let tags = ["nlp".to_string(), "fine-tuning".to_string()];
+
+        assert_eq!(
+            render_meta_tags(&ctx, &tags, Some("Adapters \"on top\" of weights."))?,
+            "<meta name=\"keywords\" content=\"nlp, fine-tuning\">\n\
+            <meta name=\"description\" content=\"Adapters &quot;on top&quot; of weights.\">\n"
+        );
+        assert_eq!(render_meta_tags(&ctx, &[], None)?, "");
+
+        let front_matter = HashMap::from([("description".to_string(), "LoRA.".to_string())]);
+        let header = AIPRHeaderSettings::from_param_str("description=\"Low-rank adapters.\"");
+        assert_eq!(
+            header
+                .with_front_matter(&front_matter)
+                .description
+                .as_deref(),
+            Some("Low-rank adapters.")
+        );
+        let header = AIPRHeaderSettings::default().with_front_matter(&front_matter);
+        assert_eq!(header.description.as_deref(), Some("LoRA."));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_structured_data() -> Result<()> {
+        let src_dir =
+            std::env::temp_dir().join(format!("aipr-structured-data-{}", std::process::id()));
+        git::init_test_repo(&src_dir)?;
+        let book = BookInfo {
+            title: Some("AI Pocket References".to_string()),
+            src_dir: src_dir.clone(),
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            name: "LoRA </script>".to_string(),
+            path: Some(PathBuf::from("nlp/lora.md")),
+            source_path: Some(PathBuf::from("nlp/lora.md")),
+            num_words: 420,
+            ..Default::default()
+        };
+        let config = AIPRConfig {
+            site_url: Some("https://example.com/book/".to_string()),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &book,
+            chapter: &chapter,
+        };
+
+        let html_string = render_structured_data(&ctx, &["octocat".to_string()])?;
+        let json = html_string
+            .strip_prefix("<script type=\"application/ld+json\">")
+            .and_then(|s| s.strip_suffix("</script>\n"))
+            .unwrap();
+        let article: serde_json::Value = serde_json::from_str(json)?;
+        assert_eq!(article["@type"], "TechArticle");
+        assert_eq!(article["headline"], "LoRA </script>");
+        assert_eq!(article["author"][0]["name"], "octocat");
+        assert_eq!(article["wordCount"], 420);
+        assert_eq!(article["url"], "https://example.com/book/nlp/lora.html");
+        assert_eq!(article["isPartOf"]["name"], "AI Pocket References");
+        assert_eq!(article["publisher"]["name"], "Vector Institute");
+        assert_eq!(article["datePublished"], "2024-01-02");
+        assert_eq!(article["dateModified"], "2024-05-06");
+        assert!(!json.contains("</script>"));
+
+        std::fs::remove_dir_all(&src_dir)?;
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_write_feed() -> Result<()> {
+        let src_dir = std::env::temp_dir().join(format!("aipr-feed-{}", std::process::id()));
+        let book = BookInfo {
+            title: Some("AI Pocket References".to_string()),
+            src_dir: src_dir.clone(),
+            ..Default::default()
+        };
+        let chapters = [
+            ChapterSource {
+                name: "LoRA".to_string(),
+                path: Some(PathBuf::from("nlp/lora.md")),
+                source_path: Some(PathBuf::from("nlp/lora.md")),
+                content: "---\ndescription: Low-rank adaptation.\n---\n# LoRA\n".to_string(),
+                ..Default::default()
+            },
+            ChapterSource {
+                name: "Draft".to_string(),
+                ..Default::default()
+            },
+        ];
+        let config = AIPRConfig {
+            feed: true,
+            site_url: Some("https://example.com/book/".to_string()),
+            ..Default::default()
+        };
+
+        let dates = HashMap::from([(
+            PathBuf::from("nlp/lora.md"),
+            chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+        )]);
+        write_feed(&chapters, &config, &book, &dates)?;
+        let xml = std::fs::read_to_string(src_dir.join("feed.xml"))?;
+        assert!(xml.contains("<title>AI Pocket References</title>"));
+        assert!(xml.contains("<link href=\"https://example.com/book/nlp/lora.html\"/>"));
+        assert!(xml.contains("<summary>Low-rank adaptation.</summary>"));
+        assert_eq!(xml.matches("<entry>").count(), 1);
+
+        std::fs::remove_dir_all(&src_dir)?;
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_validate_notebooks(#[case] strict: bool) -> Result<()> {
+        let root = std::env::temp_dir().join(format!(
+            "aipr-validate-notebooks-{strict}-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(root.join("code/notebooks/nlp"))?;
+        std::fs::write(root.join("code/notebooks/nlp/lora.ipynb"), "{}")?;
+        let config = AIPRConfig {
+            code_repo_path: Some(PathBuf::from("code")),
+            strict,
+            ..Default::default()
+        };
+        let chapter = |content: &str| ChapterSource {
+            name: "LoRA".to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        };
+
+        let chapters = [chapter(
+            "{{#aipr_header colab=nlp/lora.ipynb,kaggle=nlp/lora.ipynb}}",
+        )];
+        assert!(validate_notebooks(&chapters, &config, &root).is_ok());
+
+        let chapters = [chapter(
+            "{{#aipr_header colab=nlp/lora.ipynb,binder=nlp/lora.ipnyb}}",
+        )];
+        let result = validate_notebooks(&chapters, &config, &root);
+        assert_eq!(result.is_err(), strict);
+
+        let chapters = [chapter(
+            "{{#aipr_header colab=nlp/lora.ipynb;nlp/lora_eval.ipynb}}",
+        )];
+        let result = validate_notebooks(&chapters, &config, &root);
+        assert_eq!(result.is_err(), strict);
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_check_links(#[case] strict: bool) -> Result<()> {
+        let root =
+            std::env::temp_dir().join(format!("aipr-check-links-{strict}-{}", std::process::id()));
+        let config = AIPRConfig {
+            check_links: true,
+            check_links_timeout: 2,
+            check_links_exclude: vec!["example.com".to_string()],
+            strict,
+            ..Default::default()
+        };
+        let today = chrono::Local::now().date_naive();
+        let mut cache = LinkCache::load(&root.join(&config.cache_dir));
+        cache.insert("https://cached.io/", today);
+        cache.save()?;
+        let chapter = |content: &str| ChapterSource {
+            name: "LoRA".to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        };
+
+        // excluded and recently checked links aren't requested
+        let chapters = [chapter(
+            "[a](https://www.example.com/404) [b](https://cached.io/) `[c](http://127.0.0.1:9/)`",
+        )];
+        assert!(check_links(&chapters, &config, &root).is_ok());
+
+        let chapters = [chapter("[dead](http://127.0.0.1:9/)")];
+        assert_eq!(check_links(&chapters, &config, &root).is_err(), strict);
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_write_metadata() -> Result<()> {
+        let src_dir = std::env::temp_dir().join(format!("aipr-metadata-{}", std::process::id()));
+        let chapters = [
+            ChapterSource {
+                name: "LoRA".to_string(),
+                path: Some(PathBuf::from("nlp/lora.md")),
+                content: "{{#aipr_header colab=nlp/lora.ipynb,difficulty=Advanced}}\n\
+                    {{#aipr_tags nlp,peft}}\n\n# LoRA\n\n"
+                    .to_string()
+                    + &"word ".repeat(450),
+                ..Default::default()
+            },
+            ChapterSource {
+                name: "Draft".to_string(),
+                ..Default::default()
+            },
+        ];
+        let config = AIPRConfig {
+            metadata: true,
+            ..Default::default()
+        };
+        let book = BookInfo {
+            src_dir: src_dir.clone(),
+            chapters: index_chapters(&chapters, &config),
+            ..Default::default()
+        };
+
+        write_metadata(&chapters, &config, &book)?;
+        let json = std::fs::read_to_string(src_dir.join("aipr-metadata.json"))?;
+        let metadata: serde_json::Value = serde_json::from_str(&json)?;
+        assert_eq!(
+            metadata,
+            serde_json::json!({
+                "chapters": [{
+                    "path": "nlp/lora.md",
+                    "title": "LoRA",
+                    "url": null,
+                    "word_count": chapter_word_count(&chapters[0].content).words(false),
+                    "reading_time": 2,
+                    "code_lines": 0,
+                    "images": 0,
+                    "tags": ["nlp", "peft"],
+                    "colab": "nlp/lora.ipynb",
+                    "difficulty": "advanced",
+                }]
+            })
+        );
+
+        std::fs::remove_dir_all(&src_dir)?;
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_headings() -> Result<()> {
+        let content = "# LoRA\n\n## Why `LoRA`?\n\n```python\n## not a heading\n```\n\
+            ### Rank & alpha ###\n## Why LoRA?\n#### Deep\n## Custom {#my-id}\n#hashtag\n";
+        let headings = find_headings(content)
+            .into_iter()
+            .map(|h| (h.level, h.text, h.id))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            headings,
+            vec![
+                (1, "LoRA".to_string(), "lora".to_string()),
+                (2, "Why LoRA?".to_string(), "why-lora".to_string()),
+                (3, "Rank & alpha".to_string(), "rank--alpha".to_string()),
+                (2, "Why LoRA?".to_string(), "why-lora-1".to_string()),
+                (4, "Deep".to_string(), "deep".to_string()),
+                (2, "Custom".to_string(), "my-id".to_string()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_series_nav_render(#[case] strict: bool) -> Result<()> {
+        let summary = |name: &str| ChapterSummary {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let book = BookInfo {
+            chapters: HashMap::from([
+                (PathBuf::from("nlp/lora.md"), summary("LoRA")),
+                (PathBuf::from("nlp/qlora.md"), summary("QLoRA")),
+                (PathBuf::from("nlp/dora.md"), summary("DoRA")),
+            ]),
+            ..Default::default()
+        };
+        let config = AIPRConfig {
+            strict,
+            series: BTreeMap::from([(
+                "Fine-tuning".to_string(),
+                vec![
+                    "nlp/lora.md".to_string(),
+                    "./nlp/qlora.md".to_string(),
+                    "nlp/dora.md".to_string(),
+                ],
+            )]),
+            ..Default::default()
+        };
+        let render = |path: &str, helper: &str| -> Result<String> {
+            let chapter = ChapterInfo {
+                name: "Chapter".to_string(),
+                path: Some(PathBuf::from(path)),
+                ..Default::default()
+            };
+            let ctx = HelperContext {
+                config: &config,
+                book: &book,
+                chapter: &chapter,
+            };
+            let links = find_aipr_links(helper).collect::<Vec<_>>();
+            let AIPRLinkType::SeriesNav(settings) = &links[0].link_type else {
+                panic!("expected series_nav helper");
+            };
+            settings.render(&ctx)
+        };
+
+        let html_string = render("nlp/qlora.md", "{{#aipr_series_nav Fine-tuning}}")?;
+        assert!(html_string.contains("Fine-tuning: Part 2 of 3"));
+        assert!(html_string
+            .contains("<a class=\"aipr-series-prev\" href=\"../nlp/lora.html\">&larr; LoRA</a>"));
+        assert!(html_string
+            .contains("<a class=\"aipr-series-next\" href=\"../nlp/dora.html\">DoRA &rarr;</a>"));
+
+        let html_string = render("nlp/lora.md", "{{#aipr_series_nav}}")?;
+        assert!(html_string.contains("Fine-tuning: Part 1 of 3"));
+        assert!(!html_string.contains("aipr-series-prev"));
+        assert!(html_string.contains("aipr-series-next"));
+
+        let html_string = render("nlp/dora.md", "{{#aipr_series_nav}}")?;
+        assert!(html_string.contains("Part 3 of 3"));
+        assert!(!html_string.contains("aipr-series-next"));
+
+        for (path, helper) in [
+            ("nlp/lora.md", "{{#aipr_series_nav RLHF}}"),
+            ("nlp/rlhf.md", "{{#aipr_series_nav}}"),
+        ] {
+            match strict {
+                true => assert!(render(path, helper).is_err()),
+                false => assert_eq!(render(path, helper)?, ""),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_write_redirects(#[case] strict: bool) -> Result<()> {
+        let src_dir =
+            std::env::temp_dir().join(format!("aipr-redirects-{strict}-{}", std::process::id()));
+        let book = BookInfo {
+            src_dir: src_dir.clone(),
+            chapters: HashMap::from([(
+                PathBuf::from("nlp/lora.md"),
+                ChapterSummary {
+                    name: "LoRA".to_string(),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+        let redirect = |old: &str, new: &str| -> Result<()> {
+            let config = AIPRConfig {
+                strict,
+                redirects: BTreeMap::from([(old.to_string(), new.to_string())]),
+                ..Default::default()
+            };
+            write_redirects(&config, &book)
+        };
+
+        redirect("nlp/peft.md", "nlp/lora.md")?;
+        let html_string = std::fs::read_to_string(src_dir.join("nlp/peft.html"))?;
+        assert!(html_string
+            .contains("<meta http-equiv=\"refresh\" content=\"0; url=../nlp/lora.html\">"));
+        assert!(html_string.contains("<a href=\"../nlp/lora.html\">LoRA</a>"));
+
+        for (old, new) in [("peft.md", "nlp/dora.md"), ("nlp/lora.md", "nlp/lora.md")] {
+            match strict {
+                true => assert!(redirect(old, new).is_err()),
+                false => {
+                    redirect(old, new)?;
+                    assert!(!src_dir.join(Path::new(old).with_extension("html")).exists());
+                }
+            }
+        }
+
+        // paths outside of `src` are rejected, strict or not
+        for old in ["../../x.md", "nlp/../../x.md", "/tmp/x.md"] {
+            assert!(redirect(old, "nlp/lora.md").is_err());
+        }
+        assert!(!src_dir.join("../../x.html").exists());
+
+        std::fs::remove_dir_all(&src_dir)?;
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_append_reading_times() -> Result<()> {
+        let mut book = Book::new();
+        book.push_item(Chapter::new("LoRA", String::new(), "nlp/lora.md", vec![]));
+        book.push_item(Chapter::new_draft("QLoRA", vec![]));
+        let book_info = BookInfo {
+            chapters: HashMap::from([(
+                PathBuf::from("nlp/lora.md"),
+                ChapterSummary {
+                    word_count: WordCount {
+                        prose_words: 1000,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+        let config = AIPRConfig {
+            reading_time_format: ReadingTimeFormat::Range,
+            ..Default::default()
+        };
+
+        append_reading_times(&mut book, &config, &book_info);
+        let names = book
+            .iter()
+            .filter_map(|section| match section {
+                BookItem::Chapter(ch) => Some(ch.name.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["LoRA · 4–7 min", "QLoRA"]);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_total_reading_time_render(#[case] strict: bool) -> Result<()> {
+        let summary = |prose_words: usize, part: Option<&str>| ChapterSummary {
+            word_count: WordCount {
+                prose_words,
+                ..Default::default()
+            },
+            part: part.map(str::to_string),
+            ..Default::default()
+        };
+        let book = BookInfo {
+            chapters: HashMap::from([
+                (PathBuf::from("intro.md"), summary(200, None)),
+                (PathBuf::from("nlp/lora.md"), summary(1000, Some("NLP"))),
+                (PathBuf::from("nlp/qlora.md"), summary(400, Some("NLP"))),
+                (PathBuf::from("cv/vit.md"), summary(600, Some("Vision"))),
+            ]),
+            parts: vec!["NLP".to_string(), "Vision".to_string()],
+            ..Default::default()
+        };
+        let config = AIPRConfig {
+            strict,
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            name: "Introduction".to_string(),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &book,
+            chapter: &chapter,
+        };
+        let render = |helper: &str| -> Result<String> {
+            let links = find_aipr_links(helper).collect::<Vec<_>>();
+            let AIPRLinkType::TotalReadingTime(settings) = &links[0].link_type else {
+                panic!("expected total_reading_time helper");
+            };
+            settings.render(&ctx)
+        };
+
+        assert_eq!(
+            render("{{#aipr_total_reading_time}}")?,
+            "<span class=\"aipr-total-reading-time\">11 min</span>\n"
+        );
+        assert_eq!(
+            render("{{#aipr_total_reading_time part=NLP}}")?,
+            "<span class=\"aipr-total-reading-time\">7 min</span>\n"
+        );
+        let html_string = render("{{#aipr_total_reading_time by_part}}")?;
+        assert!(html_string.contains("<li>NLP: 7 min</li>"));
+        assert!(html_string.contains("<li>Vision: 3 min</li>"));
+        assert!(html_string.contains("<li><strong>Total</strong>: 11 min</li>"));
+
+        let helper = "{{#aipr_total_reading_time part=RLHF}}";
+        match strict {
+            true => assert!(render(helper).is_err()),
+            false => assert_eq!(render(helper)?, ""),
+        }
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_tags_render() -> Result<()> {
+        let chapter = ChapterInfo {
+            path: Some(PathBuf::from("nlp/lora.md")),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &chapter,
+        };
+
+        let links = find_aipr_links("{{#aipr_tags nlp, PEFT,fine-tuning}}").collect::<Vec<_>>();
+        let AIPRLinkType::Tags(settings) = &links[0].link_type else {
+            panic!("expected tags helper");
+        };
+        assert_eq!(settings.tags, vec!["nlp", "PEFT", "fine-tuning"]);
+
+        let html_string = settings.render(&ctx)?;
+        assert!(html_string.contains("<a class=\"aipr-tag\" href=\"../tags/nlp.html\">nlp</a>"));
+        assert!(html_string.contains("<a class=\"aipr-tag\" href=\"../tags/peft.html\">PEFT</a>"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_shortlink_render() -> Result<()> {
+        let config = AIPRConfig {
+            links: BTreeMap::from([
+                (
+                    "llama3".to_string(),
+                    "https://arxiv.org/abs/2407.21783".to_string(),
+                ),
+                (
+                    "vector".to_string(),
+                    "https://vectorinstitute.ai".to_string(),
+                ),
+            ]),
+            new_tab_exclude: vec!["vectorinstitute.ai".to_string()],
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+
+        let s = "See the {{#aipr_link llama3 \"Llama 3 paper\"}}, {{#aipr_link llama3}} \
+            and {{#aipr_link vector Vector}}.";
+        assert_eq!(
+            replace_all(s, &ctx)?,
+            "See the <a href=\"https://arxiv.org/abs/2407.21783\" target=\"_blank\" \
+            rel=\"noopener noreferrer\">Llama 3 paper</a>, \
+            <a href=\"https://arxiv.org/abs/2407.21783\" target=\"_blank\" \
+            rel=\"noopener noreferrer\">https://arxiv.org/abs/2407.21783</a> \
+            and [Vector](https://vectorinstitute.ai)."
+        );
+        assert_eq!(
+            replace_all("{{#aipr_link llama2 \"Llama 2\"}}", &ctx)?,
+            "Llama 2"
+        );
+
+        let strict_config = AIPRConfig {
+            strict: true,
+            ..config.clone()
+        };
+        let strict_ctx = HelperContext {
+            config: &strict_config,
+            ..ctx
+        };
+        assert!(replace_all("{{#aipr_link llama2}}", &strict_ctx).is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_block_helpers() -> Result<()> {
+        let s = "{{#aipr_callout type=tip}}\nOuter {{#aipr_callout}}inner{{/aipr_callout}}\n\
+            ```\n{{/aipr_callout}}\n```\n{{/aipr_callout}} after {{#aipr_last_updated}}";
+        let links = find_aipr_links(s).collect::<Vec<_>>();
+        assert_eq!(links.len(), 2);
+
+        let callout = &links[0];
+        let close = s.rfind("{{/aipr_callout}}").unwrap();
+        assert_eq!(callout.end_index, close + "{{/aipr_callout}}".len());
+        assert_eq!(callout.body, Some(26..close));
+        let AIPRLinkType::Callout(settings) = &callout.link_type else {
+            panic!("expected callout helper");
+        };
+        assert_eq!(settings.kind, "tip");
+        assert_eq!(settings.title, "Tip");
+        assert!(settings
+            .body
+            .starts_with("\nOuter {{#aipr_callout}}inner{{/aipr_callout}}"));
+        assert_eq!(
+            links[1].link_type,
+            AIPRLinkType::LastUpdated(AIPRLastUpdatedSettings)
+        );
+
+        let all_links = find_all_aipr_links(s);
+        assert_eq!(all_links.len(), 3);
+        assert_eq!(
+            &s[all_links[1].start_index..all_links[1].end_index],
+            "{{#aipr_callout}}inner{{/aipr_callout}}"
+        );
+
+        // unclosed blocks are left as a plain helper
+        let links = find_aipr_links("{{#aipr_callout}} never closed").collect::<Vec<_>>();
+        assert_eq!(links[0].body, None);
+        assert_eq!(links[0].end_index, 17);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_callout_render() -> Result<()> {
+        let s = "{{#aipr_callout type=warning,title=Heads up}}\nMind the [gap](https://fake.io).\n{{/aipr_callout}}";
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let link = MDLink {
+            start_index: 0,
+            end_index: 0,
+            text: "gap",
+            url: "https://fake.io".into(),
+            ..Default::default()
+        }
+        .render(&ctx)?;
+        let expected = format!(
+            "<div class=\"aipr-callout aipr-callout-warning\">\n\
+            <p class=\"aipr-callout-title\"><strong>Heads up</strong></p>\n\n\
+            Mind the {link}.\n\n</div>\n"
+        );
+        assert_eq!(replace_all(s, &ctx)?, expected);
+
+        assert_eq!(chapter_word_count(s).words(false), 3);
+        assert_eq!(
+            strip_all_aipr_links(s),
+            "\nMind the [gap](https://fake.io).\n"
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_figure_numbering() -> Result<()> {
+        let s = "See {{#aipr_ref fig:qlora}}.\n\
+            {{#aipr_figure src=img/lora.png caption=\"LoRA decomposition\"}}\n\
+            {{#aipr_figure src=img/q.png,id=fig:qlora,caption=QLoRA}}\n";
+        let chapter = ChapterInfo {
+            figures: number_figures(&find_all_aipr_links(s)),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &chapter,
+        };
+
+        let new_content = replace_all(s, &ctx)?;
+        assert!(
+            new_content.starts_with("See <a class=\"aipr-ref\" href=\"#fig-qlora\">Figure 2</a>.")
+        );
+        assert!(new_content.contains("<figure class=\"aipr-figure\" id=\"fig-lora\">"));
+        assert!(new_content.contains("<img src=\"img/lora.png\" alt=\"LoRA decomposition\"/>"));
+        assert!(new_content
+            .contains("<figcaption><strong>Figure 1.</strong> LoRA decomposition</figcaption>"));
+        assert!(new_content.contains("<figcaption><strong>Figure 2.</strong> QLoRA</figcaption>"));
+
+        let missing = AIPRRefSettings {
+            id: "fig:missing".to_string(),
+        };
+        assert_eq!(missing.render(&ctx)?, "fig:missing");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_equation_numbering() -> Result<()> {
+        let s = "By {{#aipr_eqref attn}}:\n\n\
+            {{#aipr_eq}}\n$$ a = b $$\n{{/aipr_eq}}\n\n\
+            {{#aipr_eq label=attn}}\n$$ \\text{softmax}(QK^T) $$\n{{/aipr_eq}}\n";
+        let chapter = ChapterInfo {
+            equations: number_equations(&find_all_aipr_links(s)),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &chapter,
+        };
+
+        let new_content = replace_all(s, &ctx)?;
+        let expected = "By <a class=\"aipr-eqref\" href=\"#eq-attn\">(Eq. 2)</a>:\n\n\
+            <div class=\"aipr-equation\">\n<div class=\"aipr-equation-body\">\n\n$$ a = b $$\n\n</div>\n\
+            <span class=\"aipr-equation-number\">(1)</span>\n</div>\n\n\n\
+            <div class=\"aipr-equation\" id=\"eq-attn\">\n<div class=\"aipr-equation-body\">\n\n\
+            $$ \\text{softmax}(QK^T) $$\n\n</div>\n\
+            <span class=\"aipr-equation-number\">(2)</span>\n</div>\n\n";
+        assert_eq!(new_content, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        "{{#aipr_chapter nlp/qlora.md}}",
+        Ok("<a class=\"aipr-chapter-link\" href=\"../nlp/qlora.html\">QLoRA</a>")
+    )]
+    #[case(
+        "{{#aipr_chapter ./nlp/qlora.md#results}}",
+        Ok("<a class=\"aipr-chapter-link\" href=\"../nlp/qlora.html#results\">QLoRA</a>")
+    )]
+    #[case("{{#aipr_chapter nlp/qlroa.md}}", Err("nlp/qlroa.md"))]
+    fn test_chapter_link_render(
+        #[case] s: &str,
+        #[case] expected: std::result::Result<&str, &str>,
+    ) -> Result<()> {
+        let book = BookInfo {
+            chapters: HashMap::from([(
+                PathBuf::from("nlp/qlora.md"),
+                ChapterSummary {
+                    name: "QLoRA".to_string(),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            path: Some(PathBuf::from("nlp/lora.md")),
+            ..Default::default()
+        };
+        let strict_config = AIPRConfig {
+            strict: true,
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book,
+            chapter: &chapter,
+        };
+
+        match expected {
+            Ok(expected) => assert_eq!(replace_all(s, &ctx)?, expected),
+            Err(unresolved) => {
+                assert_eq!(replace_all(s, &ctx)?, unresolved);
+                let strict_ctx = HelperContext {
+                    config: &strict_config,
+                    ..ctx
+                };
+                assert!(replace_all(s, &strict_ctx).is_err());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_cite_render() -> Result<()> {
+        let book = BookInfo {
+            bibliography: Bibliography::from_bibtex(
+                "@article{hu2021, title = {LoRA}, author = {Hu, Edward}, year = 2021, journal = {arXiv}}\n\
+                 @misc{vaswani2017, title = {Attention Is All You Need}}",
+            )?,
+            ..Default::default()
+        };
+        let s = "LoRA {{#aipr_cite hu2021}} builds on {{#aipr_cite vaswani2017, hu2021}}.";
+        let chapter = ChapterInfo {
+            citations: number_citations(&find_all_aipr_links(s)),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book,
+            chapter: &chapter,
+        };
+
+        assert_eq!(
+            replace_all(s, &ctx)?,
+            "LoRA <sup class=\"aipr-cite\">[<a href=\"#ref-hu2021\">1</a>]</sup> builds on \
+            <sup class=\"aipr-cite\">[<a href=\"#ref-vaswani2017\">2</a>, <a href=\"#ref-hu2021\">1</a>]</sup>."
+        );
+        let expected =
+            "<div class=\"aipr-references\">\n<p><strong>References</strong></p>\n<ol>\n  \
+            <li id=\"ref-hu2021\">E. Hu, \"LoRA,\" <em>arXiv</em>, 2021.</li>\n  \
+            <li id=\"ref-vaswani2017\">\"Attention Is All You Need.\"</li>\n</ol>\n</div>\n";
+        assert_eq!(render_references(&ctx)?, expected);
+
+        let missing = AIPRCiteSettings {
+            keys: vec!["missing".to_string()],
+        };
+        assert!(missing.render(&ctx)?.contains(">?</a>"));
+
+        let apa_config = AIPRConfig {
+            citation_style: CitationStyle::Apa,
+            ..Default::default()
+        };
+        let apa_ctx = HelperContext {
+            config: &apa_config,
+            ..ctx
+        };
+        assert_eq!(
+            replace_all(s, &apa_ctx)?,
+            "LoRA <span class=\"aipr-cite\">(<a href=\"#ref-hu2021\">Hu, 2021</a>)</span> builds on \
+            <span class=\"aipr-cite\">(<a href=\"#ref-vaswani2017\">Attention Is All You Need, n.d.</a>; \
+            <a href=\"#ref-hu2021\">Hu, 2021</a>)</span>."
+        );
+        let expected =
+            "<div class=\"aipr-references\">\n<p><strong>References</strong></p>\n<ul>\n  \
+            <li id=\"ref-vaswani2017\"><em>Attention Is All You Need</em>.</li>\n  \
+            <li id=\"ref-hu2021\">Hu, E. (2021). <em>LoRA</em>. arXiv.</li>\n</ul>\n</div>\n";
+        assert_eq!(render_references(&apa_ctx)?, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_model_card_render() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aipr-model-card-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("data/cards"))?;
+        std::fs::write(
+            root.join("data/cards/llama-3-8b.yaml"),
+            "name: Llama 3 8B\nparameters: 8B\ncontext_length: 8192\n\
+             checkpoint: https://huggingface.co/meta-llama/Meta-Llama-3-8B\n",
+        )?;
+        let book_info = BookInfo {
+            root: root.clone(),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book_info,
+            chapter: &ChapterInfo::default(),
+        };
+
+        let html_string =
+            find_aipr_links("{{#aipr_model_card file=llama-3-8b.yaml license=llama3}}")
+                .next()
+                .unwrap()
+                .render(&ctx)?;
+        assert!(html_string.contains("<strong>Llama 3 8B</strong> <small>Model</small>"));
+        assert!(html_string.contains("<tr><th>Parameters</th><td>8B</td></tr>"));
+        assert!(html_string.contains("<tr><th>Context length</th><td>8192</td></tr>"));
+        assert!(html_string.contains("<tr><th>License</th><td>llama3</td></tr>"));
+        assert!(html_string.contains(
+            "<tr><th>Checkpoint</th><td><a href=\"https://huggingface.co/meta-llama/Meta-Llama-3-8B\""
+        ));
+        assert!(!html_string.contains("Paper"));
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_quiz_render() -> Result<()> {
+        let config = AIPRConfig::default();
+        let book_info = BookInfo::default();
+        let chapter_info = ChapterInfo::default();
+        let ctx = HelperContext {
+            config: &config,
+            book: &book_info,
+            chapter: &chapter_info,
+        };
+        let content = "{{#aipr_quiz}}\n\
+            [[questions]]\n\
+            question = \"Which weights does LoRA train?\"\n\
+            choices = [\"All of them\", \"Low-rank update matrices\"]\n\
+            answer = 2\n\
+            {{/aipr_quiz}}";
+
+        let links = find_aipr_links(content).collect::<Vec<_>>();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].end_index, content.len());
+
+        let html_string = links[0].render(&ctx)?;
+        assert!(html_string.contains("<fieldset class=\"aipr-quiz-question\" data-answer=\"1\">"));
+        assert!(html_string.contains("<legend>Which weights does LoRA train?</legend>"));
+        assert!(html_string.contains(
+            "<label><input type=\"radio\" name=\"aipr-quiz-1-1\" value=\"1\"/> Low-rank update matrices</label>"
+        ));
+        assert!(!html_string.contains("\n\n"), "{html_string}");
+
+        // file quizzes aren't blocks, and missing files are an error
+        let links = find_aipr_links("{{#aipr_quiz file=missing.toml}}").collect::<Vec<_>>();
+        assert_eq!(links[0].body, None);
+        assert!(links[0].render(&ctx).is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_link_render() -> Result<()> {
+        let link = AIPRLink {
+            start_index: 19,
+            end_index: 58,
+            link_type: AIPRLinkType::Header(Box::new(AIPRHeaderSettings::from_param_str(
+                "colab=nlp/lora.ipynb",
+            ))),
+            link_text: "{{ #aipr_header colab=nlp/lora.ipynb }}",
+            body: None,
+        };
+        let num_words = 201;
+
+        let chapter = ChapterInfo {
+            num_words,
+            word_count: WordCount {
+                prose_words: num_words,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let html_string = link.render(&HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &chapter,
+        })?;
+        let expected = "<div style=\"display: flex; justify-content: \
+        space-between; align-items: center; margin-bottom: 2em;\">\n  <div>\n    \
+        <a target=\"_blank\" href=\"https://github.com/VectorInstitute/\
+        ai-pocket-reference/issues/new?template=edit-request.yml\">\n      \
+        <img src=\"https://img.shields.io/badge/Suggest_an_Edit-black?logo=\
+        github&style=flat\" alt=\"Suggest an Edit\"/>\n    </a>\n    \
+        <a target=\"_blank\" href=\"https://colab.research.google.com/github/\
+        VectorInstitute/ai-pocket-reference-code/blob/main/notebooks/nlp/lora.ipynb\
+        \">\n      <img src=\"https://colab.research.google.com/assets/colab-badge.svg\
+        \" alt=\"Open In Colab\"/>\n    </a>\n    <p style=\"margin: 0;\">\
+        <small>Reading time: 1 min</small></p>\n  </div>\n</div>\n";
+
+        println!("{:#?}", html_string);
+
+        assert_eq!(html_string, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_link_render_download() -> Result<()> {
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let links = find_aipr_links(
+            "{{#aipr_header colab=nlp/lora.ipynb,download=true}} {{#aipr_header download=true}}",
+        )
+        .collect::<Vec<_>>();
+
+        let html_string = links[0].render(&ctx)?;
+        assert!(html_string.contains(
+            "<a href=\"https://raw.githubusercontent.com/VectorInstitute/\
+            ai-pocket-reference-code/main/notebooks/nlp/lora.ipynb\" download=\"lora.ipynb\">"
+        ));
+
+        let html_string = links[1].render(&ctx)?;
+        assert!(!html_string.contains("download="));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_link_render_requirements() -> Result<()> {
+        let chapter = ChapterInfo {
+            front_matter: parse_front_matter("---\ngpu: A100\nruntime: \"1h\"\n---\n# LoRA"),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &chapter,
+        };
+        let links = find_aipr_links("{{#aipr_header gpu=T4,vram=16GB}} {{#aipr_header}}")
+            .collect::<Vec<_>>();
+
+        let html_string = links[0].render(&ctx)?;
+        assert!(html_string.contains("<strong>What you&#39;ll need:</strong>"));
+        assert!(html_string.contains("<span class=\"aipr-requirement\">GPU: T4</span>"));
+        assert!(html_string.contains("<span class=\"aipr-requirement\">VRAM: 16GB</span>"));
+        assert!(html_string.contains("<span class=\"aipr-requirement\">Runtime: 1h</span>"));
+
+        let html_string = links[1].render(&ctx)?;
+        assert!(html_string.contains("<span class=\"aipr-requirement\">GPU: A100</span>"));
+        assert!(!html_string.contains("VRAM"));
+
+        let chapter = ChapterInfo::default();
+        let html_string = links[1].render(&HelperContext {
+            chapter: &chapter,
+            ..ctx
+        })?;
+        assert!(!html_string.contains("aipr-requirements"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_link_render_header_language() -> Result<()> {
+        let translations = BTreeMap::from([(
+            "fr".to_string(),
+            BTreeMap::from([("Reading time".to_string(), "Durée de lecture".to_string())]),
+        )]);
+        let mut book = BookInfo::default();
+        book.set_catalog(Catalog::new("fr", &messages(), None, &translations));
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book,
+            chapter: &ChapterInfo::default(),
+        };
+        let links = find_aipr_links("{{#aipr_header difficulty=beginner}}").collect::<Vec<_>>();
+
+        let html_string = links[0].render(&ctx)?;
+        assert!(html_string.contains(
+            "<img src=\"https://img.shields.io/badge/Sugg%C3%A9rer_une_modification-black?\
+            logo=github&style=flat\" alt=\"Suggérer une modification\"/>"
+        ));
+        assert!(html_string.contains("badge/Difficult%C3%A9-Beginner-brightgreen"));
+        assert!(html_string.contains("<small>Durée de lecture: 0 min</small>"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_translated_messages() -> Result<()> {
+        let po = "msgid \"Part {part} of {total}\"\nmsgstr \"Partie {part} sur {total}\"\n\n\
+                  msgid \"On this page\"\nmsgstr \"Sur cette page\"\n";
+        let mut book = BookInfo::default();
+        book.set_catalog(Catalog::new("fr", &messages(), Some(po), &BTreeMap::new()));
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book,
+            chapter: &ChapterInfo::default(),
+        };
+
+        let mut data = Map::new();
+        data.insert("series".to_string(), to_json("Fine-tuning"));
+        data.insert("part".to_string(), to_json(2));
+        data.insert("total".to_string(), to_json(3));
+        let html_string = ctx.render("aipr_series_nav", &data)?;
+        assert!(html_string.contains("Fine-tuning: Partie 2 sur 3</p>"));
+
+        assert_eq!(ctx.translate("On this page"), "Sur cette page");
+        assert_eq!(ctx.translate("My own title"), "My own title");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_translate_helper_escapes_apostrophes() -> Result<()> {
+        let translations = BTreeMap::from([(
+            "fr".to_string(),
+            BTreeMap::from([(
+                "Cite this chapter".to_string(),
+                "Citer l'article".to_string(),
+            )]),
+        )]);
+        let mut book = BookInfo::default();
+        book.set_catalog(Catalog::new("fr", &messages(), None, &translations));
+
+        let html_string = book
+            .templates()
+            .render_template("<a title='{{t \"Cite this chapter\"}}'></a>", &())?;
+        assert_eq!(html_string, "<a title='Citer l&#39;article'></a>");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_messages() -> Result<()> {
+        let messages = messages();
+        for msgid in [
+            "Reading time",
+            "Part {part} of {total}",
+            "What you'll need",
+            "GPU",
+        ] {
+            assert!(messages.iter().any(|message| message == msgid), "{msgid}");
+        }
+        assert!(messages.windows(2).all(|pair| pair[0] < pair[1]));
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        "---\ntitle: \"LoRA\"\ntags: [nlp, 'fine-tuning']\nauthors:\n  - octocat\n  - \"nerdai\"\n\
+         model:\n  name: llama\n---\n",
+        vec![("title", "LoRA"), ("tags", "nlp,fine-tuning"), ("authors", "octocat,nerdai")]
+    )]
+    #[case("# gpu: T4\n", vec![])]
+    fn test_parse_front_matter(
+        #[case] content: &str,
+        #[case] expected: Vec<(&str, &str)>,
+    ) -> Result<()> {
+        let expected = expected
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(parse_front_matter(content), expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_link_render_no_colab() -> Result<()> {
+        let link = AIPRLink {
+            start_index: 19,
+            end_index: 58,
+            link_type: AIPRLinkType::Header(Box::default()),
+            link_text: "{{ #aipr_header }}",
+            body: None,
+        };
+        let num_words = 301;
+
+        let chapter = ChapterInfo {
+            num_words,
+            word_count: WordCount {
+                prose_words: num_words,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let html_string = link.render(&HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &chapter,
+        })?;
+        let expected = "<div style=\"display: flex; justify-content: \
+        space-between; align-items: center; margin-bottom: 2em;\">\n  <div>\n    \
+        <a target=\"_blank\" href=\"https://github.com/VectorInstitute/\
+        ai-pocket-reference/issues/new?template=edit-request.yml\">\n      \
+        <img src=\"https://img.shields.io/badge/Suggest_an_Edit-black?logo=\
+        github&style=flat\" alt=\"Suggest an Edit\"/>\n    </a>\n    \
+        <p style=\"margin: 0;\"><small>Reading time: 2 min</small></p>\n  \
+        </div>\n</div>\n";
+
+        assert_eq!(html_string, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_link_render_no_colab_no_reading_time() -> Result<()> {
+        let link = AIPRLink {
+            start_index: 19,
+            end_index: 58,
+            link_type: AIPRLinkType::Header(Box::new(AIPRHeaderSettings::from_param_str(
+                "reading_time=false",
+            ))),
+            link_text: "{{ #aipr_header reading_time=false }}",
+            body: None,
+        };
+        let num_words = 200;
+
+        let chapter = ChapterInfo {
+            num_words,
+            word_count: WordCount {
+                prose_words: num_words,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let html_string = link.render(&HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &chapter,
+        })?;
+        let expected = "<div style=\"display: flex; justify-content: \
+        space-between; align-items: center; margin-bottom: 2em;\">\n  <div>\n    \
+        <a target=\"_blank\" href=\"https://github.com/VectorInstitute/\
+        ai-pocket-reference/issues/new?template=edit-request.yml\">\n      \
+        <img src=\"https://img.shields.io/badge/Suggest_an_Edit-black?logo=\
+        github&style=flat\" alt=\"Suggest an Edit\"/>\n    </a>\n  \
+        </div>\n</div>\n";
+
+        assert_eq!(html_string, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_finds_md_link(simple_book_content: String) -> Result<()> {
+        let res = find_md_links(&simple_book_content[..]).collect::<Vec<_>>();
         println!("\nOUTPUT: {res:?}\n");
 
         assert_eq!(
-            res,
-            vec![
-                AIPRLink {
-                    start_index: 0,
-                    end_index: 18,
-                    link_type: AIPRLinkType::Header(AIPRHeaderSettings::default()),
-                    link_text: "{{ #aipr_header }}",
-                },
-                AIPRLink {
-                    start_index: 19,
-                    end_index: 58,
-                    link_type: AIPRLinkType::Header(AIPRHeaderSettings::from_param_str(
-                        "colab=nlp/lora.ipynb"
-                    )),
-                    link_text: "{{ #aipr_header colab=nlp/lora.ipynb }}",
-                },
-            ]
+            res,
+            vec![MDLink {
+                start_index: 71,
+                end_index: 99,
+                text: "text with",
+                url: "https://fake.io".into(),
+                ..Default::default()
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_code_block_ranges() -> Result<()> {
+        let s = "text\n```markdown\n[a](https://a.io)\n```\nmore\n  ~~~~\n~~~\nstill code\n";
+        let ranges = find_code_block_ranges(s);
+
+        assert_eq!(ranges, vec![5..39, 44..s.len()]);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_md_links_skips_fenced_code() -> Result<()> {
+        let s = "[a](https://a.io)\n\n```md\n[b](https://b.io)\n```\n\n[c](https://c.io)";
+        let urls = find_md_links(s).map(|link| link.url).collect::<Vec<_>>();
+
+        assert_eq!(urls, vec!["https://a.io", "https://c.io"]);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("a `code` b", vec![2..8])]
+    #[case("a ``co`de`` b `x", vec![2..11])]
+    #[case("a \\`not code` b", vec![])]
+    #[case("```\n`a`\n```\n`b`", vec![12..15])]
+    fn test_find_code_span_ranges(
+        #[case] s: &str,
+        #[case] expected: Vec<Range<usize>>,
+    ) -> Result<()> {
+        let block_ranges = find_code_block_ranges(s);
+        assert_eq!(find_code_span_ranges(s, &block_ranges), expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_links_skips_inline_code() -> Result<()> {
+        let s = "Use `{{#aipr_header}}` or `[a](https://a.io)`, {{#aipr_header}} [b](https://b.io)";
+        let aipr_starts = find_aipr_links(s)
+            .map(|link| link.start_index)
+            .collect::<Vec<_>>();
+        let urls = find_md_links(s).map(|link| link.url).collect::<Vec<_>>();
+
+        assert_eq!(aipr_starts, vec![47]);
+        assert_eq!(urls, vec!["https://b.io"]);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        "[a [nested] text](https://a.io)",
+        "a [nested] text",
+        "https://a.io",
+        ""
+    )]
+    #[case(
+        "[a](https://a.io/(x) \"The title\")",
+        "a",
+        "https://a.io/(x)",
+        "The title"
+    )]
+    #[case(
+        "[![badge](https://img.io/b.svg)](https://a.io)",
+        "![badge](https://img.io/b.svg)",
+        "https://a.io",
+        ""
+    )]
+    #[case("[a](<https://a.io/with space>)", "a", "https://a.io/with space", "")]
+    fn test_find_md_links_parsed(
+        #[case] s: &str,
+        #[case] text: &str,
+        #[case] url: &str,
+        #[case] title: &str,
+    ) -> Result<()> {
+        let links = find_md_links(s).collect::<Vec<_>>();
+
+        assert_eq!(links.len(), 1);
+        assert_eq!((links[0].start_index, links[0].end_index), (0, s.len()));
+        assert_eq!(links[0].text, text);
+        assert_eq!(links[0].url, url);
+        assert_eq!(links[0].title, title);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("<div>\n[a](https://a.io)\n</div>\n")]
+    #[case("Code:\n\n    [a](https://a.io)\n")]
+    #[case("\\[a](https://a.io)")]
+    fn test_find_md_links_not_links(#[case] s: &str) -> Result<()> {
+        assert_eq!(find_md_links(s).count(), 0);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_md_link_render_title() -> Result<()> {
+        let link = MDLink {
+            text: "a",
+            url: "https://a.io".into(),
+            title: "A \"site\"".into(),
+            ..Default::default()
+        };
+
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let html_string = link.render(&ctx)?;
+        assert_eq!(
+            html_string,
+            "<a href=\"https://a.io\" title=\"A &quot;site&quot;\" target=\"_blank\" \
+             rel=\"noopener noreferrer\">a</a>"
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_md_links_reference_style() -> Result<()> {
+        let s = "See [LoRA][lora], [the  Paper][] and [docs][local] or [missing][nope].\n\n\
+                 [lora]: https://arxiv.org/abs/2106.09685\n\
+                 [The Paper]: <https://fake.io/paper> \"Title\"\n\
+                 [local]: ./docs.md\n\
+                 ```\n[nope]: https://in-code.io\n```\n";
+        let links = find_md_links(s).collect::<Vec<_>>();
+        let links = links
+            .iter()
+            .map(|link| {
+                (
+                    link.text,
+                    link.url.as_ref(),
+                    &s[link.start_index..link.end_index],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            links,
+            vec![
+                ("LoRA", "https://arxiv.org/abs/2106.09685", "[LoRA][lora]"),
+                ("the  Paper", "https://fake.io/paper", "[the  Paper][]"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("![img](https://i.io/a.png)[after](https://a.io)", vec!["https://a.io"])]
+    #[case("Nice\\![link](https://l.io)", vec!["https://l.io"])]
+    #[case("![logo][ref] [site][ref]\n\n[ref]: https://r.io", vec!["https://r.io"])]
+    fn test_find_md_links_skips_images(
+        #[case] content: &str,
+        #[case] expected: Vec<&str>,
+    ) -> Result<()> {
+        let urls = find_md_links(content)
+            .map(|link| link.url)
+            .collect::<Vec<_>>();
+        assert_eq!(urls, expected);
+
+        let links = find_md_links(content)
+            .map(|link| &content[link.start_index..link.start_index + 1])
+            .collect::<Vec<_>>();
+        assert!(links.iter().all(|start| *start == "["));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_replace_all_autolinks() -> Result<()> {
+        let content = "Go to <https://fake.io/a?b=c> or <mailto:info@fake.io>, \
+                       not <./local.md> or `<https://code.io>`.\n\n[ref]: <https://def.io>\n";
+        let new_content = replace_all(
+            content,
+            &HelperContext {
+                config: &AIPRConfig::default(),
+                book: &BookInfo::default(),
+                chapter: &ChapterInfo::default(),
+            },
+        )?;
+
+        let expected = "Go to <a href=\"https://fake.io/a?b&#x3D;c\" target=\"_blank\" \
+                        rel=\"noopener noreferrer\">https://fake.io/a?b&#x3D;c</a> or \
+                        <a href=\"mailto:info@fake.io\">mailto:info@fake.io</a>, \
+                        not <./local.md> or `<https://code.io>`.\n\n[ref]: <https://def.io>\n";
+        assert_eq!(new_content, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_md_links_schemes() -> Result<()> {
+        let s = "[a](//a.io/x) [b](ftp://b.io) [c](mailto:c@c.io) [d](./d.md) [e](#e) [f](https://f.io)";
+        let links = find_md_links(s).collect::<Vec<_>>();
+        let links = links
+            .iter()
+            .map(|link| (link.url.as_ref(), link.is_external()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            links,
+            vec![
+                ("//a.io/x", true),
+                ("ftp://b.io", true),
+                ("mailto:c@c.io", false),
+                ("https://f.io", true)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(true, "<a href=\"mailto:info@fake.io\">email</a>")]
+    #[case(false, "[email](mailto:info@fake.io)")]
+    fn test_replace_all_mailto(#[case] rewrite_mailto: bool, #[case] expected: &str) -> Result<()> {
+        let config = AIPRConfig {
+            rewrite_mailto,
+            ..Default::default()
+        };
+        let new_content = replace_all(
+            "[email](mailto:info@fake.io)",
+            &HelperContext {
+                config: &config,
+                book: &BookInfo::default(),
+                chapter: &ChapterInfo::default(),
+            },
+        )?;
+        assert_eq!(new_content, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_md_link_render() -> Result<()> {
+        let link = MDLink {
+            start_index: 19,
+            end_index: 58,
+            text: "some text",
+            url: "https://fake.io".into(),
+            ..Default::default()
+        };
+
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let html_string = link.render(&ctx)?;
+        let expected = "<a href=\"https://fake.io\" target=\"_blank\" \
+        rel=\"noopener noreferrer\">some text</a>";
+
+        assert_eq!(html_string, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_md_link_render_icon() -> Result<()> {
+        let config = AIPRConfig {
+            external_link_icon: true,
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let link = |url: &'static str| MDLink {
+            start_index: 0,
+            end_index: 0,
+            text: "site",
+            url: url.into(),
+            ..Default::default()
+        };
+
+        let html_string = link("https://fake.io").render(&ctx)?;
+        assert!(html_string.starts_with("<a href=\"https://fake.io\" target=\"_blank\""));
+        assert!(html_string.contains(">site<svg class=\"aipr-external-link-icon\""));
+        assert!(html_string.ends_with("</svg></a>"));
+
+        let html_string = link("mailto:info@fake.io").render(&ctx)?;
+        assert_eq!(html_string, "<a href=\"mailto:info@fake.io\">site</a>");
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("https://fake.io", "noopener noreferrer nofollow")]
+    #[case("https://blog.vectorinstitute.ai", "noopener")]
+    fn test_md_link_render_link_rules(#[case] url: &str, #[case] rel: &str) -> Result<()> {
+        let config = AIPRConfig {
+            link_rel: "noopener noreferrer nofollow".to_string(),
+            link_rules: vec![config::LinkRule {
+                domains: vec!["vectorinstitute.ai".to_string()],
+                rel: "noopener".to_string(),
+            }],
+            ..Default::default()
+        };
+        let link = MDLink {
+            start_index: 0,
+            end_index: 0,
+            text: "site",
+            url: url.into(),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+
+        let html_string = link.render(&ctx)?;
+        let expected = format!("<a href=\"{url}\" target=\"_blank\" rel=\"{rel}\">site</a>");
+        assert_eq!(html_string, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_process_chapter_appends_default_footer() -> Result<()> {
+        let ch = ChapterSource {
+            name: "LoRA".to_string(),
+            content: "{{#aipr_header reading_time=false}}\n\nSome [text](https://fake.io)."
+                .to_string(),
+            ..Default::default()
+        };
+
+        let config = AIPRConfig::default();
+        let book = BookInfo::default();
+        let content = process_chapter(&ch, &config, &book)?;
+        let ctx = HelperContext {
+            config: &config,
+            book: &book,
+            chapter: &ChapterInfo::default(),
+        };
+
+        assert!(content.starts_with("<div style=\"display: flex;"));
+        assert!(content.contains("<a href=\"https://fake.io\" target=\"_blank\""));
+        assert!(content.ends_with(&AIPRFooterSettings::default().render(&ctx)?));
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("{{#aipr_no_footer}}\n\nSome text.", true)]
+    #[case("---\nfooter: false\n---\n\nSome text.", true)]
+    #[case("Some text.", false)]
+    fn test_process_chapter_without_footer(
+        #[case] content: &str,
+        #[case] footer: bool,
+    ) -> Result<()> {
+        let ch = ChapterSource {
+            name: "License".to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        };
+        let config = AIPRConfig {
+            footer: config::FooterConfig {
+                enabled: footer,
+                ..Default::default()
+            },
+            structured_data: false,
+            ..Default::default()
+        };
+
+        let content = process_chapter(&ch, &config, &BookInfo::default())?;
+        assert_eq!(content.trim(), "Some text.");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_process_chapter_front_matter() -> Result<()> {
+        let ch = ChapterSource {
+            name: "LoRA".to_string(),
+            content: "---\ncolab: nlp/lora.ipynb\ndifficulty: Advanced\ngpu: T4\n---\n\
+                      {{#aipr_header reading_time=false}}\n\n# LoRA"
+                .to_string(),
+            ..Default::default()
+        };
+        let config = AIPRConfig::default();
+        let content = process_chapter(&ch, &config, &BookInfo::default())?;
+
+        assert!(content.starts_with("<div style=\"display: flex;"));
+        assert!(!content.contains("colab: nlp/lora.ipynb"));
+        assert!(content.contains(&config.colab_url("nlp/lora.ipynb").unwrap()));
+        assert!(content.contains("alt=\"Difficulty: Advanced\""));
+        assert!(content.contains("<span class=\"aipr-requirement\">GPU: T4</span>"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_link_render_header_authors() -> Result<()> {
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let links = find_aipr_links("{{#aipr_header author=nerdai,@octocat}} {{#aipr_header}}")
+            .collect::<Vec<_>>();
+
+        let html_string = links[0].render(&ctx)?;
+        assert!(html_string.contains(
+            "<small>By <a target=\"_blank\" href=\"https://github.com/nerdai\">nerdai</a>, \
+             <a target=\"_blank\" href=\"https://github.com/octocat\">octocat</a></small>"
+        ));
+
+        let html_string = links[1].render(&ctx)?;
+        assert!(!html_string.contains("aipr-header-authors"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_link_render_header_word_count() -> Result<()> {
+        let config = AIPRConfig {
+            reading_time_format: ReadingTimeFormat::Range,
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            num_words: 1240,
+            word_count: WordCount {
+                prose_words: 1240,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &BookInfo::default(),
+            chapter: &chapter,
+        };
+        let links = find_aipr_links("{{#aipr_header show_word_count=true}} {{#aipr_header}}")
+            .collect::<Vec<_>>();
+
+        let html_string = links[0].render(&ctx)?;
+        assert!(html_string.contains("<small>Reading time: 5–8 min (1,240 words)</small>"));
+
+        let html_string = links[1].render(&ctx)?;
+        assert!(html_string.contains("<small>Reading time: 5–8 min</small>"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_link_render_header_notebooks() -> Result<()> {
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let links = find_aipr_links(
+            "{{#aipr_header colab=nlp/lora_train.ipynb; nlp/lora_eval.ipynb,download=true}}",
+        )
+        .collect::<Vec<_>>();
+
+        let html_string = links[0].render(&ctx)?;
+        for name in ["lora_train", "lora_eval"] {
+            assert!(html_string.contains(&format!(
+                "<a target=\"_blank\" href=\"https://colab.research.google.com/github/\
+                VectorInstitute/ai-pocket-reference-code/blob/main/notebooks/nlp/{name}.ipynb\">\n      \
+                <img src=\"https://colab.research.google.com/assets/colab-badge.svg\" \
+                alt=\"Open In Colab: {name}\"/>\n      <small>{name}</small>\n    </a>"
+            )));
+            assert!(html_string.contains(&format!("download=\"{name}.ipynb\"")));
+        }
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_link_render_header_edit_link() -> Result<()> {
+        let config = AIPRConfig {
+            edit_link: true,
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            source_path: Some(PathBuf::from("nlp/lora.md")),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &BookInfo::default(),
+            chapter: &chapter,
+        };
+        let links =
+            find_aipr_links("{{#aipr_header}} {{#aipr_header no_edit}}").collect::<Vec<_>>();
+
+        let html_string = links[0].render(&ctx)?;
+        assert!(html_string.contains(
+            "<a target=\"_blank\" href=\"https://github.com/VectorInstitute/\
+            ai-pocket-reference/edit/main/src/nlp/lora.md\">\n      \
+            <img src=\"https://img.shields.io/badge/Edit_this_page-blue?logo=github&style=flat\" \
+            alt=\"Edit this page\"/>"
+        ));
+
+        let html_string = links[1].render(&ctx)?;
+        assert!(!html_string.contains("Edit this page"));
+
+        // drafts have no source file to edit
+        let ctx = HelperContext {
+            chapter: &ChapterInfo::default(),
+            ..ctx
+        };
+        assert!(!links[0].render(&ctx)?.contains("Edit this page"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_content_matches_in_order() -> Result<()> {
+        let s = "[a](https://a.io) {{#aipr_header}} [b](https://b.io) {{#aipr_footer}}";
+        let starts = find_content_matches(s)
+            .map(|m| (matches!(m, ContentMatch::Helper(_)), m.start_index()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            starts,
+            vec![(false, 0), (true, 18), (false, 35), (true, 53)]
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_replace_all_helpers_and_links() -> Result<()> {
+        let content = "{{#aipr_authors authors=octocat}}[site](https://fake.io)";
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let new_content = replace_all(content, &ctx)?;
+
+        let authors = AIPRAuthorsSettings::from_param_str("authors=octocat").render(&ctx)?;
+        let expected = format!(
+            "{authors}<a href=\"https://fake.io\" target=\"_blank\" \
+            rel=\"noopener noreferrer\">site</a>"
         );
+        assert_eq!(new_content, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_replace_all_new_tab_exclude() -> Result<()> {
+        let content =
+            "[home](https://vectorinstitute.ai/) [book](https://ai-pocket-reference.github.io/x) \
+                       [site](https://fake.io)";
+        let config = AIPRConfig {
+            new_tab_exclude: vec![
+                "vectorinstitute.ai".to_string(),
+                "ai-pocket-reference.*".to_string(),
+            ],
+            ..Default::default()
+        };
+        let new_content = replace_all(
+            content,
+            &HelperContext {
+                config: &config,
+                book: &BookInfo::default(),
+                chapter: &ChapterInfo::default(),
+            },
+        )?;
+
+        let expected = "[home](https://vectorinstitute.ai/) [book](https://ai-pocket-reference.github.io/x) \
+                        <a href=\"https://fake.io\" target=\"_blank\" rel=\"noopener noreferrer\">site</a>";
+        assert_eq!(new_content, expected);
+
         Ok(())
     }
 
     #[rstest]
     #[case(
-        "submit_issue=false,colab=nlp/lora.ipynb,reading_time=false",
-        AIPRHeaderSettings {
-            colab: Some("nlp/lora.ipynb".to_string()),
-            submit_issue: false,
-            reading_time: false
-        }
+        true,
+        "Uses <abbr title=\"Reinforcement Learning from Human Feedback\">RLHF</abbr> \
+        via [RLHF](https://fake.io/RLHF), RLHF."
     )]
-    #[case(
-        "colab=nlp/lora.ipynb",
-        AIPRHeaderSettings {
-            colab: Some("nlp/lora.ipynb".to_string()),
+    #[case(false, "Uses RLHF via [RLHF](https://fake.io/RLHF), RLHF.")]
+    fn test_replace_all_abbreviations(
+        #[case] expand_abbreviations: bool,
+        #[case] expected_prefix: &str,
+    ) -> Result<()> {
+        let content = "Uses RLHF via [RLHF](https://fake.io/RLHF), RLHF.";
+        let config = AIPRConfig {
+            abbreviations: BTreeMap::from([(
+                "RLHF".to_string(),
+                "Reinforcement Learning from Human Feedback".to_string(),
+            )]),
             ..Default::default()
-        }
-    )]
-    #[case(
-        "reading_time=falsee",
-        AIPRHeaderSettings {
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo {
+                expand_abbreviations,
+                ..Default::default()
+            },
+        };
+        let new_content = replace_all(content, &ctx)?;
+        let link = MDLink {
+            start_index: 0,
+            end_index: 0,
+            text: "RLHF",
+            url: "https://fake.io/RLHF".into(),
             ..Default::default()
         }
-    )]
-    fn test_aipr_header_settings(
-        #[case] param_str: &str,
-        #[case] expected_setting: AIPRHeaderSettings,
-    ) -> Result<()> {
-        let setting = AIPRHeaderSettings::from_param_str(param_str);
-        assert_eq!(setting, expected_setting);
+        .render(&ctx)?;
+        let expected = expected_prefix.replace("[RLHF](https://fake.io/RLHF)", &link);
+        assert_eq!(new_content, expected);
+
+        let header = AIPRHeaderSettings::from_param_str("abbreviations=false");
+        assert!(!header.abbreviations);
 
         Ok(())
     }
 
     #[rstest]
-    fn test_link_render() -> Result<()> {
-        let link = AIPRLink {
-            start_index: 19,
-            end_index: 58,
-            link_type: AIPRLinkType::Header(AIPRHeaderSettings::from_param_str(
-                "colab=nlp/lora.ipynb",
-            )),
-            link_text: "{{ #aipr_header colab=nlp/lora.ipynb }}",
+    fn test_replace_all_book_templates() -> Result<()> {
+        let mut templates = TEMPLATES.clone();
+        templates.register_template_string("aipr_abbr", "<abbr>{{abbr}}</abbr>")?;
+        templates
+            .register_template_string("md_link_expansion", "<a href=\"{{url}}\">{{text}}</a>")?;
+        let config = AIPRConfig {
+            abbreviations: BTreeMap::from([(
+                "RLHF".to_string(),
+                "Reinforcement Learning from Human Feedback".to_string(),
+            )]),
+            ..Default::default()
+        };
+        let book = BookInfo {
+            templates: Some(templates),
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            expand_abbreviations: true,
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &book,
+            chapter: &chapter,
         };
-        let num_words = 201;
 
-        let html_string = link.render(num_words)?;
-        let expected = "<div style=\"display: flex; justify-content: \
-        space-between; align-items: center; margin-bottom: 2em;\">\n  <div>\n    \
-        <a target=\"_blank\" href=\"https://github.com/VectorInstitute/\
-        ai-pocket-reference/issues/new?template=edit-request.yml\">\n      \
-        <img src=\"https://img.shields.io/badge/Suggest_an_Edit-black?logo=\
-        github&style=flat\" alt=\"Suggest an Edit\"/>\n    </a>\n    \
-        <a target=\"_blank\" href=\"https://colab.research.google.com/github/\
-        VectorInstitute/ai-pocket-reference-code/blob/main/notebooks/nlp/lora.ipynb\
-        \">\n      <img src=\"https://colab.research.google.com/assets/colab-badge.svg\
-        \" alt=\"Open In Colab\"/>\n    </a>\n    <p style=\"margin: 0;\">\
-        <small>Reading time: 1 min</small></p>\n  </div>\n</div>\n";
+        let new_content = replace_all("Uses RLHF via [a](https://fake.io).", &ctx)?;
+        assert_eq!(
+            new_content,
+            "Uses <abbr>RLHF</abbr> via <a href=\"https://fake.io\">a</a>."
+        );
 
-        println!("{:#?}", html_string);
+        Ok(())
+    }
 
-        assert_eq!(html_string, expected);
+    #[rstest]
+    #[case("---\ntags: [nlp]\n---\n# Title", Some(0..20))]
+    #[case("---\ntags: [nlp]\n...\n# Title", Some(0..20))]
+    #[case("# Title\n---\nfoo\n---\n", None)]
+    #[case("---\nnot closed", None)]
+    fn test_find_front_matter_range(
+        #[case] content: &str,
+        #[case] expected: Option<Range<usize>>,
+    ) -> Result<()> {
+        assert_eq!(find_front_matter_range(content), expected);
 
         Ok(())
     }
 
     #[rstest]
-    fn test_link_render_no_colab() -> Result<()> {
-        let link = AIPRLink {
-            start_index: 19,
-            end_index: 58,
-            link_type: AIPRLinkType::Header(AIPRHeaderSettings::default()),
-            link_text: "{{ #aipr_header }}",
-        };
-        let num_words = 301;
+    #[case(false, 6)]
+    #[case(true, 10)]
+    fn test_chapter_word_count(#[case] count_code: bool, #[case] expected: usize) -> Result<()> {
+        let content = "---\nauthors: [a, b, c]\n---\n\
+            {{#aipr_header colab=nlp/lora.ipynb}}\n\
+            <div class=\"note\">Two words</div><!-- a hidden comment -->\n\n\
+            ```\nlet x = 1;\n```\n\
+            Four more prose words.";
 
-        let html_string = link.render(num_words)?;
-        let expected = "<div style=\"display: flex; justify-content: \
-        space-between; align-items: center; margin-bottom: 2em;\">\n  <div>\n    \
-        <a target=\"_blank\" href=\"https://github.com/VectorInstitute/\
-        ai-pocket-reference/issues/new?template=edit-request.yml\">\n      \
-        <img src=\"https://img.shields.io/badge/Suggest_an_Edit-black?logo=\
-        github&style=flat\" alt=\"Suggest an Edit\"/>\n    </a>\n    \
-        <p style=\"margin: 0;\"><small>Reading time: 2 min</small></p>\n  \
-        </div>\n</div>\n";
+        assert_eq!(chapter_word_count(content).words(count_code), expected);
 
-        assert_eq!(html_string, expected);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_find_helper_problems() -> Result<()> {
+        let content = "{{#aipr_headr colab=nlp/lora.ipynb}}\n{{#aipr_footer}}\n\
+            `{{#aipr_colab}}` and \\{{#aipr_escaped}} and {{ #aipr_colab }}\n\
+            {{#include ../snippet.md}}\n\
+            {{#aipr_header colab=nlp/lora.ipynb author=octocat,nerdai colb=x}}\n\
+            {{#aipr_header reading_time=flase,download=\"true\"}}\n\
+            {{#aipr_header no_issue no_logo}}\n\
+            {{#aipr_header colab=https://colab.research.google.com/drive/1a2b}}\n\
+            {{#aipr_header colab=https://gitlab.com/my-org/my-code/-/blob/main/lora.ipynb}}\n\
+            {{#aipr_toc 2}}\n{{#aipr_video title=\"Demo}}\n\
+            {{#aipr_callout type=note,title=Check the license}}\n\
+            {{#aipr_figure src=lora.png caption=Why download matters}}\n\
+            {{#aipr_code lora/train.py lines=1:10}}\n{{#aipr_model_card name=LLaMA}}\n\
+            {{#aipr_footer links=github,discord}}\n\
+            {{#aipr_footer logo=false";
+
+        assert_eq!(
+            find_helper_problems(content, &AIPRConfig::default(), &HelperRegistry::default()),
+            vec![
+                "unknown helper `aipr_headr`, did you mean `aipr_header`?",
+                "unknown helper `aipr_colab`, did you mean `aipr_code`?",
+                "unknown param `colb` of `aipr_header`",
+                "param `reading_time=flase` of `aipr_header` isn't `true` or `false`",
+                "unknown flag `no_logo` of `aipr_header`",
+                "`colab` URL `https://gitlab.com/my-org/my-code/-/blob/main/lora.ipynb` of \
+                `aipr_header` isn't a Colab link or a notebook on github.com",
+                "malformed param `2` of `aipr_toc`, expected `key=value` or a flag",
+                "unclosed quote in the params of `aipr_video`",
+                "unknown link `discord` of `aipr_footer`",
+                "unclosed helper `aipr_footer`",
+            ]
+        );
 
         Ok(())
     }
 
     #[rstest]
-    fn test_link_render_no_colab_no_reading_time() -> Result<()> {
-        let link = AIPRLink {
-            start_index: 19,
-            end_index: 58,
-            link_type: AIPRLinkType::Header(AIPRHeaderSettings::from_param_str(
-                "reading_time=false",
-            )),
-            link_text: "{{ #aipr_header reading_time=false }}",
+    #[case("aipr_headr", "aipr_header")]
+    #[case("aipr_lastupdated", "aipr_last_updated")]
+    #[case("aipr_seriesnav", "aipr_series_nav")]
+    #[case("aipr_callouts", "aipr_callout")]
+    fn test_closest_helper(#[case] name: &str, #[case] expected: &str) -> Result<()> {
+        assert_eq!(closest_helper(name, &HelperRegistry::default()), expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("html", true)]
+    #[case("markdown", false)]
+    #[case("pdf", false)]
+    fn test_supports_renderer(#[case] renderer: &str, #[case] expected: bool) -> Result<()> {
+        let preprocessor = AIPRPreprocessor::new();
+        let dir =
+            std::env::temp_dir().join(format!("aipr-supports-{renderer}-{}", std::process::id()));
+        assert_eq!(preprocessor.supports(&dir, renderer)?, expected);
+
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("book.toml"),
+            "[preprocessor.ai-pocket-reference]\nexpand_renderers = [\"html\", \"pdf\"]\n",
+        )?;
+        assert_eq!(
+            preprocessor.supports(&dir, renderer)?,
+            renderer != "markdown"
+        );
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_check() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aipr-check-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src/nlp"))?;
+        std::fs::create_dir_all(root.join("code/notebooks/nlp"))?;
+        std::fs::write(root.join("code/notebooks/nlp/lora.ipynb"), "{}")?;
+        std::fs::write(
+            root.join("book.toml"),
+            "[book]\ntitle = \"Test\"\n\n\
+            [preprocessor.ai-pocket-reference]\ncode_repo_path = \"code\"\noffline = true\n",
+        )?;
+        std::fs::write(
+            root.join("src/SUMMARY.md"),
+            "# Summary\n\n- [LoRA](nlp/lora.md)\n- [QLoRA](nlp/qlora.md)\n",
+        )?;
+        std::fs::write(
+            root.join("src/nlp/lora.md"),
+            "{{#aipr_header colab=nlp/lora.ipynb}}\n\n# LoRA\n\nSee {{#aipr_chapter nlp/qlora.md}}.\n",
+        )?;
+        std::fs::write(
+            root.join("src/nlp/qlora.md"),
+            "{{#aipr_header colab=nlp/qlora.ipynb,colb=x}}\n\n# QLoRA\n\n\
+            {{#aipr_prereqs prereqs=nlp/lora.md,nlp/quantization.md}}\n",
+        )?;
+
+        let problems = AIPRPreprocessor::new()
+            .check(&root)?
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            vec![
+                "QLoRA: unknown param `colb` of `aipr_header`",
+                "QLoRA: notebook `nlp/qlora.ipynb` is not in VectorInstitute/ai-pocket-reference-code",
+                "QLoRA: chapter `nlp/quantization.md` is not in the book",
+            ]
+        );
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_stats() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aipr-stats-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src/nlp"))?;
+        std::fs::write(root.join("book.toml"), "[book]\ntitle = \"Test\"\n")?;
+        std::fs::write(
+            root.join("src/SUMMARY.md"),
+            "# Summary\n\n- [LoRA](nlp/lora.md)\n- [QLoRA](nlp/qlora.md)\n",
+        )?;
+        std::fs::write(
+            root.join("src/nlp/lora.md"),
+            "{{#aipr_header colab=nlp/lora.ipynb}}\n\n# LoRA\n\n\
+            Read [the paper](https://arxiv.org/abs/2106.09685) or [QLoRA](qlora.md).\n\n\
+            ![Diagram](lora.png)\n\n```python\nimport peft\n```\n",
+        )?;
+        std::fs::write(root.join("src/nlp/qlora.md"), "# QLoRA\n")?;
+
+        let stats = AIPRPreprocessor::new().stats(&root)?;
+        assert_eq!(
+            stats,
+            vec![
+                ChapterStats {
+                    path: "nlp/lora.md".to_string(),
+                    title: "LoRA".to_string(),
+                    word_count: 6,
+                    reading_time: 0,
+                    code_lines: 1,
+                    equations: 0,
+                    cjk_chars: 0,
+                    images: 1,
+                    external_links: 1,
+                    notebook: Some("nlp/lora.ipynb".to_string()),
+                },
+                ChapterStats {
+                    path: "nlp/qlora.md".to_string(),
+                    title: "QLoRA".to_string(),
+                    word_count: 1,
+                    reading_time: 0,
+                    code_lines: 0,
+                    equations: 0,
+                    cjk_chars: 0,
+                    images: 0,
+                    external_links: 0,
+                    notebook: None,
+                },
+            ]
+        );
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[rstest]
+    // as counted by `stats`
+    #[case(16, 15, 1)]
+    // by tools counting a run of CJK characters as one word
+    #[case(3, 15, 0)]
+    fn test_chapter_stats_counts(
+        #[case] word_count: usize,
+        #[case] cjk_chars: usize,
+        #[case] expected: usize,
+    ) -> Result<()> {
+        // "# 低秩适配\n\nLoRA 冻结预训练模型的权重。"
+        let stats = ChapterStats {
+            path: "nlp/lora.md".to_string(),
+            title: "低秩适配".to_string(),
+            word_count,
+            reading_time: 0,
+            code_lines: 0,
+            equations: 0,
+            cjk_chars,
+            images: 0,
+            external_links: 0,
+            notebook: None,
         };
-        let num_words = 200;
 
-        let html_string = link.render(num_words)?;
-        let expected = "<div style=\"display: flex; justify-content: \
-        space-between; align-items: center; margin-bottom: 2em;\">\n  <div>\n    \
-        <a target=\"_blank\" href=\"https://github.com/VectorInstitute/\
-        ai-pocket-reference/issues/new?template=edit-request.yml\">\n      \
-        <img src=\"https://img.shields.io/badge/Suggest_an_Edit-black?logo=\
-        github&style=flat\" alt=\"Suggest an Edit\"/>\n    </a>\n  \
-        </div>\n</div>\n";
+        let counts = stats.counts();
+        assert_eq!(counts.prose_words, expected);
+        assert_eq!(counts.cjk_chars, cjk_chars);
+        render_header(
+            &AIPRHeaderSettings::default(),
+            &stats,
+            &AIPRConfig::default(),
+        )?;
 
-        assert_eq!(html_string, expected);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_header() -> Result<()> {
+        let config = AIPRConfig {
+            language: Some("fr".to_string()),
+            ..Default::default()
+        };
+        let stats = ChapterStats {
+            path: "nlp/lora.md".to_string(),
+            title: "LoRA".to_string(),
+            word_count: 1240,
+            reading_time: 6,
+            code_lines: 12,
+            equations: 0,
+            cjk_chars: 0,
+            images: 1,
+            external_links: 0,
+            notebook: None,
+        };
+        let settings =
+            AIPRHeaderSettings::from_param_str("colab=nlp/lora.ipynb,difficulty=beginner");
+
+        // as the preprocessor renders it
+        let mut book = BookInfo::default();
+        book.set_catalog(Catalog::new("fr", &messages(), None, &BTreeMap::new()));
+        let chapter = ChapterInfo {
+            num_words: 1240,
+            word_count: WordCount {
+                prose_words: 1240,
+                code_lines: 12,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &book,
+            chapter: &chapter,
+        };
+        let links = find_aipr_links("{{#aipr_header colab=nlp/lora.ipynb,difficulty=beginner}}")
+            .collect::<Vec<_>>();
+
+        let html_string = render_header(&settings, &stats, &config)?;
+        assert!(html_string.contains("Temps de lecture: 6 min"));
+        assert_eq!(html_string, links[0].render(&ctx)?);
 
         Ok(())
     }
 
     #[rstest]
-    fn test_finds_md_link(simple_book_content: String) -> Result<()> {
-        let res = find_md_links(&simple_book_content[..]).collect::<Vec<_>>();
-        println!("\nOUTPUT: {res:?}\n");
+    fn test_check_helpers() -> Result<()> {
+        let chapters = [ChapterSource {
+            name: "LoRA".to_string(),
+            content: "{{#aipr_headr}}".to_string(),
+            ..Default::default()
+        }];
+        let helpers = HelperRegistry::default();
+        check_helpers(&chapters, &AIPRConfig::default(), &helpers)?;
 
+        let config = AIPRConfig {
+            strict: true,
+            ..Default::default()
+        };
+        let err = check_helpers(&chapters, &config, &helpers).unwrap_err();
         assert_eq!(
-            res,
-            vec![MDLink {
-                start_index: 71,
-                end_index: 99,
-                text: "text with",
-                url: "https://fake.io"
-            }]
+            err.to_string(),
+            "Invalid helper in `LoRA`: unknown helper `aipr_headr`, did you mean `aipr_header`?"
         );
 
         Ok(())
     }
 
+    struct Badge {
+        text: String,
+    }
+
+    impl HelperHandler for Badge {
+        fn name() -> &'static str {
+            "aipr_badge"
+        }
+
+        fn parse(params: &str) -> anyhow::Result<Self> {
+            anyhow::ensure!(!params.is_empty(), "`aipr_badge` needs a text");
+            Ok(Self {
+                text: params.to_string(),
+            })
+        }
+
+        fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+            Ok(format!(
+                "<span class=\"badge\">{} ({})</span>",
+                ctx.translate(&self.text),
+                ctx.chapter_name()
+            ))
+        }
+    }
+
     #[rstest]
-    fn test_md_link_render() -> Result<()> {
-        let link = MDLink {
-            start_index: 19,
-            end_index: 58,
-            text: "some text",
-            url: "https://fake.io",
+    fn test_custom_helper() -> Result<()> {
+        let preprocessor = AIPRPreprocessor::new().with_helper::<Badge>();
+        let book = BookInfo {
+            helpers: preprocessor.helpers.clone(),
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            name: "LoRA".to_string(),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book,
+            chapter: &chapter,
         };
 
-        let html_string = link.render()?;
-        let expected = "<a href=\"https://fake.io\" target=\"_blank\" \
-        rel=\"noopener noreferrer\">some text</a>";
+        let s = "{{#aipr_badge New}} {{#aipr_badge}} {{#aipr_badg x}}";
+        let links = find_aipr_links(s).collect::<Vec<_>>();
+        assert_eq!(links.len(), 3);
+        assert_eq!(
+            links[0].render(&ctx)?,
+            "<span class=\"badge\">New (LoRA)</span>"
+        );
+        assert!(links[1].render(&ctx).is_err());
+        // helpers that weren't added are left as they are
+        assert_eq!(links[2].render(&ctx)?, "{{#aipr_badg x}}");
 
-        assert_eq!(html_string, expected);
+        assert_eq!(
+            find_helper_problems(s, &AIPRConfig::default(), &preprocessor.helpers),
+            vec!["unknown helper `aipr_badg`, did you mean `aipr_badge`?"]
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[should_panic(expected = "helper `aipr_header` doesn't start with `aipr_` or is built in")]
+    fn test_custom_helper_built_in_name() {
+        struct Header;
+
+        impl HelperHandler for Header {
+            fn name() -> &'static str {
+                "aipr_header"
+            }
+
+            fn parse(_params: &str) -> anyhow::Result<Self> {
+                Ok(Self)
+            }
+
+            fn render(&self, _ctx: &HelperContext) -> anyhow::Result<String> {
+                Ok(String::new())
+            }
+        }
+
+        AIPRPreprocessor::new().with_helper::<Header>();
+    }
+
+    #[rstest]
+    fn test_strip_all_aipr_links() -> Result<()> {
+        let content = "# Title\n\n{{#aipr_header colab=nlp/lora.ipynb}}\n\n\
+            Text with `{{#aipr_header}}` and [link](https://fake.io).\n{{#aipr_footer}}";
+
+        let stripped = strip_all_aipr_links(content);
+        let expected = "# Title\n\n\n\n\
+            Text with `{{#aipr_header}}` and [link](https://fake.io).\n";
+
+        assert_eq!(stripped, expected);
+        assert_eq!(
+            strip_all_aipr_links("Use \\{{#aipr_footer logo=false}}."),
+            "Use {{#aipr_footer logo=false}}."
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_replace_all_escaped_helpers() -> Result<()> {
+        let content = "Write \\{{#aipr_header colab=nlp/lora.ipynb}} then {{#aipr_last_updated}}, \
+                       not \\{{#include file.rs}}.";
+        let new_content = replace_all(
+            content,
+            &HelperContext {
+                config: &AIPRConfig::default(),
+                book: &BookInfo::default(),
+                chapter: &ChapterInfo::default(),
+            },
+        )?;
+
+        assert_eq!(
+            new_content,
+            "Write {{#aipr_header colab=nlp/lora.ipynb}} then , not \\{{#include file.rs}}."
+        );
 
         Ok(())
     }
@@ -568,7 +4963,14 @@ mod tests {
             whereas ![this](https://not-covered.io), and \
             neither is \\[this\\](http://not-covered.io).";
 
-        let new_content = replace_all_md_links(content);
+        let new_content = replace_all(
+            content,
+            &HelperContext {
+                config: &AIPRConfig::default(),
+                book: &BookInfo::default(),
+                chapter: &ChapterInfo::default(),
+            },
+        )?;
         let expected = "This is <a href=\"https://good.io\" target=\"_blank\" \
          rel=\"noopener noreferrer\">good link</a>, whereas ![this](https://not-covered.io), \
          and neither is \\[this\\](http://not-covered.io).";
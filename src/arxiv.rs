@@ -0,0 +1,158 @@
+//! arXiv papers shown by `{{#aipr_arxiv}}`, read from the Atom feed of the
+//! arXiv API.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Authors listed on a card before the rest are abbreviated as "et al.".
+const MAX_AUTHORS: usize = 3;
+
+/// Whether `id` is an arXiv identifier, new style (`2106.09685v2`) or old
+/// style (`hep-th/9901001`).
+pub(crate) fn is_arxiv_id(id: &str) -> bool {
+    static ID_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(?:\d{4}\.\d{4,5}|[a-z-]+(?:\.[A-Z]{2})?/\d{7})(?:v\d+)?$").unwrap()
+    });
+    ID_RE.is_match(id)
+}
+
+pub(crate) fn abs_url(id: &str) -> String {
+    format!("https://arxiv.org/abs/{id}")
+}
+
+pub(crate) fn api_url(id: &str) -> String {
+    format!("https://export.arxiv.org/api/query?id_list={id}")
+}
+
+/// Path, within the cache directory, of the API response for `id`.
+pub(crate) fn cache_key(id: &str) -> PathBuf {
+    PathBuf::from("arxiv").join(format!("{id}.xml"))
+}
+
+/// A paper, as shown on its card.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct ArxivPaper {
+    pub title: String,
+    pub authors: String,
+    pub year: String,
+    #[serde(rename = "abstract")]
+    pub summary: String,
+}
+
+impl ArxivPaper {
+    /// The paper of the first entry of an arXiv API Atom feed.
+    pub fn from_atom(xml: &str) -> anyhow::Result<Self> {
+        static ENTRY_RE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"(?s)<entry>(.*?)</entry>").unwrap());
+        static AUTHOR_RE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"(?s)<author>\s*<name>(.*?)</name>").unwrap());
+
+        let entry = ENTRY_RE
+            .captures(xml)
+            .and_then(|cap| cap.get(1))
+            .ok_or_else(|| anyhow::anyhow!("No entry in arXiv response"))?
+            .as_str();
+        let element = |name: &str| {
+            let re = Regex::new(&format!(r"(?s)<{name}>(.*?)</{name}>")).ok()?;
+            Some(clean_text(re.captures(entry)?.get(1)?.as_str()))
+        };
+        // the API answers unknown ids with an entry titled "Error"
+        let title = element("title").filter(|title| title != "Error");
+        let title = title.ok_or_else(|| anyhow::anyhow!("No paper in arXiv response"))?;
+
+        let names = AUTHOR_RE
+            .captures_iter(entry)
+            .map(|cap| clean_text(&cap[1]))
+            .collect::<Vec<_>>();
+        let authors = match names.len() {
+            n if n > MAX_AUTHORS => format!("{} et al.", names[..MAX_AUTHORS].join(", ")),
+            _ => names.join(", "),
+        };
+
+        Ok(Self {
+            title,
+            authors,
+            year: element("published")
+                .map(|date| date.chars().take(4).collect())
+                .unwrap_or_default(),
+            summary: element("summary").unwrap_or_default(),
+        })
+    }
+}
+
+/// Unescapes XML entities and collapses whitespace.
+fn clean_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title type="html">ArXiv Query: id_list=2106.09685</title>
+  <entry>
+    <id>http://arxiv.org/abs/2106.09685v2</id>
+    <published>2021-06-17T17:37:18Z</published>
+    <title>LoRA: Low-Rank Adaptation of
+  Large Language Models</title>
+    <summary>  An important paradigm of natural language processing &amp; more.
+</summary>
+    <author>
+      <name>Edward J. Hu</name>
+    </author>
+    <author>
+      <name>Yelong Shen</name>
+    </author>
+    <author>
+      <name>Phillip Wallis</name>
+    </author>
+    <author>
+      <name>Zeyuan Allen-Zhu</name>
+    </author>
+  </entry>
+</feed>
+"#;
+
+    #[rstest]
+    fn test_paper_from_atom() -> Result<()> {
+        let paper = ArxivPaper::from_atom(ATOM)?;
+        assert_eq!(
+            paper,
+            ArxivPaper {
+                title: "LoRA: Low-Rank Adaptation of Large Language Models".to_string(),
+                authors: "Edward J. Hu, Yelong Shen, Phillip Wallis et al.".to_string(),
+                year: "2021".to_string(),
+                summary: "An important paradigm of natural language processing & more.".to_string(),
+            }
+        );
+        assert!(ArxivPaper::from_atom("<feed><entry><title>Error</title></entry></feed>").is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("2106.09685", true)]
+    #[case("2106.09685v2", true)]
+    #[case("hep-th/9901001", true)]
+    #[case("math.GT/0309136", true)]
+    #[case("2106.096", false)]
+    #[case("../etc/passwd", false)]
+    fn test_is_arxiv_id(#[case] id: &str, #[case] expected: bool) -> Result<()> {
+        assert_eq!(is_arxiv_id(id), expected);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+(function () {
+  document.querySelectorAll(".aipr-feedback[data-endpoint]").forEach(function (widget) {
+    // footers can be composed more than once, each adding this script
+    if (widget.dataset.bound) {
+      return;
+    }
+    widget.dataset.bound = "true";
+    var votes = widget.querySelectorAll(".aipr-feedback-vote");
+    votes.forEach(function (vote) {
+      vote.addEventListener("click", function () {
+        fetch(widget.dataset.endpoint, {
+          method: "POST",
+          headers: { "Content-Type": "application/json" },
+          body: JSON.stringify({
+            path: widget.dataset.path,
+            helpful: vote.dataset.helpful === "true",
+            url: window.location.href,
+          }),
+        }).catch(function () {});
+        votes.forEach(function (vote) {
+          vote.disabled = true;
+        });
+        widget.querySelector(".aipr-feedback-thanks").hidden = false;
+      });
+    });
+  });
+})();
@@ -0,0 +1,18 @@
+(function () {
+  document.querySelectorAll(".aipr-quiz-question").forEach(function (question) {
+    var answer = question.dataset.answer;
+    var feedback = question.querySelector(".aipr-quiz-feedback");
+    var result = question.querySelector(".aipr-quiz-result");
+    question.querySelector(".aipr-quiz-reveal").addEventListener("click", function () {
+      var selected = question.querySelector("input:checked");
+      var correct = selected !== null && selected.value === answer;
+      question.classList.toggle("aipr-quiz-correct", correct);
+      question.classList.toggle("aipr-quiz-incorrect", !correct);
+      question.querySelectorAll("label").forEach(function (label, index) {
+        label.classList.toggle("aipr-quiz-answer", String(index) === answer);
+      });
+      result.textContent = correct ? "Correct!" : "Not quite.";
+      feedback.hidden = false;
+    });
+  });
+})();
@@ -0,0 +1,179 @@
+//! Benchmark results rendered by `{{#aipr_benchmark}}`, read from a CSV file
+//! with a header row, or a JSON array of objects:
+//!
+//! ```json
+//! [
+//!   {"Model": "BERT-base", "MNLI": 84.6, "QQP": 71.2},
+//!   {"Model": "RoBERTa-base", "MNLI": 87.6, "QQP": 91.9}
+//! ]
+//! ```
+
+use anyhow::Context;
+use serde_json::Value;
+use std::path::Path;
+
+/// A table of results, with every cell as written in the data file.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Read the table at `path`, as JSON if it ends in `.json` and as CSV
+    /// otherwise.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read results {}", path.display()))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json(&contents),
+            _ => Self::from_csv(&contents),
+        }
+        .with_context(|| format!("Failed to parse results {}", path.display()))
+    }
+
+    pub fn from_csv(contents: &str) -> anyhow::Result<Self> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(contents.as_bytes());
+        let columns = reader
+            .headers()?
+            .iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let rows = reader
+            .records()
+            .map(|record| Ok(record?.iter().map(str::to_string).collect()))
+            .collect::<anyhow::Result<Vec<Vec<_>>>>()?;
+        Ok(Self { columns, rows })
+    }
+
+    /// Columns are ordered by first appearance; missing values are empty.
+    pub fn from_json(contents: &str) -> anyhow::Result<Self> {
+        let records: Vec<serde_json::Map<String, Value>> = serde_json::from_str(contents)?;
+        let mut columns: Vec<String> = vec![];
+        for key in records.iter().flat_map(|record| record.keys()) {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+        let rows = records
+            .iter()
+            .map(|record| {
+                columns
+                    .iter()
+                    .map(|column| match record.get(column) {
+                        Some(Value::String(value)) => value.clone(),
+                        Some(Value::Null) | None => String::new(),
+                        Some(value) => value.to_string(),
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok(Self { columns, rows })
+    }
+
+    /// The table restricted to `columns`, in that order.
+    pub fn select(&self, columns: &[String]) -> anyhow::Result<Self> {
+        let indices = columns
+            .iter()
+            .map(|column| {
+                self.columns
+                    .iter()
+                    .position(|c| c == column)
+                    .with_context(|| format!("No column `{column}` in results"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            columns: columns.to_vec(),
+            rows: self
+                .rows
+                .iter()
+                .map(|row| indices.iter().map(|&i| row[i].clone()).collect())
+                .collect(),
+        })
+    }
+
+    /// Whether each cell holds the best value of its column. Only columns
+    /// whose every value is numeric have a best value, the highest one unless
+    /// the column is in `lower_is_better`.
+    pub fn best_cells(&self, lower_is_better: &[String]) -> Vec<Vec<bool>> {
+        let mut best = vec![vec![false; self.columns.len()]; self.rows.len()];
+        for (index, column) in self.columns.iter().enumerate() {
+            let values = self
+                .rows
+                .iter()
+                .map(|row| parse_number(&row[index]))
+                .collect::<Option<Vec<_>>>();
+            let Some(values) = values.filter(|values| !values.is_empty()) else {
+                continue;
+            };
+            let lower = lower_is_better.contains(column);
+            let best_value = values
+                .iter()
+                .copied()
+                .reduce(|a, b| if lower { a.min(b) } else { a.max(b) })
+                .unwrap_or_default();
+            for (row, value) in values.iter().enumerate() {
+                best[row][index] = *value == best_value;
+            }
+        }
+        best
+    }
+}
+
+/// `84.6` or `84.6%` as a number.
+fn parse_number(value: &str) -> Option<f64> {
+    value.trim().trim_end_matches('%').parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_table_from_csv_and_json() -> Result<()> {
+        let csv_table =
+            Table::from_csv("Model, MNLI, QQP\nBERT, 84.6, 71.2\n\"Ro,BERTa\", 87.6, 91.9\n")?;
+        let json_table = Table::from_json(
+            r#"[{"Model": "BERT", "MNLI": 84.6, "QQP": "71.2"},
+                {"Model": "Ro,BERTa", "MNLI": 87.6, "QQP": 91.9}]"#,
+        )?;
+        assert_eq!(csv_table, json_table);
+        assert_eq!(csv_table.columns, vec!["Model", "MNLI", "QQP"]);
+        assert_eq!(csv_table.rows[1], vec!["Ro,BERTa", "87.6", "91.9"]);
+
+        let selected = csv_table.select(&["QQP".to_string(), "Model".to_string()])?;
+        assert_eq!(selected.rows[0], vec!["71.2", "BERT"]);
+        assert!(csv_table.select(&["SST-2".to_string()]).is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_best_cells() -> Result<()> {
+        let table = Table::from_csv("Model,Accuracy,Latency\nA,90%,12\nB,85%,8\nC,90%,n/a\n")?;
+        assert_eq!(
+            table.best_cells(&[]),
+            vec![
+                vec![false, true, false],
+                vec![false, false, false],
+                vec![false, true, false],
+            ]
+        );
+
+        let table = table.select(&["Latency".to_string()])?;
+        let table = Table {
+            rows: table.rows[..2].to_vec(),
+            ..table
+        };
+        assert_eq!(
+            table.best_cells(&["Latency".to_string()]),
+            vec![vec![false], vec![true]]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+//! Bibliography read from a BibTeX file in the book root, used by
+//! `{{#aipr_cite}}` to number citations and list references per chapter.
+//!
+//! Only the parts of BibTeX found in typical reference managers' exports are
+//! supported: entries with braced, quoted or bare field values. `@string`,
+//! `@preamble` and `@comment` blocks are skipped.
+
+use anyhow::Context;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A BibTeX entry, with field names lowercased and values stripped of braces.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BibEntry {
+    pub key: String,
+    pub entry_type: String,
+    pub fields: HashMap<String, String>,
+}
+
+impl BibEntry {
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+
+    /// Given and last names of the authors, e.g. `("Noam", "Shazeer")` for
+    /// both `Shazeer, Noam` and `Noam Shazeer`. Single names are last names.
+    pub fn names(&self) -> Vec<(String, String)> {
+        self.field("author")
+            .into_iter()
+            .flat_map(|authors| authors.split(" and "))
+            .map(|author| match author.split_once(',') {
+                Some((last, first)) => (first.trim().to_string(), last.trim().to_string()),
+                None => match author.trim().rsplit_once(' ') {
+                    Some((first, last)) => (first.to_string(), last.to_string()),
+                    None => (String::new(), author.trim().to_string()),
+                },
+            })
+            .collect()
+    }
+
+    /// Authors with abbreviated given names, e.g. `Vaswani, Ashish and Noam Shazeer`
+    /// becomes `Vaswani, A., Shazeer, N.`.
+    pub fn authors(&self) -> Option<String> {
+        self.field("author")?;
+        let authors = self
+            .names()
+            .into_iter()
+            .map(|(first, last)| {
+                if first.is_empty() {
+                    last
+                } else {
+                    format!("{last}, {}", initials(&first))
+                }
+            })
+            .collect::<Vec<_>>();
+        Some(authors.join(", "))
+    }
+
+    /// Journal, proceedings or publisher the entry appeared in.
+    pub fn venue(&self) -> Option<&str> {
+        ["journal", "booktitle", "publisher", "howpublished"]
+            .iter()
+            .find_map(|name| self.field(name))
+    }
+
+    /// Link to the entry, from its `url` or `doi` field.
+    pub fn url(&self) -> Option<String> {
+        self.field("url")
+            .map(str::to_string)
+            .or_else(|| Some(format!("https://doi.org/{}", self.field("doi")?)))
+    }
+}
+
+/// `Ashish Kumar` as `A. K.`.
+pub(crate) fn initials(names: &str) -> String {
+    names
+        .split_whitespace()
+        .filter_map(|name| name.chars().next())
+        .map(|initial| format!("{initial}."))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Bibliography {
+    entries: HashMap<String, BibEntry>,
+}
+
+impl Bibliography {
+    /// Read the bibliography at `path`. A missing file is an empty bibliography.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read bibliography {}", path.display()))?;
+        Self::from_bibtex(&contents)
+            .with_context(|| format!("Failed to parse bibliography {}", path.display()))
+    }
+
+    pub fn from_bibtex(contents: &str) -> anyhow::Result<Self> {
+        let entries = parse_bibtex(contents)?
+            .into_iter()
+            .map(|entry| (entry.key.clone(), entry))
+            .collect();
+        Ok(Self { entries })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&BibEntry> {
+        self.entries.get(key)
+    }
+}
+
+/// Parses the entries of a BibTeX file.
+fn parse_bibtex(input: &str) -> anyhow::Result<Vec<BibEntry>> {
+    let mut parser = Parser { input, pos: 0 };
+    let mut entries = vec![];
+
+    while let Some(at) = input[parser.pos..].find('@') {
+        parser.pos += at + 1;
+        let entry_type = parser.take_while(|c| c.is_alphanumeric()).to_lowercase();
+        parser.skip_whitespace();
+        let close = match parser.next_char() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => anyhow::bail!("Expected `{{` after `@{entry_type}` at byte {}", parser.pos),
+        };
+        if matches!(entry_type.as_str(), "comment" | "string" | "preamble") {
+            parser.skip_balanced(close)?;
+            continue;
+        }
+
+        let key = parser.take_while(|c| c != ',' && c != close).trim();
+        anyhow::ensure!(!key.is_empty(), "Missing key of `@{entry_type}` entry");
+        let key = key.to_string();
+        let mut fields = HashMap::new();
+        loop {
+            parser.skip_whitespace();
+            match parser.next_char() {
+                Some(',') => continue,
+                Some(c) if c == close => break,
+                Some(_) => parser.pos -= 1,
+                None => anyhow::bail!("Unclosed entry `{key}`"),
+            }
+            let name = parser.take_while(|c| c != '=' && c != close).trim();
+            anyhow::ensure!(
+                parser.next_char() == Some('='),
+                "Expected `=` after field `{name}` of entry `{key}`"
+            );
+            let name = name.to_lowercase();
+            parser.skip_whitespace();
+            let value = parser
+                .value(close)
+                .with_context(|| format!("Invalid field `{name}` of entry `{key}`"))?;
+            fields.insert(name, clean_value(&value));
+        }
+
+        entries.push(BibEntry {
+            key,
+            entry_type,
+            fields,
+        });
+    }
+    Ok(entries)
+}
+
+/// Strips braces from a field value and collapses whitespace.
+fn clean_value(value: &str) -> String {
+    value
+        .replace(['{', '}'], "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.input[self.pos..].chars().next()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let rest = &self.input[self.pos..];
+        let len = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    /// Skips to after the `close` matching an already consumed opening.
+    fn skip_balanced(&mut self, close: char) -> anyhow::Result<()> {
+        let open = if close == '}' { '{' } else { '(' };
+        let mut depth = 0;
+        while let Some(c) = self.next_char() {
+            match c {
+                c if c == open => depth += 1,
+                c if c == close && depth == 0 => return Ok(()),
+                c if c == close => depth -= 1,
+                _ => {}
+            }
+        }
+        anyhow::bail!("Unbalanced `{open}`")
+    }
+
+    /// A braced, quoted or bare field value, with `#` concatenations joined.
+    fn value(&mut self, close: char) -> anyhow::Result<String> {
+        let mut value = String::new();
+        loop {
+            match self.next_char() {
+                Some('{') => {
+                    let start = self.pos;
+                    self.skip_balanced('}')?;
+                    value.push_str(&self.input[start..self.pos - 1]);
+                }
+                Some('"') => {
+                    let start = self.pos;
+                    let mut depth = 0;
+                    loop {
+                        match self.next_char() {
+                            Some('{') => depth += 1,
+                            Some('}') => depth -= 1,
+                            Some('"') if depth == 0 => break,
+                            Some(_) => {}
+                            None => anyhow::bail!("Unclosed `\"`"),
+                        }
+                    }
+                    value.push_str(&self.input[start..self.pos - 1]);
+                }
+                Some(_) => {
+                    self.pos -= 1;
+                    let bare = self.take_while(|c| c != ',' && c != close && c != '#');
+                    value.push_str(bare.trim());
+                }
+                None => anyhow::bail!("Missing value"),
+            }
+            self.skip_whitespace();
+            if self.input[self.pos..].starts_with('#') {
+                self.pos += 1;
+                self.skip_whitespace();
+                continue;
+            }
+            return Ok(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    const BIBTEX: &str = r#"
+@comment{exported by a reference manager}
+@inproceedings{vaswani2017,
+  title = {Attention Is {All} You Need},
+  author = {Vaswani, Ashish and Noam Shazeer},
+  booktitle = "Advances in Neural Information Processing Systems",
+  year = 2017,
+  url = {https://arxiv.org/abs/1706.03762},
+}
+
+@article{hu2021,
+  title = "LoRA: Low-Rank " # "Adaptation",
+  author = {Hu, Edward J.},
+  journal = {arXiv},
+  doi = {10.48550/arXiv.2106.09685}
+}
+"#;
+
+    #[rstest]
+    fn test_parse_bibtex() -> Result<()> {
+        let bibliography = Bibliography::from_bibtex(BIBTEX)?;
+
+        let vaswani = bibliography.get("vaswani2017").unwrap();
+        assert_eq!(vaswani.entry_type, "inproceedings");
+        assert_eq!(vaswani.field("title"), Some("Attention Is All You Need"));
+        assert_eq!(vaswani.field("year"), Some("2017"));
+        assert_eq!(
+            vaswani.authors().as_deref(),
+            Some("Vaswani, A., Shazeer, N.")
+        );
+        assert_eq!(
+            vaswani.venue(),
+            Some("Advances in Neural Information Processing Systems")
+        );
+        assert_eq!(
+            vaswani.url().as_deref(),
+            Some("https://arxiv.org/abs/1706.03762")
+        );
+
+        let hu = bibliography.get("hu2021").unwrap();
+        assert_eq!(hu.field("title"), Some("LoRA: Low-Rank Adaptation"));
+        assert_eq!(hu.authors().as_deref(), Some("Hu, E. J."));
+        assert_eq!(
+            hu.url().as_deref(),
+            Some("https://doi.org/10.48550/arXiv.2106.09685")
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("@article{broken, title = {Unclosed}")]
+    #[case("@article{broken, title {No equals}}")]
+    fn test_parse_bibtex_errors(#[case] bibtex: &str) -> Result<()> {
+        assert!(Bibliography::from_bibtex(bibtex).is_err());
+
+        Ok(())
+    }
+}
@@ -3,8 +3,9 @@ use clap::{Parser, Subcommand};
 use mdbook::errors::Error;
 use mdbook::preprocess::CmdPreprocessor;
 use mdbook::preprocess::Preprocessor;
-use mdbook_ai_pocket_reference::AIPRPreprocessor;
+use mdbook_ai_pocket_reference::{init_chapter, install, AIPRPreprocessor};
 use std::io;
+use std::path::PathBuf;
 use std::process;
 
 /// mdbook preprocessor for AI-Pocket-Reference
@@ -19,6 +20,48 @@ struct Cli {
 enum Commands {
     /// Check whether a renderer is supported by this preprocessor
     Supports { renderer: String },
+    /// Check a book's helpers, notebook paths and chapter references without
+    /// building it, exiting with 1 if any problems are found
+    Check {
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+    },
+    /// Add the preprocessor and its stylesheet to a book's book.toml
+    Install {
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+        /// Don't write the stylesheet or add it to `output.html`
+        #[arg(long)]
+        no_css: bool,
+    },
+    /// Print each chapter's word count, reading time, lines of code, images,
+    /// external links and notebook
+    Stats {
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print a gettext template (.pot) of the text helpers add to chapters,
+    /// for translating them
+    Messages {
+        /// Write the template to this file instead
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Create a chapter with front matter stubs and a header, and add it to
+    /// SUMMARY.md
+    InitChapter {
+        /// Path of the chapter, relative to the book's `src` directory
+        path: PathBuf,
+        /// Title of the chapter, by default its file name
+        #[arg(long)]
+        title: Option<String>,
+        /// Directory of the book
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+    },
 }
 
 fn main() {
@@ -40,11 +83,95 @@ fn run(cli: Cli) -> Result<()> {
         Some(Commands::Supports { renderer }) => {
             handle_supports(renderer);
         }
+        Some(Commands::Check { dir }) => handle_check(dir),
+        Some(Commands::Install { dir, no_css }) => handle_install(dir, !no_css),
+        Some(Commands::Stats { dir, json }) => handle_stats(dir, json),
+        Some(Commands::Messages { output }) => {
+            let pot = AIPRPreprocessor::new().messages_template();
+            match output {
+                Some(path) => std::fs::write(path, pot)?,
+                None => print!("{pot}"),
+            }
+            Ok(())
+        }
+        Some(Commands::InitChapter { path, title, dir }) => {
+            let file = init_chapter(&dir, &path, title.as_deref())?;
+            println!("Created {}", file.display());
+            Ok(())
+        }
+    }
+}
+
+fn handle_stats(dir: PathBuf, json: bool) -> Result<()> {
+    let stats = AIPRPreprocessor::new().stats(&dir)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    let path_width = stats
+        .iter()
+        .map(|chapter| chapter.path.len())
+        .chain(["Chapter".len()])
+        .max()
+        .unwrap_or_default();
+    println!(
+        "{:path_width$}  {:>6}  {:>4}  {:>5}  {:>6}  {:>5}  Notebook",
+        "Chapter", "Words", "Min", "Code", "Images", "Links"
+    );
+    for chapter in &stats {
+        println!(
+            "{:path_width$}  {:>6}  {:>4}  {:>5}  {:>6}  {:>5}  {}",
+            chapter.path,
+            chapter.word_count,
+            chapter.reading_time,
+            chapter.code_lines,
+            chapter.images,
+            chapter.external_links,
+            chapter.notebook.as_deref().unwrap_or("-")
+        );
+    }
+    let with_notebook = stats
+        .iter()
+        .filter(|chapter| chapter.notebook.is_some())
+        .count();
+    println!(
+        "\n{} chapters, {with_notebook} with a notebook, {} words in total.",
+        stats.len(),
+        stats
+            .iter()
+            .map(|chapter| chapter.word_count)
+            .sum::<usize>()
+    );
+    Ok(())
+}
+
+fn handle_install(dir: PathBuf, css: bool) -> Result<()> {
+    let changes = install(&dir, css)?;
+    for change in &changes {
+        println!("{change}");
+    }
+    if changes.is_empty() {
+        println!("Already installed.");
+    }
+    Ok(())
+}
+
+fn handle_check(dir: PathBuf) -> Result<()> {
+    let problems = AIPRPreprocessor::new().check(&dir)?;
+    for problem in &problems {
+        println!("{problem}");
+    }
+    if problems.is_empty() {
+        println!("No problems found.");
+        return Ok(());
     }
+    println!("{} problem(s) found.", problems.len());
+    process::exit(1);
 }
 
 fn handle_supports(renderer: String) -> ! {
-    let supported = AIPRPreprocessor.supports_renderer(&renderer);
+    let supported = AIPRPreprocessor::new().supports_renderer(&renderer);
 
     // Signal whether the renderer is supported by exiting with 1 or 0.
     if supported {
@@ -66,7 +193,7 @@ fn handle_preprocessing() -> Result<(), Error> {
         );
     }
 
-    let processed_book = AIPRPreprocessor.run(&ctx, book)?;
+    let processed_book = AIPRPreprocessor::new().run(&ctx, book)?;
     serde_json::to_writer(io::stdout(), &processed_book)?;
 
     Ok(())
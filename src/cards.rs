@@ -0,0 +1,108 @@
+//! Card definitions for `{{#aipr_dataset}}` and `{{#aipr_model_card}}`, read
+//! from TOML files under `cards_dir`, e.g. `data/cards/squad.toml`:
+//!
+//! ```toml
+//! name = "SQuAD"
+//! size = "100k questions"
+//! license = "CC BY-SA 4.0"
+//! link = "https://rajpurkar.github.io/SQuAD-explorer/"
+//! description = "Reading comprehension questions on Wikipedia articles."
+//! ```
+//!
+//! Files ending in `.yaml` or `.yml` are read as YAML instead. Only flat
+//! mappings of `key: value` lines are supported, which is all a card needs.
+
+use anyhow::Context;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Fields of the card at `path`. Values that aren't strings, e.g. numbers,
+/// are kept as written.
+pub(crate) fn read_card(path: &Path) -> anyhow::Result<HashMap<String, String>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read card {}", path.display()))?;
+    let is_yaml = matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("yaml" | "yml")
+    );
+    match is_yaml {
+        true => parse_yaml_card(&contents),
+        false => parse_card(&contents),
+    }
+    .with_context(|| format!("Failed to parse card {}", path.display()))
+}
+
+fn parse_card(contents: &str) -> anyhow::Result<HashMap<String, String>> {
+    let table: toml::value::Table = toml::from_str(contents)?;
+    Ok(table
+        .into_iter()
+        .map(|(key, value)| match value {
+            toml::Value::String(value) => (key, value),
+            value => (key, value.to_string()),
+        })
+        .collect())
+}
+
+/// Parses a flat YAML mapping, with optionally quoted values and comments.
+fn parse_yaml_card(contents: &str) -> anyhow::Result<HashMap<String, String>> {
+    let mut card = HashMap::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim_end();
+        if line.trim().is_empty() || line.trim_start().starts_with('#') || line == "---" {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            anyhow::bail!("Expected `key: value` on line {}", index + 1);
+        };
+        anyhow::ensure!(
+            !key.starts_with(char::is_whitespace),
+            "Nested mappings aren't supported, on line {}",
+            index + 1
+        );
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value
+                .strip_prefix(quote)
+                .and_then(|value| value.strip_suffix(quote))
+                .with_context(|| format!("Unclosed quote on line {}", index + 1))?,
+            _ => value.split(" #").next().unwrap_or_default().trim(),
+        };
+        card.insert(key.trim().to_string(), value.to_string());
+    }
+    Ok(card)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_parse_card() -> Result<()> {
+        let card = parse_card("name = \"SQuAD\"\nsize = 107785\n")?;
+        assert_eq!(card.get("name").map(String::as_str), Some("SQuAD"));
+        assert_eq!(card.get("size").map(String::as_str), Some("107785"));
+        assert!(parse_card("name = ").is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_parse_yaml_card() -> Result<()> {
+        let card = parse_yaml_card(
+            "---\n# Llama 3\nname: \"Llama 3: 8B\"\nparameters: 8B # dense\n\
+             paper: https://arxiv.org/abs/2407.21783\n",
+        )?;
+        assert_eq!(card.get("name").map(String::as_str), Some("Llama 3: 8B"));
+        assert_eq!(card.get("parameters").map(String::as_str), Some("8B"));
+        assert_eq!(
+            card.get("paper").map(String::as_str),
+            Some("https://arxiv.org/abs/2407.21783")
+        );
+        assert!(parse_yaml_card("name: Llama\n  nested: true\n").is_err());
+        assert!(parse_yaml_card("name: \"Llama\n").is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+//! Styles of `{{#aipr_cite}}` markers and of the references list of citing
+//! chapters, selected with `citation_style`. Each style is a
+//! [`CitationFormatter`]: IEEE numbers citations in the order they're first
+//! cited, APA labels them by author and year and lists references by author.
+
+use crate::bibliography::{initials, BibEntry};
+use serde::Deserialize;
+
+/// Style of in-text citations and references.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CitationStyle {
+    /// Numbered markers, e.g. `[1]`, and references in citation order.
+    #[default]
+    Ieee,
+    /// Author-year markers, e.g. `(Hu et al., 2021)`, and references by
+    /// author.
+    Apa,
+}
+
+impl CitationStyle {
+    pub(crate) fn formatter(self) -> &'static dyn CitationFormatter {
+        match self {
+            Self::Ieee => &Ieee,
+            Self::Apa => &Apa,
+        }
+    }
+}
+
+/// How a citation style writes markers and references.
+pub(crate) trait CitationFormatter {
+    /// Whether markers are numbers in brackets, rather than author-year
+    /// labels in parentheses.
+    fn numeric(&self) -> bool;
+
+    /// Label of the marker of `key`, the `number`th entry cited in the
+    /// chapter, with its bibliography entry, if any.
+    fn label(&self, key: &str, number: usize, entry: Option<&BibEntry>) -> String;
+
+    /// Authors of `entry` as its reference lists them.
+    fn authors(&self, entry: &BibEntry) -> Option<String>;
+
+    /// Key the references are sorted by, or `None` to list them in citation
+    /// order.
+    fn sort_key(&self, _key: &str, _entry: Option<&BibEntry>) -> Option<String> {
+        None
+    }
+}
+
+/// IEEE style: `[1]`, and `A. Vaswani and N. Shazeer, "Title," Venue, 2017.`
+struct Ieee;
+
+impl CitationFormatter for Ieee {
+    fn numeric(&self) -> bool {
+        true
+    }
+
+    fn label(&self, _key: &str, number: usize, _entry: Option<&BibEntry>) -> String {
+        number.to_string()
+    }
+
+    fn authors(&self, entry: &BibEntry) -> Option<String> {
+        entry.field("author")?;
+        let names = entry
+            .names()
+            .into_iter()
+            .map(|(first, last)| {
+                if first.is_empty() {
+                    last
+                } else {
+                    format!("{} {last}", initials(&first))
+                }
+            })
+            .collect::<Vec<_>>();
+        Some(match names.as_slice() {
+            [] => String::new(),
+            [name] => name.clone(),
+            [first, second] => format!("{first} and {second}"),
+            [first, ..] if names.len() > 6 => format!("{first} et al."),
+            [rest @ .., last] => format!("{}, and {last}", rest.join(", ")),
+        })
+    }
+}
+
+/// APA style: `(Vaswani & Shazeer, 2017)`, and
+/// `Vaswani, A., Shazeer, N. (2017). Title. Venue.`
+struct Apa;
+
+impl CitationFormatter for Apa {
+    fn numeric(&self) -> bool {
+        false
+    }
+
+    fn label(&self, key: &str, _number: usize, entry: Option<&BibEntry>) -> String {
+        let Some(entry) = entry else {
+            return key.to_string();
+        };
+        let last_names = entry
+            .names()
+            .into_iter()
+            .map(|(_, last)| last)
+            .collect::<Vec<_>>();
+        let authors = match last_names.as_slice() {
+            [] => entry.field("title").unwrap_or(key).to_string(),
+            [name] => name.clone(),
+            [first, second] => format!("{first} & {second}"),
+            [first, ..] => format!("{first} et al."),
+        };
+        format!("{authors}, {}", entry.field("year").unwrap_or("n.d."))
+    }
+
+    fn authors(&self, entry: &BibEntry) -> Option<String> {
+        entry.authors()
+    }
+
+    fn sort_key(&self, key: &str, entry: Option<&BibEntry>) -> Option<String> {
+        let author =
+            entry.and_then(|entry| entry.authors().or(entry.field("title").map(str::to_string)));
+        let year = entry
+            .and_then(|entry| entry.field("year"))
+            .unwrap_or_default();
+        Some(format!("{} {year}", author.as_deref().unwrap_or(key)).to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibliography::Bibliography;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    #[case("Hu, Edward", "E. Hu", "Hu, 2021")]
+    #[case(
+        "Ashish Vaswani and Noam Shazeer",
+        "A. Vaswani and N. Shazeer",
+        "Vaswani & Shazeer, 2021"
+    )]
+    #[case(
+        "Hu, Edward J. and Shen, Yelong and Wallis, Phillip",
+        "E. J. Hu, Y. Shen, and P. Wallis",
+        "Hu et al., 2021"
+    )]
+    #[case(
+        "A B and C D and E F and G H and I J and K L and M N",
+        "A. B et al.",
+        "B et al., 2021"
+    )]
+    fn test_formatters(
+        #[case] author: &str,
+        #[case] ieee_authors: &str,
+        #[case] apa_label: &str,
+    ) -> Result<()> {
+        let bibliography = Bibliography::from_bibtex(&format!(
+            "@article{{hu2021, title = {{LoRA}}, author = {{{author}}}, year = 2021}}"
+        ))?;
+        let entry = bibliography.get("hu2021");
+
+        let ieee = CitationStyle::Ieee.formatter();
+        assert_eq!(ieee.label("hu2021", 3, entry), "3");
+        assert_eq!(ieee.authors(entry.unwrap()).as_deref(), Some(ieee_authors));
+        assert_eq!(ieee.sort_key("hu2021", entry), None);
+
+        let apa = CitationStyle::Apa.formatter();
+        assert_eq!(apa.label("hu2021", 3, entry), apa_label);
+        assert_eq!(apa.label("missing", 1, None), "missing");
+
+        Ok(())
+    }
+}
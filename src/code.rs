@@ -0,0 +1,111 @@
+//! Source files of the companion code repository embedded by
+//! `{{#aipr_code}}`. Files are read from a local checkout when
+//! `code_repo_path` is configured, and otherwise fetched from GitHub.
+
+use crate::config::AIPRConfig;
+use crate::fetch::fetch_cached;
+use anyhow::Context;
+use std::path::Path;
+
+/// Contents of the file at `path` in the code repository, with `root` the
+/// book root that configured paths are relative to.
+pub(crate) fn read_code_file(
+    config: &AIPRConfig,
+    root: &Path,
+    path: &str,
+) -> anyhow::Result<String> {
+    let path = path.trim().trim_start_matches("./");
+    if let Some(code_repo_path) = &config.code_repo_path {
+        let file = root.join(code_repo_path).join(path);
+        return std::fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read code file {}", file.display()));
+    }
+
+    let cache_key = Path::new("code")
+        .join(config.code_repo.trim_matches('/'))
+        .join(&config.code_branch)
+        .join(path);
+    fetch_cached(config, root, &config.code_raw_url(path), &cache_key)
+}
+
+/// Lines `start` to `end` of `contents`, 1-based and inclusive. Missing
+/// bounds default to the first and last line.
+pub(crate) fn select_lines(
+    contents: &str,
+    start: Option<usize>,
+    end: Option<usize>,
+) -> anyhow::Result<String> {
+    let lines = contents.lines().collect::<Vec<_>>();
+    let start = start.unwrap_or(1);
+    let end = end.unwrap_or(lines.len());
+    anyhow::ensure!(
+        1 <= start && start <= end && end <= lines.len(),
+        "Lines {start}:{end} are out of range, the file has {} lines",
+        lines.len()
+    );
+    Ok(lines[start - 1..end].join("\n"))
+}
+
+/// Fenced code block language of the file at `path`, from its extension.
+pub(crate) fn language(path: &str) -> &str {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    match extension {
+        "py" => "python",
+        "rs" => "rust",
+        "sh" => "bash",
+        "js" => "javascript",
+        "ts" => "typescript",
+        "yml" => "yaml",
+        "md" => "markdown",
+        ext => ext,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+    use std::path::PathBuf;
+
+    #[rstest]
+    #[case(Some(2), Some(3), "b\nc")]
+    #[case(Some(3), None, "c\nd")]
+    #[case(None, Some(1), "a")]
+    #[case(None, None, "a\nb\nc\nd")]
+    fn test_select_lines(
+        #[case] start: Option<usize>,
+        #[case] end: Option<usize>,
+        #[case] expected: &str,
+    ) -> Result<()> {
+        assert_eq!(select_lines("a\nb\nc\nd\n", start, end)?, expected);
+        assert!(select_lines("a\nb\nc\nd\n", Some(3), Some(5)).is_err());
+        assert!(select_lines("a\nb\nc\nd\n", Some(3), Some(2)).is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_read_code_file_local() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aipr-code-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("code/nlp"))?;
+        std::fs::write(root.join("code/nlp/train.py"), "print('hi')\n")?;
+        let config = AIPRConfig {
+            code_repo_path: Some(PathBuf::from("code")),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            read_code_file(&config, &root, "./nlp/train.py")?,
+            "print('hi')\n"
+        );
+        assert!(read_code_file(&config, &root, "nlp/missing.py").is_err());
+        assert_eq!(language("nlp/train.py"), "python");
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,1227 @@
+//! Options of the preprocessor, read from a book's `book.toml`.
+
+use crate::citations::CitationStyle;
+use crate::forge::Forge;
+use crate::helpers::footer::FooterLink;
+use crate::link_check;
+use crate::AIPRPreprocessor;
+use mdbook::Config;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Options read from the `[preprocessor.ai-pocket-reference]` table of a
+/// book's `book.toml`. Missing keys fall back to the AI Pocket Reference
+/// defaults.
+///
+/// ```toml
+/// [preprocessor.ai-pocket-reference]
+/// colab_repo = "VectorInstitute/ai-pocket-reference-code"
+/// colab_branch = "main"
+/// colab_root = "notebooks"
+/// colab_auto = false
+/// colab_auto_pattern = "{path}.ipynb"
+/// issue_repo = "VectorInstitute/ai-pocket-reference"
+/// issue_template = "edit-request.yml"
+/// forge = "github"
+/// edit_link = true
+/// edit_repo = "VectorInstitute/ai-pocket-reference"
+/// edit_branch = "main"
+/// edit_root = "src"
+/// site_url = "https://vectorinstitute.github.io/ai-pocket-reference/"
+/// current_version = "v0.3"
+/// latest_url = "https://vectorinstitute.github.io/ai-pocket-reference/"
+/// stale_after_days = 365
+/// expand_renderers = ["html"]
+/// new_tab_exclude = ["vectorinstitute.ai", "ai-pocket-reference.*"]
+/// rewrite_mailto = true
+/// link_rel = "noopener noreferrer"
+/// external_link_icon = false
+/// count_code = false
+/// reading_time_rounding = "round"
+/// reading_time_min = 0
+/// reading_time_display = "{} min"
+/// reading_time_format = "point"
+/// reading_time_slow_wpm = 150
+/// reading_time_fast_wpm = 250
+/// reading_time_code_line_words = 0
+/// reading_time_equation_words = 0
+/// cjk_chars_per_minute = 300
+/// sidebar_reading_time = false
+/// contributors_max = 10
+/// contributors_exclude = ["dependabot[bot]"]
+/// strict = false
+/// glossary = "glossary.toml"
+/// bibliography = "references.bib"
+/// citation_style = "apa"
+/// code_repo = "VectorInstitute/ai-pocket-reference-code"
+/// code_branch = "main"
+/// code_repo_path = "../ai-pocket-reference-code"
+/// cards_dir = "data/cards"
+/// validate_notebooks = false
+/// validate_notebooks_max_age = 7
+/// check_links = false
+/// check_links_timeout = 10
+/// check_links_max_age = 7
+/// check_links_exclude = ["localhost"]
+/// license = "CC-BY-4.0"
+/// footer_template = "theme/footer.hbs"
+/// partials_dir = "theme/partials"
+/// badge_style = "flat"
+/// language = "fr"
+/// po_dir = "po"
+/// structured_data = true
+/// meta_tags = true
+/// feed = false
+/// feed_max = 20
+/// metadata = false
+/// publisher = "Vector Institute"
+/// cache_dir = ".aipr-cache"
+/// offline = false
+/// fetch_timeout = 10
+/// crossref_api = "https://api.crossref.org"
+///
+/// [preprocessor.ai-pocket-reference.abbreviations]
+/// RLHF = "Reinforcement Learning from Human Feedback"
+///
+/// [preprocessor.ai-pocket-reference.footer]
+/// logo = "https://example.com/logo.png"
+/// logo_dark = "https://example.com/logo-dark.png"
+/// tagline = "A pocket reference for AI"
+/// links = [{ label = "Blog", url = "https://example.com/blog" }]
+///
+/// [preprocessor.ai-pocket-reference.footer.named_links]
+/// discord = { label = "Discord", url = "https://discord.gg/example" }
+///
+/// [preprocessor.ai-pocket-reference.comments]
+/// provider = "giscus"
+/// repo = "VectorInstitute/ai-pocket-reference"
+/// repo_id = "R_kgDOxxxxxx"
+/// category = "Comments"
+/// category_id = "DIC_kwDOxxxxxx"
+///
+/// [preprocessor.ai-pocket-reference.feedback]
+/// enabled = true
+/// endpoint = "https://example.com/api/feedback"
+///
+/// [preprocessor.ai-pocket-reference.edit_badge]
+/// color = "black"
+/// logo = "github"
+///
+/// [preprocessor.ai-pocket-reference.colab_badge]
+/// color = "F9AB00"
+/// logo = "googlecolab"
+///
+/// [preprocessor.ai-pocket-reference.translations.fr]
+/// "Reading time" = "Durée de lecture"
+///
+/// [preprocessor.ai-pocket-reference.difficulty_colors]
+/// beginner = "brightgreen"
+///
+/// [preprocessor.ai-pocket-reference.redirects]
+/// "nlp/peft.md" = "nlp/lora.md"
+///
+/// [preprocessor.ai-pocket-reference.links]
+/// llama3 = "https://arxiv.org/abs/2407.21783"
+///
+/// [preprocessor.ai-pocket-reference.series]
+/// fine-tuning = ["nlp/lora.md", "nlp/qlora.md", "nlp/dora.md"]
+///
+/// [[preprocessor.ai-pocket-reference.link_rules]]
+/// domains = ["vectorinstitute.ai", "*.vectorinstitute.ai"]
+/// rel = "noopener"
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AIPRConfig {
+    /// `<owner>/<repo>` hosting the companion notebooks.
+    pub colab_repo: String,
+    /// Branch of `colab_repo` that notebook links point at.
+    pub colab_branch: String,
+    /// Directory within `colab_repo` that the header's notebook paths, e.g.
+    /// `colab=`, are relative to.
+    pub colab_root: String,
+    /// Whether headers without a notebook get the one `colab=auto` stands
+    /// for, unless they set `colab=none`.
+    pub colab_auto: bool,
+    /// Notebook of `colab=auto`, relative to `colab_root`, where `{path}`,
+    /// `{dir}` and `{stem}` are replaced by the chapter's source path without
+    /// extension, its directory and its file name without extension.
+    pub colab_auto_pattern: String,
+    /// `<owner>/<repo>` that "Suggest an Edit" issues are filed against.
+    pub issue_repo: String,
+    /// Issue template used by the "Suggest an Edit" badge. Empty for none.
+    pub issue_template: String,
+    /// Host of `colab_repo`, `issue_repo` and `code_repo`, that edit,
+    /// notebook and contributor links point to.
+    pub forge: Forge,
+    /// Whether headers link to the chapter's source file with an "Edit this
+    /// page" badge, unless they set `edit_link=false`.
+    pub edit_link: bool,
+    /// `<owner>/<repo>` of the book's sources, `issue_repo` if unset.
+    pub edit_repo: Option<String>,
+    /// Branch of the book's repository that "Edit this page" links edit.
+    pub edit_branch: String,
+    /// Directory of the book's `src` within its repository.
+    pub edit_root: String,
+    /// Public URL the book is served from, used for absolute chapter links.
+    pub site_url: Option<String>,
+    /// Version of this build of the book, e.g. `v0.3`, for
+    /// `aipr_version_banner`.
+    pub current_version: Option<String>,
+    /// URL of the latest version of the book, for `aipr_version_banner`.
+    pub latest_url: Option<String>,
+    /// Days since the last commit touching a chapter after which its header
+    /// is followed by a "This chapter may be outdated" notice. Unset for
+    /// none.
+    pub stale_after_days: Option<i64>,
+    /// Renderers that helpers are expanded for. Other renderers get the
+    /// helpers stripped from the chapter content. Not `renderers`, which
+    /// mdBook reads to skip the preprocessor for other renderers altogether.
+    pub expand_renderers: Vec<String>,
+    /// Domains, with `*` wildcards, whose Markdown links are left to open in
+    /// the same tab instead of being rewritten to open in a new one.
+    pub new_tab_exclude: Vec<String>,
+    /// Whether `mailto:` Markdown links are rewritten to HTML links, opening
+    /// in the same tab, along with external links.
+    pub rewrite_mailto: bool,
+    /// `rel` of rewritten external links, unless a `link_rules` rule applies.
+    pub link_rel: String,
+    /// Whether rewritten external links end with an arrow icon.
+    pub external_link_icon: bool,
+    /// Whether code blocks count towards the reading time.
+    pub count_code: bool,
+    /// How fractional reading times are rounded to whole minutes.
+    pub reading_time_rounding: Rounding,
+    /// Minimum reading time shown, in minutes.
+    pub reading_time_min: usize,
+    /// Display format of the reading time; `{}` is replaced by the minutes.
+    pub reading_time_display: String,
+    /// Whether reading times are one estimate or a range, from reading at
+    /// `reading_time_fast_wpm` to reading at `reading_time_slow_wpm`.
+    pub reading_time_format: ReadingTimeFormat,
+    /// Words per minute of slow readers, for reading-time ranges.
+    pub reading_time_slow_wpm: usize,
+    /// Words per minute of fast readers, for reading-time ranges.
+    pub reading_time_fast_wpm: usize,
+    /// Words each non-blank line of code counts as in reading times, on top
+    /// of `count_code`, e.g. 4 as code is read slower than prose.
+    pub reading_time_code_line_words: f32,
+    /// Words each display-math block counts as in reading times.
+    pub reading_time_equation_words: f32,
+    /// Characters per minute chapters mostly in Chinese, Japanese or Korean
+    /// are read at, rather than words per minute.
+    pub cjk_chars_per_minute: usize,
+    /// Whether chapter names, as the sidebar shows them, end with their
+    /// reading time, e.g. `LoRA · 5 min`.
+    pub sidebar_reading_time: bool,
+    /// Maximum number of avatars shown by `aipr_contributors`.
+    pub contributors_max: usize,
+    /// Names, emails or GitHub usernames left out of `aipr_contributors`.
+    pub contributors_exclude: Vec<String>,
+    /// Whether invalid helpers, references to chapters missing from the book
+    /// and missing notebooks fail the build, rather than being skipped with a
+    /// warning.
+    pub strict: bool,
+    /// Glossary of terms used by `aipr_term`, relative to the book root.
+    pub glossary: PathBuf,
+    /// BibTeX file of the entries cited by `aipr_cite`, relative to the book root.
+    pub bibliography: PathBuf,
+    /// Style of `aipr_cite` markers and of the references list, `ieee` or
+    /// `apa`.
+    pub citation_style: CitationStyle,
+    /// `<owner>/<repo>` of the companion code embedded by `aipr_code`.
+    pub code_repo: String,
+    /// Branch of `code_repo` that code is fetched from.
+    pub code_branch: String,
+    /// Local checkout of `code_repo`, relative to the book root. Code is read
+    /// from it instead of being fetched from GitHub.
+    pub code_repo_path: Option<PathBuf>,
+    /// Whether the header's notebook paths are checked to exist in
+    /// `colab_repo`. Off by default, as without `code_repo_path` every build
+    /// requests the forge's API.
+    pub validate_notebooks: bool,
+    /// Days a notebook found through the forge's API isn't rechecked for.
+    pub validate_notebooks_max_age: i64,
+    /// Whether external links in chapters are requested to report dead ones.
+    pub check_links: bool,
+    /// Seconds to wait for each checked link.
+    pub check_links_timeout: u64,
+    /// Days a link found alive isn't rechecked for.
+    pub check_links_max_age: i64,
+    /// Domains of links that aren't checked, with `*` wildcards.
+    pub check_links_exclude: Vec<String>,
+    /// Directory of the card definitions read by `aipr_dataset` and
+    /// `aipr_model_card`, relative to the book root.
+    pub cards_dir: PathBuf,
+    /// Directory, relative to the book root, caching resources fetched at
+    /// build time, such as code from GitHub.
+    pub cache_dir: PathBuf,
+    /// Whether to only use cached resources rather than fetching them.
+    pub offline: bool,
+    /// Seconds to wait for each resource fetched at build time, after which
+    /// it's handled as if offline.
+    pub fetch_timeout: u64,
+    /// Base URL of the Crossref API that `aipr_doi` fetches metadata from,
+    /// e.g. a mirror.
+    pub crossref_api: String,
+    /// SPDX id of the book's content license, shown in every chapter's footer.
+    pub license: Option<String>,
+    /// The default footer of chapters without an `aipr_footer` helper:
+    /// `false` leaves it out, while a table sets the variables of footers.
+    pub footer: FooterConfig,
+    /// Handlebars template, relative to the book root, footers are rendered
+    /// with instead of the built-in one.
+    pub footer_template: Option<PathBuf>,
+    /// Directory, relative to the book root, of Handlebars partials
+    /// `<name>.hbs` that templates include with `{{> name}}`. The header
+    /// includes `header_badges` after its badges and `header_extra` at its
+    /// end, and the footer `footer_extra`.
+    pub partials_dir: Option<PathBuf>,
+    /// Discussions or issues `aipr_comments` embeds.
+    pub comments: CommentsConfig,
+    /// "Was this page helpful?" widget at the end of footers.
+    pub feedback: FeedbackConfig,
+    /// shields.io style of the header's "Suggest an Edit" and Colab badges.
+    pub badge_style: BadgeStyle,
+    /// Look of the header's "Suggest an Edit" badge.
+    pub edit_badge: BadgeConfig,
+    /// Look of the header's Colab badge. Without it, and with the default
+    /// `badge_style`, Colab's own badge is shown.
+    pub colab_badge: Option<BadgeConfig>,
+    /// Language of the text helpers add, e.g. `fr`. Defaults to the book's
+    /// `book.language`.
+    pub language: Option<String>,
+    /// Translations of the text helpers add by language, keyed by their
+    /// English text, overriding the catalogs' and built-in ones.
+    pub translations: BTreeMap<String, BTreeMap<String, String>>,
+    /// Directory, relative to the book root, of the gettext catalogs, e.g.
+    /// `fr.po`, translating the text helpers add.
+    pub po_dir: PathBuf,
+    /// Whether chapters get a schema.org `TechArticle` JSON-LD block for
+    /// search engines.
+    pub structured_data: bool,
+    /// Whether chapters get `keywords` and `description` meta tags, from
+    /// their tags and their header's or front matter's description.
+    pub meta_tags: bool,
+    /// Publisher named in the structured data. Empty for none.
+    pub publisher: String,
+    /// Whether to write an Atom feed of recently updated chapters to
+    /// `feed.xml` in the `src` directory. Requires `site_url`.
+    pub feed: bool,
+    /// Number of chapters in the feed.
+    pub feed_max: usize,
+    /// Whether to write the metadata of every chapter to
+    /// `aipr-metadata.json` in the `src` directory, for external tools.
+    pub metadata: bool,
+    /// Abbreviations whose first occurrence in a chapter is wrapped in an
+    /// `<abbr>` tag with their expansion.
+    pub abbreviations: BTreeMap<String, String>,
+    /// Colors of the header's difficulty badges by level, overriding the
+    /// built-in ones.
+    pub difficulty_colors: BTreeMap<String, String>,
+    /// Named series of chapter paths, relative to the `src` directory, in
+    /// reading order.
+    pub series: BTreeMap<String, Vec<String>>,
+    /// New paths of renamed chapters by their old path, relative to the
+    /// `src` directory, whose old pages redirect to the new ones.
+    pub redirects: BTreeMap<String, String>,
+    /// URLs of `aipr_link` by name, so that frequently cited ones are
+    /// maintained in one place.
+    pub links: BTreeMap<String, String>,
+    /// `rel` of rewritten links to some domains. The first matching rule
+    /// applies.
+    pub link_rules: Vec<LinkRule>,
+}
+
+/// `rel` of rewritten links to some domains, overriding `link_rel`.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default)]
+pub struct LinkRule {
+    /// Domains the rule applies to, with `*` wildcards.
+    pub domains: Vec<String>,
+    pub rel: String,
+}
+
+/// The default footer, read from either `footer = false` or a
+/// `[preprocessor.ai-pocket-reference.footer]` table of its variables.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(from = "FooterValue")]
+pub struct FooterConfig {
+    /// Whether chapters without an `aipr_footer` helper get the default
+    /// footer appended.
+    pub enabled: bool,
+    pub variables: FooterVariables,
+}
+
+impl Default for FooterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            variables: FooterVariables::default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FooterValue {
+    Enabled(bool),
+    Variables(FooterVariables),
+}
+
+impl From<FooterValue> for FooterConfig {
+    fn from(value: FooterValue) -> Self {
+        match value {
+            FooterValue::Enabled(enabled) => Self {
+                enabled,
+                ..Default::default()
+            },
+            FooterValue::Variables(variables) => Self {
+                enabled: true,
+                variables,
+            },
+        }
+    }
+}
+
+/// Variables of footers, available to `footer_template`.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default)]
+pub struct FooterVariables {
+    /// URL of the logo, replacing the Vector Institute one.
+    pub logo: Option<String>,
+    /// URL of the logo shown in mdBook's dark themes, if different.
+    pub logo_dark: Option<String>,
+    /// URL the logo links to.
+    pub logo_link: Option<String>,
+    /// Line of text shown under the logo.
+    pub tagline: Option<String>,
+    /// Links shown in every footer, after those of the `links` param.
+    pub links: Vec<FooterLink>,
+    /// Links the `links` param of `aipr_footer` can name, besides, or
+    /// replacing, the built-in `github`, `issues` and `website`.
+    pub named_links: BTreeMap<String, FooterLink>,
+}
+
+/// Service the comments of `aipr_comments` are embedded with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentsProvider {
+    /// giscus, commenting in GitHub Discussions.
+    #[default]
+    Giscus,
+    /// utterances, commenting in GitHub issues.
+    Utterances,
+}
+
+/// Comments of chapters, from the
+/// `[preprocessor.ai-pocket-reference.comments]` table.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CommentsConfig {
+    pub provider: CommentsProvider,
+    /// `<owner>/<repo>` on GitHub whose discussions or issues hold the
+    /// comments.
+    pub repo: Option<String>,
+    /// giscus id of `repo`, as given by <https://giscus.app>.
+    pub repo_id: Option<String>,
+    /// giscus discussion category of the comments.
+    pub category: String,
+    /// giscus id of `category`.
+    pub category_id: Option<String>,
+    /// How chapters map to discussions or issues: `pathname`, `url`,
+    /// `title` or `og:title`.
+    pub mapping: String,
+    /// Label of the utterances issues.
+    pub label: Option<String>,
+    /// Theme of the embed, defaulting to following the reader's system.
+    pub theme: Option<String>,
+}
+
+impl Default for CommentsConfig {
+    fn default() -> Self {
+        Self {
+            provider: CommentsProvider::default(),
+            repo: None,
+            repo_id: None,
+            category: "General".to_string(),
+            category_id: None,
+            mapping: "pathname".to_string(),
+            label: None,
+            theme: None,
+        }
+    }
+}
+
+impl CommentsConfig {
+    /// Theme of the embed, or the provider's theme following the reader's
+    /// system.
+    pub fn theme(&self) -> &str {
+        self.theme.as_deref().unwrap_or(match self.provider {
+            CommentsProvider::Giscus => "preferred_color_scheme",
+            CommentsProvider::Utterances => "preferred-color-scheme",
+        })
+    }
+}
+
+/// "Was this page helpful?" widget of footers, from the
+/// `[preprocessor.ai-pocket-reference.feedback]` table.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default)]
+pub struct FeedbackConfig {
+    /// Whether footers end with the widget.
+    pub enabled: bool,
+    /// URL votes are posted to as JSON, with the chapter's `path`, whether
+    /// it was `helpful` and the page's `url`. Without one, votes open an
+    /// issue in `issue_repo` prefilled with them.
+    pub endpoint: Option<String>,
+}
+
+/// shields.io style of badges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BadgeStyle {
+    #[default]
+    Flat,
+    FlatSquare,
+    Plastic,
+    ForTheBadge,
+    Social,
+}
+
+impl BadgeStyle {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BadgeStyle::Flat => "flat",
+            BadgeStyle::FlatSquare => "flat-square",
+            BadgeStyle::Plastic => "plastic",
+            BadgeStyle::ForTheBadge => "for-the-badge",
+            BadgeStyle::Social => "social",
+        }
+    }
+}
+
+/// Look of a header badge rendered with shields.io. Missing keys fall back to
+/// the badge's defaults.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default)]
+pub struct BadgeConfig {
+    /// Color of the badge, e.g. `black` or `F9AB00`.
+    pub color: Option<String>,
+    /// Color of the badge's label, left of the text.
+    pub label_color: Option<String>,
+    /// simple-icons name of the badge's logo. Empty for none.
+    pub logo: Option<String>,
+    pub logo_color: Option<String>,
+}
+
+/// How fractional reading times are rounded to whole minutes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rounding {
+    #[default]
+    Round,
+    Ceil,
+    Floor,
+}
+
+impl Rounding {
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            Rounding::Round => value.round(),
+            Rounding::Ceil => value.ceil(),
+            Rounding::Floor => value.floor(),
+        }
+    }
+}
+
+impl FromStr for Rounding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round" => Ok(Rounding::Round),
+            "ceil" => Ok(Rounding::Ceil),
+            "floor" => Ok(Rounding::Floor),
+            _ => anyhow::bail!("unknown rounding mode `{s}`, expected round, ceil or floor"),
+        }
+    }
+}
+
+/// Whether reading times are one estimate, e.g. `5 min`, or a range, e.g.
+/// `4–6 min`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadingTimeFormat {
+    #[default]
+    Point,
+    Range,
+}
+
+impl FromStr for ReadingTimeFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "point" => Ok(ReadingTimeFormat::Point),
+            "range" => Ok(ReadingTimeFormat::Range),
+            _ => anyhow::bail!("unknown reading time format `{s}`, expected point or range"),
+        }
+    }
+}
+
+impl Default for AIPRConfig {
+    fn default() -> Self {
+        Self {
+            colab_repo: "VectorInstitute/ai-pocket-reference-code".to_string(),
+            colab_branch: "main".to_string(),
+            colab_root: "notebooks".to_string(),
+            colab_auto: false,
+            colab_auto_pattern: "{path}.ipynb".to_string(),
+            issue_repo: "VectorInstitute/ai-pocket-reference".to_string(),
+            issue_template: "edit-request.yml".to_string(),
+            forge: Forge::default(),
+            edit_link: false,
+            edit_repo: None,
+            edit_branch: "main".to_string(),
+            edit_root: "src".to_string(),
+            site_url: None,
+            current_version: None,
+            latest_url: None,
+            stale_after_days: None,
+            expand_renderers: vec!["html".to_string()],
+            new_tab_exclude: vec![],
+            rewrite_mailto: true,
+            link_rel: "noopener noreferrer".to_string(),
+            external_link_icon: false,
+            count_code: false,
+            reading_time_rounding: Rounding::Round,
+            reading_time_min: 0,
+            reading_time_display: "{} min".to_string(),
+            reading_time_format: ReadingTimeFormat::Point,
+            reading_time_slow_wpm: 150,
+            reading_time_fast_wpm: 250,
+            reading_time_code_line_words: 0.0,
+            reading_time_equation_words: 0.0,
+            cjk_chars_per_minute: 300,
+            sidebar_reading_time: false,
+            contributors_max: 10,
+            contributors_exclude: vec![],
+            strict: false,
+            glossary: PathBuf::from("glossary.toml"),
+            bibliography: PathBuf::from("references.bib"),
+            citation_style: CitationStyle::default(),
+            code_repo: "VectorInstitute/ai-pocket-reference-code".to_string(),
+            code_branch: "main".to_string(),
+            code_repo_path: None,
+            validate_notebooks: false,
+            validate_notebooks_max_age: 7,
+            check_links: false,
+            check_links_timeout: 10,
+            check_links_max_age: 7,
+            check_links_exclude: vec![],
+            cards_dir: PathBuf::from("data/cards"),
+            cache_dir: PathBuf::from(".aipr-cache"),
+            offline: false,
+            fetch_timeout: 10,
+            crossref_api: "https://api.crossref.org".to_string(),
+            license: None,
+            footer: FooterConfig::default(),
+            footer_template: None,
+            partials_dir: None,
+            badge_style: BadgeStyle::Flat,
+            edit_badge: BadgeConfig::default(),
+            comments: CommentsConfig::default(),
+            feedback: FeedbackConfig::default(),
+            colab_badge: None,
+            language: None,
+            translations: BTreeMap::new(),
+            po_dir: PathBuf::from("po"),
+            structured_data: true,
+            meta_tags: true,
+            publisher: "Vector Institute".to_string(),
+            feed: false,
+            feed_max: 20,
+            metadata: false,
+            abbreviations: BTreeMap::new(),
+            difficulty_colors: BTreeMap::new(),
+            series: BTreeMap::new(),
+            redirects: BTreeMap::new(),
+            links: BTreeMap::new(),
+            link_rules: vec![],
+        }
+    }
+}
+
+impl AIPRConfig {
+    /// Read the preprocessor's table from the book configuration.
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        let key = format!("preprocessor.{}", AIPRPreprocessor::NAME);
+        let aipr_config = config.get_deserialized_opt(key)?;
+        Ok(aipr_config.unwrap_or_default())
+    }
+
+    /// Notebook of the chapter at `source_path`, relative to the book's `src`
+    /// directory, that `colab=auto` stands for.
+    pub fn auto_notebook(&self, source_path: &Path) -> String {
+        let slashed = |path: &Path| {
+            path.components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        };
+        let stem = source_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        let dir = source_path.parent().map(slashed).unwrap_or_default();
+        self.colab_auto_pattern
+            .replace("{path}", &slashed(&source_path.with_extension("")))
+            .replace("{dir}", &dir)
+            .replace("{stem}", &stem)
+            .trim_start_matches('/')
+            .to_string()
+    }
+
+    /// Path within `colab_repo` of the notebook at `path`, relative to
+    /// `colab_root`.
+    pub fn notebook_path(&self, path: &str) -> String {
+        [self.colab_root.trim_matches('/'), path.trim_matches('/')]
+            .iter()
+            .filter(|segment| !segment.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// URL of the page showing the notebook at `path`, relative to
+    /// `colab_root`.
+    pub fn notebook_blob_url(&self, path: &str) -> String {
+        self.forge.blob_url(
+            &self.colab_repo,
+            &self.colab_branch,
+            &self.notebook_path(path),
+        )
+    }
+
+    /// Colab URL opening the notebook at `path`, relative to `colab_root`,
+    /// or at the URL `path`. Colab only opens notebooks hosted on github.com.
+    pub fn colab_url(&self, path: &str) -> Option<String> {
+        if is_notebook_url(path) {
+            return colab_link(path);
+        }
+        let blob_url = self.notebook_blob_url(path);
+        let blob_path = blob_url.strip_prefix("https://github.com/")?;
+        Some(format!(
+            "https://colab.research.google.com/github/{blob_path}"
+        ))
+    }
+
+    /// API URL of the notebook at `path`, relative to `colab_root`.
+    pub fn notebook_api_url(&self, path: &str) -> String {
+        self.forge.file_api_url(
+            &self.colab_repo,
+            &self.colab_branch,
+            &self.notebook_path(path),
+        )
+    }
+
+    /// Raw URL of the notebook file at `path`, relative to `colab_root`.
+    pub fn notebook_raw_url(&self, path: &str) -> String {
+        self.forge.raw_url(
+            &self.colab_repo,
+            &self.colab_branch,
+            &self.notebook_path(path),
+        )
+    }
+
+    /// Kaggle URL importing the notebook at `path`, relative to `colab_root`,
+    /// into a new kernel.
+    pub fn kaggle_url(&self, path: &str) -> String {
+        format!(
+            "https://kaggle.com/kernels/welcome?src={}",
+            self.notebook_blob_url(path)
+        )
+    }
+
+    /// URL of the raw contents of the file at `path` in `code_repo`.
+    pub fn code_raw_url(&self, path: &str) -> String {
+        self.forge.raw_url(&self.code_repo, &self.code_branch, path)
+    }
+
+    /// SageMaker Studio Lab URL importing the notebook at `path`, relative to
+    /// `colab_root`. Studio Lab only imports notebooks hosted on github.com.
+    pub fn sagemaker_url(&self, path: &str) -> Option<String> {
+        let blob_url = self.notebook_blob_url(path);
+        let blob_path = blob_url.strip_prefix("https://github.com/")?;
+        Some(format!(
+            "https://studiolab.sagemaker.aws/import/github/{blob_path}"
+        ))
+    }
+
+    /// Binder URL launching JupyterLab on the notebook at `path`, relative to
+    /// `colab_root`, in an environment built from `colab_repo`.
+    pub fn binder_url(&self, path: &str) -> String {
+        let repo = self.colab_repo.trim_matches('/');
+        let spec = match self.forge.host() {
+            "github.com" => format!("gh/{repo}"),
+            "gitlab.com" => format!("gl/{}", percent_encode(repo)),
+            _ => format!("git/{}", percent_encode(&self.forge.repo_url(repo))),
+        };
+        format!(
+            "https://mybinder.org/v2/{spec}/{}?labpath={}",
+            self.colab_branch,
+            percent_encode(&self.notebook_path(path))
+        )
+    }
+
+    /// Badge color of the difficulty `level`, if it's configured or built in.
+    pub fn difficulty_color(&self, level: &str) -> Option<&str> {
+        if let Some(color) = self.difficulty_colors.get(level) {
+            return Some(color);
+        }
+        match level {
+            "beginner" => Some("brightgreen"),
+            "intermediate" => Some("orange"),
+            "advanced" => Some("red"),
+            _ => None,
+        }
+    }
+
+    /// `rel` of the rewritten external link to `url`.
+    pub fn link_rel(&self, url: &str) -> &str {
+        self.link_rules
+            .iter()
+            .find(|rule| link_check::host_matches(url, &rule.domains))
+            .map_or(&self.link_rel, |rule| &rule.rel)
+    }
+
+    /// Absolute URL of the rendered chapter at `path` (relative to the book's
+    /// `src` directory), if `site_url` is configured.
+    pub fn chapter_url(&self, path: &Path) -> Option<String> {
+        let site_url = self.site_url.as_ref()?;
+        let html_path = path.with_extension("html");
+        let html_path = html_path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        Some(format!("{}/{html_path}", site_url.trim_end_matches('/')))
+    }
+
+    /// URL of the commit `hash` of the book's repository, `issue_repo`.
+    pub fn commit_url(&self, hash: &str) -> String {
+        self.forge.commit_url(&self.issue_repo, hash)
+    }
+
+    /// URL for filing a new issue, using `template` over the configured
+    /// `issue_template` when given.
+    pub fn issue_url(&self, template: Option<&str>) -> String {
+        self.forge
+            .new_issue_url(&self.issue_repo, template.unwrap_or(&self.issue_template))
+    }
+
+    /// URL editing the source file of the chapter at `path`, relative to the
+    /// book's `src` directory, on the `forge`.
+    pub fn edit_url(&self, path: &Path) -> String {
+        let path = path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let path = [self.edit_root.trim_matches('/'), &path]
+            .iter()
+            .filter(|segment| !segment.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join("/");
+        self.forge.edit_url(
+            self.edit_repo.as_ref().unwrap_or(&self.issue_repo),
+            &self.edit_branch,
+            &path,
+        )
+    }
+
+    /// Image URL of the header's "Edit this page" badge, reading `text`.
+    pub fn edit_page_badge_url(&self, text: &str) -> String {
+        self.badge_url(text, &BadgeConfig::default(), "blue", self.forge.logo())
+    }
+
+    /// Image URL of the header's "Suggest an Edit" badge, reading `text`.
+    pub fn edit_badge_url(&self, text: &str) -> String {
+        self.badge_url(text, &self.edit_badge, "black", self.forge.logo())
+    }
+
+    /// Image URL of the header's Colab badge, reading `text` unless it's
+    /// Colab's own.
+    pub fn colab_badge_url(&self, text: &str) -> String {
+        match &self.colab_badge {
+            None if self.badge_style == BadgeStyle::Flat => {
+                "https://colab.research.google.com/assets/colab-badge.svg".to_string()
+            }
+            badge => self.badge_url(
+                text,
+                &badge.clone().unwrap_or_default(),
+                "F9AB00",
+                "googlecolab",
+            ),
+        }
+    }
+
+    /// shields.io URL of a badge reading `text`, looking like `badge` over the
+    /// default `color` and `logo`.
+    fn badge_url(&self, text: &str, badge: &BadgeConfig, color: &str, logo: &str) -> String {
+        let hex = |color: &str| color.trim_start_matches('#').to_string();
+        let mut params = vec![];
+        let logo = badge.logo.as_deref().unwrap_or(logo);
+        if !logo.is_empty() {
+            params.push(format!("logo={logo}"));
+        }
+        if let Some(color) = &badge.logo_color {
+            params.push(format!("logoColor={}", hex(color)));
+        }
+        if let Some(color) = &badge.label_color {
+            params.push(format!("labelColor={}", hex(color)));
+        }
+        params.push(format!("style={}", self.badge_style.as_str()));
+        format!(
+            "https://img.shields.io/badge/{}-{}?{}",
+            percent_encode(&text.replace('-', "--").replace(' ', "_")),
+            hex(badge.color.as_deref().unwrap_or(color)),
+            params.join("&")
+        )
+    }
+}
+
+/// Whether the notebook `path` of a header is a URL rather than a path in
+/// `colab_repo`.
+pub(crate) fn is_notebook_url(path: &str) -> bool {
+    path.starts_with("https://") || path.starts_with("http://")
+}
+
+/// Colab URL of the notebook at `url`, if Colab opens it: a Colab link, kept
+/// as is, or the page of a notebook on github.com.
+pub(crate) fn colab_link(url: &str) -> Option<String> {
+    if let Some(path) = url.strip_prefix("https://colab.research.google.com/") {
+        return (!path.is_empty()).then(|| url.to_string());
+    }
+    let blob_path = url.strip_prefix("https://github.com/")?;
+    let is_notebook = blob_path.split('/').nth(2) == Some("blob")
+        && blob_path.split(['?', '#']).next()?.ends_with(".ipynb");
+    is_notebook.then(|| format!("https://colab.research.google.com/github/{blob_path}"))
+}
+
+/// Percent-encodes every byte of `value` but unreserved URL characters.
+pub(crate) fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_config_defaults_without_table() -> Result<()> {
+        let config = Config::from_str("[book]\ntitle = \"test\"")?;
+        assert_eq!(AIPRConfig::from_config(&config)?, AIPRConfig::default());
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_config_from_table() -> Result<()> {
+        let config = Config::from_str(
+            "[preprocessor.ai-pocket-reference]\n\
+             command = \"mdbook-ai-pocket-reference\"\n\
+             colab_repo = \"my-org/my-code\"\n\
+             colab_root = \"\"\n\
+             issue_template = \"\"\n\
+             expand_renderers = [\"html\", \"epub\"]\n\
+             reading_time_rounding = \"ceil\"\n\
+             reading_time_format = \"range\"\n\
+             [preprocessor.ai-pocket-reference.difficulty_colors]\n\
+             beginner = \"blue\"\n\
+             expert = \"black\"\n\
+             [preprocessor.ai-pocket-reference.series]\n\
+             fine-tuning = [\"nlp/lora.md\", \"nlp/qlora.md\"]\n\
+             [[preprocessor.ai-pocket-reference.link_rules]]\n\
+             domains = [\"vectorinstitute.ai\"]\n\
+             rel = \"noopener\"\n\
+             [[preprocessor.ai-pocket-reference.link_rules]]\n\
+             domains = [\"*\"]\n\
+             rel = \"noopener noreferrer nofollow\"\n",
+        )?;
+        let aipr_config = AIPRConfig::from_config(&config)?;
+
+        assert_eq!(
+            aipr_config,
+            AIPRConfig {
+                colab_repo: "my-org/my-code".to_string(),
+                colab_root: "".to_string(),
+                issue_template: "".to_string(),
+                expand_renderers: vec!["html".to_string(), "epub".to_string()],
+                reading_time_rounding: Rounding::Ceil,
+                reading_time_format: ReadingTimeFormat::Range,
+                difficulty_colors: BTreeMap::from([
+                    ("beginner".to_string(), "blue".to_string()),
+                    ("expert".to_string(), "black".to_string()),
+                ]),
+                series: BTreeMap::from([(
+                    "fine-tuning".to_string(),
+                    vec!["nlp/lora.md".to_string(), "nlp/qlora.md".to_string()],
+                )]),
+                link_rules: vec![
+                    LinkRule {
+                        domains: vec!["vectorinstitute.ai".to_string()],
+                        rel: "noopener".to_string(),
+                    },
+                    LinkRule {
+                        domains: vec!["*".to_string()],
+                        rel: "noopener noreferrer nofollow".to_string(),
+                    },
+                ],
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            aipr_config.link_rel("https://www.vectorinstitute.ai/"),
+            "noopener"
+        );
+        assert_eq!(
+            aipr_config.link_rel("https://fake.io"),
+            "noopener noreferrer nofollow"
+        );
+        assert_eq!(
+            AIPRConfig::default().link_rel("https://fake.io"),
+            "noopener noreferrer"
+        );
+        assert_eq!(aipr_config.difficulty_color("beginner"), Some("blue"));
+        assert_eq!(aipr_config.difficulty_color("expert"), Some("black"));
+        assert_eq!(aipr_config.difficulty_color("advanced"), Some("red"));
+        assert_eq!(aipr_config.difficulty_color("easy"), None);
+        assert_eq!(
+            aipr_config.colab_url("nlp/lora.ipynb").as_deref(),
+            Some(
+                "https://colab.research.google.com/github/my-org/my-code/blob/main/nlp/lora.ipynb"
+            )
+        );
+        assert_eq!(
+            aipr_config.commit_url("0a1b2c3"),
+            "https://github.com/VectorInstitute/ai-pocket-reference/commit/0a1b2c3"
+        );
+        assert_eq!(
+            aipr_config.notebook_api_url("nlp/lora.ipynb"),
+            "https://api.github.com/repos/my-org/my-code/contents/nlp/lora.ipynb?ref=main"
+        );
+        assert_eq!(
+            aipr_config.notebook_raw_url("nlp/lora.ipynb"),
+            "https://raw.githubusercontent.com/my-org/my-code/main/nlp/lora.ipynb"
+        );
+        assert_eq!(
+            aipr_config.binder_url("nlp/lora notes.ipynb"),
+            "https://mybinder.org/v2/gh/my-org/my-code/main?labpath=nlp%2Flora%20notes.ipynb"
+        );
+        assert_eq!(
+            aipr_config.sagemaker_url("nlp/lora.ipynb").as_deref(),
+            Some("https://studiolab.sagemaker.aws/import/github/my-org/my-code/blob/main/nlp/lora.ipynb")
+        );
+        assert_eq!(
+            aipr_config.kaggle_url("nlp/lora.ipynb"),
+            "https://kaggle.com/kernels/welcome?src=https://github.com/my-org/my-code/blob/main/nlp/lora.ipynb"
+        );
+        assert_eq!(
+            aipr_config.issue_url(None),
+            "https://github.com/VectorInstitute/ai-pocket-reference/issues/new"
+        );
+        assert_eq!(
+            aipr_config.issue_url(Some("typo.yml")),
+            "https://github.com/VectorInstitute/ai-pocket-reference/issues/new?template=typo.yml"
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_config_footer() -> Result<()> {
+        let footer = |table: &str| -> Result<FooterConfig> {
+            let config = Config::from_str(&format!("[preprocessor.ai-pocket-reference]\n{table}"))?;
+            Ok(AIPRConfig::from_config(&config)?.footer)
+        };
+
+        assert_eq!(footer("")?, FooterConfig::default());
+        assert!(!footer("footer = false")?.enabled);
+        let config = footer(
+            "[preprocessor.ai-pocket-reference.footer]\n\
+             tagline = \"Pocket-sized ML\"\n\
+             links = [{ label = \"Blog\", url = \"https://fake.io/blog\" }]\n",
+        )?;
+        assert!(config.enabled);
+        assert_eq!(config.variables.tagline.as_deref(), Some("Pocket-sized ML"));
+        assert_eq!(config.variables.links.len(), 1);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_config_comments() -> Result<()> {
+        let config = Config::from_str(
+            "[preprocessor.ai-pocket-reference.comments]\n\
+             provider = \"utterances\"\n\
+             repo = \"VectorInstitute/ai-pocket-reference\"\n",
+        )?;
+        let comments = AIPRConfig::from_config(&config)?.comments;
+        assert_eq!(comments.provider, CommentsProvider::Utterances);
+        assert_eq!(
+            comments.repo.as_deref(),
+            Some("VectorInstitute/ai-pocket-reference")
+        );
+        assert_eq!(comments.mapping, "pathname");
+        assert_eq!(comments.theme(), "preferred-color-scheme");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_config_forge() -> Result<()> {
+        let config = Config::from_str(
+            "[preprocessor.ai-pocket-reference]\n\
+             colab_repo = \"my-org/my-code\"\n\
+             issue_template = \"typo.md\"\n\
+             forge = \"gitlab\"\n",
+        )?;
+        let config = AIPRConfig::from_config(&config)?;
+        assert_eq!(config.colab_url("nlp/lora.ipynb"), None);
+        assert_eq!(
+            config.notebook_raw_url("nlp/lora.ipynb"),
+            "https://gitlab.com/my-org/my-code/-/raw/main/notebooks/nlp/lora.ipynb"
+        );
+        assert_eq!(
+            config.kaggle_url("nlp/lora.ipynb"),
+            "https://kaggle.com/kernels/welcome?src=\
+            https://gitlab.com/my-org/my-code/-/blob/main/notebooks/nlp/lora.ipynb"
+        );
+        assert_eq!(
+            config.binder_url("nlp/lora.ipynb"),
+            "https://mybinder.org/v2/gl/my-org%2Fmy-code/main?labpath=notebooks%2Fnlp%2Flora.ipynb"
+        );
+        assert_eq!(
+            config.issue_url(None),
+            "https://gitlab.com/VectorInstitute/ai-pocket-reference/-/issues/new\
+            ?issuable_template=typo"
+        );
+        assert_eq!(
+            config.edit_badge_url("Suggest an Edit"),
+            "https://img.shields.io/badge/Suggest_an_Edit-black?logo=gitlab&style=flat"
+        );
+
+        let config = Config::from_str(
+            "[preprocessor.ai-pocket-reference]\nforge = \"https://github.example.com\"\n",
+        )?;
+        let config = AIPRConfig::from_config(&config)?;
+        assert_eq!(
+            config.binder_url("lora.ipynb"),
+            "https://mybinder.org/v2/git/https%3A%2F%2Fgithub.example.com%2F\
+            VectorInstitute%2Fai-pocket-reference-code/main?labpath=notebooks%2Flora.ipynb"
+        );
+
+        let config = Config::from_str("[preprocessor.ai-pocket-reference]\nforge = \"hg\"\n")?;
+        assert!(AIPRConfig::from_config(&config).is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("{path}.ipynb", "nlp/lora.ipynb")]
+    #[case("{dir}/{stem}/main.ipynb", "nlp/lora/main.ipynb")]
+    #[case("{stem}.ipynb", "lora.ipynb")]
+    fn test_auto_notebook(#[case] pattern: &str, #[case] expected: &str) -> Result<()> {
+        let config = AIPRConfig {
+            colab_auto_pattern: pattern.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.auto_notebook(Path::new("nlp/lora.md")), expected);
+        assert_eq!(
+            config.colab_url(&config.auto_notebook(Path::new("nlp/lora.md"))),
+            Some(format!(
+                "https://colab.research.google.com/github/VectorInstitute/\
+                ai-pocket-reference-code/blob/main/notebooks/{expected}"
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        "https://colab.research.google.com/drive/1a2b",
+        Some("https://colab.research.google.com/drive/1a2b")
+    )]
+    #[case(
+        "https://github.com/my-org/other-repo/blob/main/lora.ipynb",
+        Some("https://colab.research.google.com/github/my-org/other-repo/blob/main/lora.ipynb")
+    )]
+    #[case("https://github.com/my-org/other-repo/tree/main/notebooks", None)]
+    #[case("https://colab.research.google.com/", None)]
+    #[case("https://example.com/lora.ipynb", None)]
+    fn test_colab_url_of_url(#[case] url: &str, #[case] expected: Option<&str>) -> Result<()> {
+        assert_eq!(AIPRConfig::default().colab_url(url).as_deref(), expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_badge_urls() -> Result<()> {
+        let config = AIPRConfig::default();
+        assert_eq!(
+            config.edit_badge_url("Suggest an Edit"),
+            "https://img.shields.io/badge/Suggest_an_Edit-black?logo=github&style=flat"
+        );
+        assert_eq!(
+            config.colab_badge_url("Open in Colab"),
+            "https://colab.research.google.com/assets/colab-badge.svg"
+        );
+
+        let config = Config::from_str(
+            "[preprocessor.ai-pocket-reference]\n\
+             badge_style = \"for-the-badge\"\n\
+             [preprocessor.ai-pocket-reference.edit_badge]\n\
+             color = \"#0F766E\"\n\
+             label_color = \"555\"\n\
+             logo = \"\"\n",
+        )?;
+        let config = AIPRConfig::from_config(&config)?;
+        assert_eq!(
+            config.edit_badge_url("Suggest an Edit"),
+            "https://img.shields.io/badge/Suggest_an_Edit-0F766E?labelColor=555&style=for-the-badge"
+        );
+        assert_eq!(
+            config.colab_badge_url("Open in Colab"),
+            "https://img.shields.io/badge/Open_in_Colab-F9AB00?logo=googlecolab&style=for-the-badge"
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("round", 1.5, 2.0)]
+    #[case("ceil", 0.1, 1.0)]
+    #[case("floor", 1.9, 1.0)]
+    fn test_rounding(#[case] mode: &str, #[case] value: f32, #[case] expected: f32) -> Result<()> {
+        assert_eq!(Rounding::from_str(mode)?.apply(value), expected);
+        assert!(Rounding::from_str("up").is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+//! Works cited by DOI through `{{#aipr_doi}}`, read from the CSL JSON that
+//! Crossref serves for them.
+
+use crate::bibliography::initials;
+use crate::config::percent_encode;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Whether `doi` looks like a DOI, i.e. `10.<registrant>/<suffix>`.
+pub(crate) fn is_doi(doi: &str) -> bool {
+    doi.strip_prefix("10.")
+        .and_then(|rest| rest.split_once('/'))
+        .is_some_and(|(registrant, suffix)| !registrant.is_empty() && !suffix.is_empty())
+}
+
+pub(crate) fn doi_url(doi: &str) -> String {
+    format!("https://doi.org/{doi}")
+}
+
+/// URL of the CSL JSON of `doi` in the Crossref API at `api`.
+pub(crate) fn api_url(api: &str, doi: &str) -> String {
+    format!(
+        "{}/works/{doi}/transform/application/vnd.citationstyles.csl+json",
+        api.trim_end_matches('/')
+    )
+}
+
+/// Path, within the cache directory, of the CSL JSON of `doi`.
+pub(crate) fn cache_key(doi: &str) -> PathBuf {
+    PathBuf::from("doi").join(format!("{}.json", percent_encode(doi)))
+}
+
+/// A work, with the fields of its formatted citation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Work {
+    pub authors: Option<String>,
+    pub year: Option<String>,
+    pub title: String,
+    pub venue: Option<String>,
+}
+
+impl Work {
+    pub fn from_csl_json(json: &str) -> anyhow::Result<Self> {
+        let value: Value = serde_json::from_str(json)?;
+        // Crossref gives some fields as single-element lists
+        let text = |name: &str| match value.get(name)? {
+            Value::String(text) => Some(text.clone()),
+            Value::Array(items) => items.first()?.as_str().map(str::to_string),
+            _ => None,
+        };
+
+        let authors = value
+            .get("author")
+            .and_then(Value::as_array)
+            .map(|authors| {
+                authors
+                    .iter()
+                    .filter_map(|author| {
+                        let family = author.get("family").and_then(Value::as_str);
+                        let given = author.get("given").and_then(Value::as_str);
+                        match (family, given) {
+                            (Some(family), Some(given)) => {
+                                Some(format!("{family}, {}", initials(given)))
+                            }
+                            (Some(family), None) => Some(family.to_string()),
+                            _ => author.get("literal")?.as_str().map(str::to_string),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .filter(|authors| !authors.is_empty());
+        let year = value
+            .pointer("/issued/date-parts/0/0")
+            .and_then(Value::as_u64)
+            .map(|year| year.to_string());
+        let title = text("title").ok_or_else(|| anyhow::anyhow!("Work has no title"))?;
+
+        Ok(Self {
+            authors,
+            year,
+            title,
+            venue: text("container-title").or_else(|| text("publisher")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_work_from_csl_json() -> Result<()> {
+        let work = Work::from_csl_json(
+            r#"{
+                "title": "Deep learning",
+                "author": [
+                    {"family": "LeCun", "given": "Yann"},
+                    {"family": "Bengio", "given": "Yoshua"},
+                    {"literal": "Google Brain"}
+                ],
+                "issued": {"date-parts": [[2015, 5, 27]]},
+                "container-title": ["Nature"],
+                "publisher": "Springer"
+            }"#,
+        )?;
+        assert_eq!(
+            work,
+            Work {
+                authors: Some("LeCun, Y., Bengio, Y., Google Brain".to_string()),
+                year: Some("2015".to_string()),
+                title: "Deep learning".to_string(),
+                venue: Some("Nature".to_string()),
+            }
+        );
+        assert!(Work::from_csl_json(r#"{"author": []}"#).is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("10.1038/nature14539", true)]
+    #[case("10.1145/3442188.3445922", true)]
+    #[case("10./x", false)]
+    #[case("nature14539", false)]
+    fn test_is_doi(#[case] doi: &str, #[case] expected: bool) -> Result<()> {
+        assert_eq!(is_doi(doi), expected);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+//! Atom feed of the book's most recently updated chapters, so readers can
+//! subscribe to new and revised pocket references.
+//!
+//! The feed is written to `feed.xml` in the book's `src` directory, which
+//! mdBook copies to the rendered book alongside the chapters.
+
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Path, relative to the book's `src`, of the generated feed.
+pub(crate) const FEED_PATH: &str = "feed.xml";
+
+/// Maximum length, in characters, of an entry's summary.
+const SUMMARY_MAX_CHARS: usize = 280;
+
+/// A chapter listed in the feed.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FeedEntry {
+    pub title: String,
+    pub url: String,
+    pub summary: Option<String>,
+    pub updated: NaiveDate,
+}
+
+/// The first paragraph of prose in the Markdown `content`, with links and
+/// emphasis reduced to their text and shortened to a few sentences.
+pub(crate) fn summary(content: &str) -> Option<String> {
+    static LINK_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap());
+
+    let paragraph = content.split("\n\n").map(str::trim).find(|paragraph| {
+        paragraph.starts_with(|c: char| c.is_alphanumeric() || matches!(c, '*' | '_' | '['))
+    })?;
+    let text = LINK_RE.replace_all(paragraph, "$1").replace(['*', '`'], "");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= SUMMARY_MAX_CHARS {
+        return Some(text);
+    }
+    let truncated = text.chars().take(SUMMARY_MAX_CHARS).collect::<String>();
+    let truncated = truncated
+        .rsplit_once(' ')
+        .map_or(truncated.as_str(), |(head, _)| head);
+    Some(format!("{truncated}…"))
+}
+
+/// Escapes `s` for use in XML text and attribute values.
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// `date` as an Atom timestamp, at midnight UTC.
+fn atom_date(date: NaiveDate) -> String {
+    format!("{}T00:00:00Z", date.format("%Y-%m-%d"))
+}
+
+/// The Atom feed of `entries`, most recently updated first, for the book at
+/// `site_url`.
+pub(crate) fn to_atom(
+    title: &str,
+    site_url: &str,
+    authors: &[String],
+    entries: &[FeedEntry],
+) -> String {
+    let site_url = format!("{}/", site_url.trim_end_matches('/'));
+    let updated = entries.iter().map(|entry| entry.updated).max();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <title>{}</title>\n", xml_escape(title)));
+    xml.push_str(&format!("  <link href=\"{}\"/>\n", xml_escape(&site_url)));
+    xml.push_str(&format!(
+        "  <link rel=\"self\" href=\"{}{FEED_PATH}\"/>\n",
+        xml_escape(&site_url)
+    ));
+    xml.push_str(&format!("  <id>{}</id>\n", xml_escape(&site_url)));
+    if let Some(updated) = updated {
+        xml.push_str(&format!("  <updated>{}</updated>\n", atom_date(updated)));
+    }
+    for author in authors {
+        xml.push_str(&format!(
+            "  <author><name>{}</name></author>\n",
+            xml_escape(author)
+        ));
+    }
+    for entry in entries {
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!(
+            "    <title>{}</title>\n",
+            xml_escape(&entry.title)
+        ));
+        xml.push_str(&format!(
+            "    <link href=\"{}\"/>\n",
+            xml_escape(&entry.url)
+        ));
+        xml.push_str(&format!("    <id>{}</id>\n", xml_escape(&entry.url)));
+        xml.push_str(&format!(
+            "    <updated>{}</updated>\n",
+            atom_date(entry.updated)
+        ));
+        if let Some(summary) = &entry.summary {
+            xml.push_str(&format!("    <summary>{}</summary>\n", xml_escape(summary)));
+        }
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    #[case(
+        "{{#aipr_header}}\n\n# LoRA\n\n<div>html</div>\n\n**LoRA** adapts [large models](https://a.io)\n\
+         with `low-rank` updates.\n\nMore.",
+        Some("LoRA adapts large models with low-rank updates.")
+    )]
+    #[case("# Only a heading\n", None)]
+    fn test_summary(#[case] content: &str, #[case] expected: Option<&str>) -> Result<()> {
+        assert_eq!(summary(content).as_deref(), expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_summary_truncated() -> Result<()> {
+        let content = "word ".repeat(100);
+        let summary = summary(&content).unwrap();
+        assert!(summary.ends_with("word…"));
+        assert!(summary.chars().count() <= SUMMARY_MAX_CHARS + 1);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_to_atom() -> Result<()> {
+        let entries = [
+            FeedEntry {
+                title: "Q&A".to_string(),
+                url: "https://example.com/book/nlp/qa.html".to_string(),
+                summary: Some("Answers <questions>.".to_string()),
+                updated: NaiveDate::from_ymd_opt(2024, 5, 2).unwrap(),
+            },
+            FeedEntry {
+                title: "LoRA".to_string(),
+                url: "https://example.com/book/nlp/lora.html".to_string(),
+                summary: None,
+                updated: NaiveDate::from_ymd_opt(2024, 4, 1).unwrap(),
+            },
+        ];
+        let xml = to_atom(
+            "AI Pocket References",
+            "https://example.com/book",
+            &["Vector AI Engineering".to_string()],
+            &entries,
+        );
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n"));
+        assert!(xml.contains("  <link rel=\"self\" href=\"https://example.com/book/feed.xml\"/>\n"));
+        assert!(xml.contains("  <updated>2024-05-02T00:00:00Z</updated>\n"));
+        assert!(xml.contains("  <author><name>Vector AI Engineering</name></author>\n"));
+        assert!(xml.contains(
+            "  <entry>\n    <title>Q&amp;A</title>\n    \
+             <link href=\"https://example.com/book/nlp/qa.html\"/>\n    \
+             <id>https://example.com/book/nlp/qa.html</id>\n    \
+             <updated>2024-05-02T00:00:00Z</updated>\n    \
+             <summary>Answers &lt;questions&gt;.</summary>\n  </entry>\n"
+        ));
+        assert_eq!(xml.matches("<summary>").count(), 1);
+        assert!(xml.ends_with("</feed>\n"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+//! Build-time fetching of remote resources by shelling out to `curl`.
+//! Responses are cached on disk under `cache_dir`, so rebuilds don't hit the
+//! network and books keep building with `offline = true`. Delete the cache to
+//! refetch. Fetches are bounded by `fetch_timeout`, so an unreachable host
+//! fails like a missing cache rather than hanging the build.
+
+use crate::config::AIPRConfig;
+use anyhow::Context;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+/// An HTTP error status in response to a fetch, e.g. 404 for a missing
+/// resource or 403 when rate limited.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct HttpError {
+    pub url: String,
+    pub status: u16,
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to fetch {}: HTTP {}", self.url, self.status)
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// Body of the response to a GET of `url`, cached at `cache_key` within the
+/// cache directory. `root` is the book root the cache directory is relative to.
+pub(crate) fn fetch_cached(
+    config: &AIPRConfig,
+    root: &Path,
+    url: &str,
+    cache_key: &Path,
+) -> anyhow::Result<String> {
+    fetch_cached_within(config, root, url, cache_key, None)
+}
+
+/// Like [`fetch_cached`], but refetches responses cached longer than
+/// `max_age_days` ago, unless `offline` is set.
+pub(crate) fn fetch_cached_within(
+    config: &AIPRConfig,
+    root: &Path,
+    url: &str,
+    cache_key: &Path,
+    max_age_days: Option<i64>,
+) -> anyhow::Result<String> {
+    let cached = root.join(&config.cache_dir).join(cache_key);
+    let max_age = max_age_days.map(|days| Duration::from_secs(days.max(0) as u64 * 86_400));
+    let is_fresh = || {
+        max_age.is_none_or(|max_age| {
+            std::fs::metadata(&cached)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age < max_age)
+        })
+    };
+    if let Ok(contents) = std::fs::read_to_string(&cached) {
+        if config.offline || is_fresh() {
+            return Ok(contents);
+        }
+    }
+    anyhow::ensure!(!config.offline, "{url} is not cached and `offline` is set");
+
+    let contents = fetch(url, config.fetch_timeout)?;
+    let written = cached
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&cached, &contents));
+    if let Err(e) = written {
+        log::warn!("Failed to cache {url} at {}: {e}", cached.display());
+    }
+    Ok(contents)
+}
+
+/// Body of the response to a GET of `url`, given up on after `timeout_secs`.
+/// Error statuses are [`HttpError`]s.
+fn fetch(url: &str, timeout_secs: u64) -> anyhow::Result<String> {
+    let output = Command::new("curl")
+        .args(["--silent", "--show-error", "--location"])
+        .args(["--max-time", &timeout_secs.to_string()])
+        .args(["--write-out", "\n%{http_code}", url])
+        .output()
+        .with_context(|| format!("Failed to run curl for {url}"))?;
+    // curl's exit code for an operation timeout
+    anyhow::ensure!(
+        output.status.code() != Some(28),
+        "Timed out fetching {url} after {timeout_secs}s"
+    );
+    anyhow::ensure!(
+        output.status.success(),
+        "Failed to fetch {url}: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    let stdout = String::from_utf8(output.stdout).with_context(|| format!("{url} is not UTF-8"))?;
+    let (body, status) = stdout.rsplit_once('\n').unwrap_or(("", &stdout));
+    let status = status
+        .trim()
+        .parse()
+        .with_context(|| format!("No HTTP status in the response to {url}"))?;
+    if !(200..300).contains(&status) {
+        return Err(HttpError {
+            url: url.to_string(),
+            status,
+        }
+        .into());
+    }
+    Ok(body.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_fetch_cached_offline() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aipr-fetch-{}", std::process::id()));
+        let config = AIPRConfig {
+            offline: true,
+            ..Default::default()
+        };
+        std::fs::create_dir_all(root.join(".aipr-cache/example"))?;
+        std::fs::write(root.join(".aipr-cache/example/cached.txt"), "cached")?;
+
+        let url = "https://example.com/cached.txt";
+        assert_eq!(
+            fetch_cached(&config, &root, url, Path::new("example/cached.txt"))?,
+            "cached"
+        );
+        assert!(fetch_cached(&config, &root, url, Path::new("example/missing.txt")).is_err());
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_fetch_timeout() -> Result<()> {
+        // a server accepting connections but never responding
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/slow", listener.local_addr()?);
+
+        let start = std::time::Instant::now();
+        let error = fetch(&url, 1).unwrap_err();
+        assert!(error.to_string().starts_with("Timed out fetching"));
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+        Ok(())
+    }
+}
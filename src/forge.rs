@@ -0,0 +1,398 @@
+//! Hosts of the book's repositories, that edit, notebook and contributor
+//! links point to: GitHub, including GitHub Enterprise servers, GitLab and
+//! Bitbucket.
+
+use crate::config::percent_encode;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Kind of a [`Forge`], which decides how its URLs are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgeKind {
+    GitHub,
+    GitLab,
+    Bitbucket,
+}
+
+/// Host of the book's repositories, read from `forge = "gitlab"`, the base
+/// URL of a GitHub Enterprise server, or `<kind>:<base URL>` for a
+/// self-hosted GitLab, e.g. `gitlab:https://gitlab.example.com`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Forge {
+    pub kind: ForgeKind,
+    /// Base URL of the forge, without a trailing `/`.
+    pub url: String,
+}
+
+impl Default for Forge {
+    fn default() -> Self {
+        Self {
+            kind: ForgeKind::GitHub,
+            url: "https://github.com".to_string(),
+        }
+    }
+}
+
+impl FromStr for Forge {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, url) = match s.split_once(':') {
+            Some(("http" | "https", _)) => ("github", Some(s)),
+            Some((kind, url)) => (kind, Some(url)),
+            None => (s, None),
+        };
+        let (kind, default_url) = match kind {
+            "github" => (ForgeKind::GitHub, "https://github.com"),
+            "gitlab" => (ForgeKind::GitLab, "https://gitlab.com"),
+            "bitbucket" => (ForgeKind::Bitbucket, "https://bitbucket.org"),
+            _ => anyhow::bail!(
+                "unknown forge `{s}`, expected github, gitlab, bitbucket or a base URL"
+            ),
+        };
+        let url = url.unwrap_or(default_url).trim_end_matches('/');
+        anyhow::ensure!(
+            url.starts_with("http://") || url.starts_with("https://"),
+            "forge URL `{url}` isn't an http(s) URL"
+        );
+        Ok(Self {
+            kind,
+            url: url.to_string(),
+        })
+    }
+}
+
+impl TryFrom<String> for Forge {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Forge {
+    /// Host name of the forge, e.g. `github.com`.
+    pub fn host(&self) -> &str {
+        let host = self
+            .url
+            .split_once("://")
+            .map_or(&*self.url, |(_, rest)| rest);
+        host.split('/').next().unwrap_or(host)
+    }
+
+    /// Whether the forge is github.com, the only one Colab and SageMaker
+    /// Studio Lab open notebooks from.
+    pub fn is_github_com(&self) -> bool {
+        self.kind == ForgeKind::GitHub && self.host() == "github.com"
+    }
+
+    /// simple-icons name of the forge's logo.
+    pub fn logo(&self) -> &'static str {
+        match self.kind {
+            ForgeKind::GitHub => "github",
+            ForgeKind::GitLab => "gitlab",
+            ForgeKind::Bitbucket => "bitbucket",
+        }
+    }
+
+    /// URL of the repository `repo`, `<owner>/<repo>`.
+    pub fn repo_url(&self, repo: &str) -> String {
+        format!("{}/{}", self.url, repo.trim_matches('/'))
+    }
+
+    /// Name of the forge, e.g. `GitHub`.
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            ForgeKind::GitHub => "GitHub",
+            ForgeKind::GitLab => "GitLab",
+            ForgeKind::Bitbucket => "Bitbucket",
+        }
+    }
+
+    /// URL of the issues of `repo`.
+    pub fn issues_url(&self, repo: &str) -> String {
+        match self.kind {
+            ForgeKind::GitLab => format!("{}/-/issues", self.repo_url(repo)),
+            ForgeKind::GitHub | ForgeKind::Bitbucket => format!("{}/issues", self.repo_url(repo)),
+        }
+    }
+
+    /// URL of the page showing the file at `path` of `repo` on `branch`.
+    pub fn blob_url(&self, repo: &str, branch: &str, path: &str) -> String {
+        let path = path.trim_matches('/');
+        match self.kind {
+            ForgeKind::GitHub => format!("{}/blob/{branch}/{path}", self.repo_url(repo)),
+            ForgeKind::GitLab => format!("{}/-/blob/{branch}/{path}", self.repo_url(repo)),
+            ForgeKind::Bitbucket => format!("{}/src/{branch}/{path}", self.repo_url(repo)),
+        }
+    }
+
+    /// URL of the raw contents of the file at `path` of `repo` on `branch`.
+    pub fn raw_url(&self, repo: &str, branch: &str, path: &str) -> String {
+        let path = path.trim_matches('/');
+        match self.kind {
+            ForgeKind::GitHub if self.is_github_com() => format!(
+                "https://raw.githubusercontent.com/{}/{branch}/{path}",
+                repo.trim_matches('/')
+            ),
+            ForgeKind::GitHub | ForgeKind::Bitbucket => {
+                format!("{}/raw/{branch}/{path}", self.repo_url(repo))
+            }
+            ForgeKind::GitLab => format!("{}/-/raw/{branch}/{path}", self.repo_url(repo)),
+        }
+    }
+
+    /// API URL of the file at `path` of `repo` on `branch`, which answers 404
+    /// if the file is missing.
+    pub fn file_api_url(&self, repo: &str, branch: &str, path: &str) -> String {
+        let repo = repo.trim_matches('/');
+        let path = path.trim_matches('/');
+        match self.kind {
+            ForgeKind::GitHub if self.is_github_com() => format!(
+                "https://api.github.com/repos/{repo}/contents/{path}?ref={}",
+                percent_encode(branch)
+            ),
+            ForgeKind::GitHub => format!(
+                "{}/api/v3/repos/{repo}/contents/{path}?ref={}",
+                self.url,
+                percent_encode(branch)
+            ),
+            ForgeKind::GitLab => format!(
+                "{}/api/v4/projects/{}/repository/files/{}?ref={}",
+                self.url,
+                percent_encode(repo),
+                percent_encode(path),
+                percent_encode(branch)
+            ),
+            ForgeKind::Bitbucket => format!(
+                "https://api.bitbucket.org/2.0/repositories/{repo}/src/{}/{path}",
+                percent_encode(branch)
+            ),
+        }
+    }
+
+    /// URL of the forge's editor of the file at `path` of `repo` on `branch`.
+    pub fn edit_url(&self, repo: &str, branch: &str, path: &str) -> String {
+        let path = path.trim_matches('/');
+        match self.kind {
+            ForgeKind::GitHub => format!("{}/edit/{branch}/{path}", self.repo_url(repo)),
+            ForgeKind::GitLab => format!("{}/-/edit/{branch}/{path}", self.repo_url(repo)),
+            ForgeKind::Bitbucket => {
+                format!("{}/src/{branch}/{path}?mode=edit", self.repo_url(repo))
+            }
+        }
+    }
+
+    /// URL of the commit `hash` of `repo`.
+    pub fn commit_url(&self, repo: &str, hash: &str) -> String {
+        match self.kind {
+            ForgeKind::GitHub => format!("{}/commit/{hash}", self.repo_url(repo)),
+            ForgeKind::GitLab => format!("{}/-/commit/{hash}", self.repo_url(repo)),
+            ForgeKind::Bitbucket => format!("{}/commits/{hash}", self.repo_url(repo)),
+        }
+    }
+
+    /// URL for filing a new issue against `repo` with the issue template
+    /// `template`, if any. Bitbucket has no issue templates.
+    pub fn new_issue_url(&self, repo: &str, template: &str) -> String {
+        match (self.kind, template) {
+            (ForgeKind::GitHub, "") | (ForgeKind::Bitbucket, _) => {
+                format!("{}/issues/new", self.repo_url(repo))
+            }
+            (ForgeKind::GitHub, template) => {
+                format!("{}/issues/new?template={template}", self.repo_url(repo))
+            }
+            (ForgeKind::GitLab, "") => format!("{}/-/issues/new", self.repo_url(repo)),
+            // GitLab names templates by their file name without `.md`
+            (ForgeKind::GitLab, template) => format!(
+                "{}/-/issues/new?issuable_template={}",
+                self.repo_url(repo),
+                percent_encode(template.trim_end_matches(".md"))
+            ),
+        }
+    }
+
+    /// URL for filing a new issue against `repo` prefilled with `title` and
+    /// `body`. Bitbucket can't prefill issues.
+    pub fn prefilled_issue_url(&self, repo: &str, title: &str, body: &str) -> String {
+        match self.kind {
+            ForgeKind::GitHub => format!(
+                "{}/issues/new?title={}&body={}",
+                self.repo_url(repo),
+                percent_encode(title),
+                percent_encode(body)
+            ),
+            ForgeKind::GitLab => format!(
+                "{}/-/issues/new?issue[title]={}&issue[description]={}",
+                self.repo_url(repo),
+                percent_encode(title),
+                percent_encode(body)
+            ),
+            ForgeKind::Bitbucket => format!("{}/issues/new", self.repo_url(repo)),
+        }
+    }
+
+    /// Profile URL of the user `username`.
+    pub fn user_url(&self, username: &str) -> String {
+        format!("{}/{username}", self.url)
+    }
+
+    /// Avatar URL of the user `username`, if the forge serves them by
+    /// username.
+    pub fn avatar_url(&self, username: &str) -> Option<String> {
+        match self.kind {
+            ForgeKind::GitHub => Some(format!("{}/{username}.png", self.url)),
+            ForgeKind::GitLab | ForgeKind::Bitbucket => None,
+        }
+    }
+
+    /// Username of the commit author `email`, if it's a noreply address of
+    /// the forge, `<id>+<username>@users.noreply.<host>` on GitHub or
+    /// `<id>-<username>@users.noreply.<host>` on GitLab.
+    pub fn noreply_username<'a>(&self, email: &'a str) -> Option<&'a str> {
+        let local = email.strip_suffix(&format!("@users.noreply.{}", self.host()))?;
+        let separator = match self.kind {
+            ForgeKind::GitHub => '+',
+            ForgeKind::GitLab => '-',
+            ForgeKind::Bitbucket => return None,
+        };
+        // newer noreply addresses are prefixed by the user id
+        Some(match local.split_once(separator) {
+            Some((id, username)) if id.bytes().all(|b| b.is_ascii_digit()) => username,
+            _ => local,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    #[case("github", ForgeKind::GitHub, "https://github.com")]
+    #[case("gitlab", ForgeKind::GitLab, "https://gitlab.com")]
+    #[case("bitbucket", ForgeKind::Bitbucket, "https://bitbucket.org")]
+    #[case(
+        "https://github.example.com/",
+        ForgeKind::GitHub,
+        "https://github.example.com"
+    )]
+    #[case(
+        "gitlab:https://gitlab.example.com",
+        ForgeKind::GitLab,
+        "https://gitlab.example.com"
+    )]
+    fn test_forge_from_str(
+        #[case] value: &str,
+        #[case] kind: ForgeKind,
+        #[case] url: &str,
+    ) -> Result<()> {
+        let forge = value.parse::<Forge>()?;
+        assert_eq!(forge.kind, kind);
+        assert_eq!(forge.url, url);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("sourcehut")]
+    #[case("gitlab:gitlab.example.com")]
+    fn test_forge_from_str_errors(#[case] value: &str) -> Result<()> {
+        assert!(value.parse::<Forge>().is_err());
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_forge_urls() -> Result<()> {
+        let github = Forge::default();
+        let enterprise: Forge = "https://github.example.com".parse()?;
+        let gitlab: Forge = "gitlab".parse()?;
+        let bitbucket: Forge = "bitbucket".parse()?;
+        let repo = "my-org/my-code";
+
+        assert_eq!(
+            github.raw_url(repo, "main", "nlp/lora.ipynb"),
+            "https://raw.githubusercontent.com/my-org/my-code/main/nlp/lora.ipynb"
+        );
+        assert_eq!(
+            enterprise.raw_url(repo, "main", "nlp/lora.ipynb"),
+            "https://github.example.com/my-org/my-code/raw/main/nlp/lora.ipynb"
+        );
+        assert_eq!(
+            gitlab.blob_url(repo, "main", "nlp/lora.ipynb"),
+            "https://gitlab.com/my-org/my-code/-/blob/main/nlp/lora.ipynb"
+        );
+        assert_eq!(
+            bitbucket.blob_url(repo, "main", "nlp/lora.ipynb"),
+            "https://bitbucket.org/my-org/my-code/src/main/nlp/lora.ipynb"
+        );
+        assert_eq!(
+            enterprise.file_api_url(repo, "main", "nlp/lora.ipynb"),
+            "https://github.example.com/api/v3/repos/my-org/my-code/contents/nlp/lora.ipynb?ref=main"
+        );
+        assert_eq!(
+            gitlab.file_api_url(repo, "main", "nlp/lora.ipynb"),
+            "https://gitlab.com/api/v4/projects/my-org%2Fmy-code/repository/files/\
+            nlp%2Flora.ipynb?ref=main"
+        );
+        assert_eq!(
+            gitlab.edit_url(repo, "main", "src/nlp/lora.md"),
+            "https://gitlab.com/my-org/my-code/-/edit/main/src/nlp/lora.md"
+        );
+        assert_eq!(
+            bitbucket.edit_url(repo, "main", "src/nlp/lora.md"),
+            "https://bitbucket.org/my-org/my-code/src/main/src/nlp/lora.md?mode=edit"
+        );
+        assert_eq!(
+            gitlab.commit_url(repo, "0a1b2c3"),
+            "https://gitlab.com/my-org/my-code/-/commit/0a1b2c3"
+        );
+        assert_eq!(
+            gitlab.new_issue_url(repo, "typo.md"),
+            "https://gitlab.com/my-org/my-code/-/issues/new?issuable_template=typo"
+        );
+        assert_eq!(
+            bitbucket.new_issue_url(repo, "typo.yml"),
+            "https://bitbucket.org/my-org/my-code/issues/new"
+        );
+        assert_eq!(
+            github.prefilled_issue_url(repo, "LoRA: helpful", "Page: nlp/lora.md"),
+            "https://github.com/my-org/my-code/issues/new?title=LoRA%3A%20helpful&\
+            body=Page%3A%20nlp%2Flora.md"
+        );
+        assert_eq!(
+            gitlab.prefilled_issue_url(repo, "LoRA", "Page"),
+            "https://gitlab.com/my-org/my-code/-/issues/new?issue[title]=LoRA&\
+            issue[description]=Page"
+        );
+        assert_eq!(
+            enterprise.avatar_url("octocat").as_deref(),
+            Some("https://github.example.com/octocat.png")
+        );
+        assert_eq!(gitlab.avatar_url("octocat"), None);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        "https://github.example.com",
+        "7+octocat@users.noreply.github.example.com",
+        Some("octocat")
+    )]
+    #[case("gitlab", "42-jane-doe@users.noreply.gitlab.com", Some("jane-doe"))]
+    #[case("gitlab", "12345+octocat@users.noreply.github.com", None)]
+    fn test_noreply_username(
+        #[case] forge: &str,
+        #[case] email: &str,
+        #[case] expected: Option<&str>,
+    ) -> Result<()> {
+        assert_eq!(forge.parse::<Forge>()?.noreply_username(email), expected);
+
+        Ok(())
+    }
+}
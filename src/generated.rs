@@ -0,0 +1,68 @@
+//! Helpers for chapters that the preprocessor generates and appends to the
+//! book, rather than reading from the book's `src` directory, and for files it
+//! generates into that directory.
+
+use anyhow::Context;
+use mdbook::book::Chapter;
+use mdbook::utils::fs::path_to_root;
+use std::path::{Path, PathBuf};
+
+/// A chapter referenced from a generated chapter.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ChapterRef {
+    pub name: String,
+    /// Path relative to the book's `src` directory.
+    pub path: PathBuf,
+}
+
+impl ChapterRef {
+    pub fn new(name: &str, path: &Path) -> Self {
+        Self {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// Markdown link to this chapter from the chapter at `from`.
+    pub fn md_link(&self, from: &Path) -> String {
+        format!(
+            "[{}]({}{})",
+            self.name,
+            path_to_root(from),
+            markdown_path(&self.path)
+        )
+    }
+}
+
+/// A chapter that exists only in the processed book, not on disk.
+pub(crate) fn generated_chapter(
+    name: &str,
+    content: String,
+    path: PathBuf,
+    parent_names: Vec<String>,
+) -> Chapter {
+    let mut chapter = Chapter::new(name, content, path, parent_names);
+    chapter.source_path = None;
+    chapter
+}
+
+/// `path` with `/` separators, for use in Markdown links.
+pub(crate) fn markdown_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Writes `contents` to the generated file at `path`, unless it already has
+/// them, so that `mdbook serve` doesn't rebuild endlessly on its own output.
+pub(crate) fn write_generated_file(path: &Path, contents: &str) -> anyhow::Result<()> {
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    }
+    std::fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
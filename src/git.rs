@@ -0,0 +1,288 @@
+//! Helpers for reading chapter history from git. These shell out to the
+//! `git` binary and return `None` when git is unavailable or the file has no
+//! history, so books built outside of a git checkout still render.
+
+use crate::forge::Forge;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Runs `git log` with `args` on the file at `path` and returns its stdout.
+fn git_log(path: &Path, args: &[&str]) -> Option<String> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let file_name = path.file_name()?;
+
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    let output = command
+        .arg("log")
+        .args(args)
+        .arg("--")
+        .arg(file_name)
+        .output()
+        .map_err(|e| log::debug!("Failed to run git for {}: {e}", path.display()))
+        .ok()?;
+
+    if !output.status.success() {
+        log::debug!(
+            "git log failed for {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Date of the last commit that touched the file at `path`.
+pub(crate) fn last_modified(path: &Path) -> Option<NaiveDate> {
+    let stdout = git_log(path, &["-1", "--format=%cs"])?;
+    NaiveDate::parse_from_str(stdout.trim(), "%Y-%m-%d").ok()
+}
+
+/// Dates of the last commits that touched the files under `dir`, by path
+/// relative to it, from a single `git log` rather than one per file.
+pub(crate) fn last_modified_under(dir: &Path) -> Option<HashMap<PathBuf, NaiveDate>> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(["-c", "core.quotePath=false", "log", "--format=%x00%cs"])
+        .args(["--name-only", "--relative", "--", "."])
+        .output()
+        .map_err(|e| log::debug!("Failed to run git in {}: {e}", dir.display()))
+        .ok()?;
+    if !output.status.success() {
+        log::debug!(
+            "git log failed in {}: {}",
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+
+    // commits are listed newest first, so a file's first date is its last
+    let mut dates = HashMap::new();
+    let mut date = None;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        match line.strip_prefix('\0') {
+            Some(commit_date) => date = NaiveDate::parse_from_str(commit_date, "%Y-%m-%d").ok(),
+            None if line.is_empty() => {}
+            None => {
+                if let Some(date) = date {
+                    dates.entry(PathBuf::from(line)).or_insert(date);
+                }
+            }
+        }
+    }
+    Some(dates)
+}
+
+/// Date of the first commit that added the file at `path`, following renames.
+pub(crate) fn first_committed(path: &Path) -> Option<NaiveDate> {
+    let stdout = git_log(path, &["--follow", "--format=%cs"])?;
+    NaiveDate::parse_from_str(stdout.lines().last()?.trim(), "%Y-%m-%d").ok()
+}
+
+/// An author of commits touching a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Contributor {
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+}
+
+impl Contributor {
+    /// Username on `forge`, if the email is a noreply address of it.
+    pub fn username(&self, forge: &Forge) -> Option<&str> {
+        forge.noreply_username(&self.email)
+    }
+}
+
+/// Authors of commits touching the file at `path` (following renames),
+/// deduplicated by email and ordered by number of commits.
+pub(crate) fn contributors(path: &Path) -> Option<Vec<Contributor>> {
+    let stdout = git_log(path, &["--follow", "--format=%aN%x09%aE"])?;
+
+    let mut contributors: Vec<Contributor> = vec![];
+    for line in stdout.lines() {
+        let Some((name, email)) = line.split_once('\t') else {
+            continue;
+        };
+        match contributors
+            .iter_mut()
+            .find(|c| c.email.eq_ignore_ascii_case(email))
+        {
+            Some(contributor) => contributor.commits += 1,
+            None => contributors.push(Contributor {
+                name: name.to_string(),
+                email: email.to_string(),
+                commits: 1,
+            }),
+        }
+    }
+    // stable sort keeps most recent authors first among ties
+    contributors.sort_by_key(|c| std::cmp::Reverse(c.commits));
+    Some(contributors)
+}
+
+/// A commit touching a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Revision {
+    pub hash: String,
+    pub date: NaiveDate,
+    pub author: String,
+    pub subject: String,
+}
+
+/// The `max` most recent commits touching the file at `path` (following
+/// renames), newest first.
+pub(crate) fn revisions(path: &Path, max: usize) -> Option<Vec<Revision>> {
+    let max = format!("--max-count={max}");
+    let stdout = git_log(path, &["--follow", &max, "--format=%H%x09%cs%x09%aN%x09%s"])?;
+
+    let revisions = stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\t');
+            let (hash, date, author, subject) = (
+                fields.next()?,
+                fields.next()?,
+                fields.next()?,
+                fields.next()?,
+            );
+            Some(Revision {
+                hash: hash.to_string(),
+                date: NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?,
+                author: author.to_string(),
+                subject: subject.to_string(),
+            })
+        })
+        .collect();
+    Some(revisions)
+}
+
+/// Initializes a repository at `dir` with a fixed history, for tests:
+/// `nlp/lora.md` committed by Jane Doe on 2024-01-02 and 2024-05-06 and by
+/// the Octocat on 2024-03-04, then `nlp/qlora.md` by the Octocat on
+/// 2024-06-07.
+#[cfg(test)]
+pub(crate) fn init_test_repo(dir: &Path) -> anyhow::Result<()> {
+    const JANE: (&str, &str) = ("Jane Doe", "jane@example.com");
+    const OCTOCAT: (&str, &str) = ("The Octocat", "1+octocat@users.noreply.github.com");
+    let commits = [
+        ("nlp/lora.md", "2024-01-02", JANE, "Add LoRA"),
+        ("nlp/lora.md", "2024-03-04", OCTOCAT, "Fix typo in LoRA"),
+        ("nlp/lora.md", "2024-05-06", JANE, "Expand LoRA"),
+        ("nlp/qlora.md", "2024-06-07", OCTOCAT, "Add QLoRA"),
+    ];
+
+    std::fs::create_dir_all(dir.join("nlp"))?;
+    let git = |args: &[&str], date: &str, (name, email): (&str, &str)| {
+        let date = format!("{date}T12:00:00+00:00");
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "commit.gpgsign=false"])
+            .args(args)
+            .env("GIT_AUTHOR_NAME", name)
+            .env("GIT_AUTHOR_EMAIL", email)
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_NAME", name)
+            .env("GIT_COMMITTER_EMAIL", email)
+            .env("GIT_COMMITTER_DATE", &date)
+            .status()?;
+        anyhow::ensure!(status.success(), "git {} failed", args.join(" "));
+        Ok(())
+    };
+    git(&["init", "--quiet"], "2024-01-01", JANE)?;
+    for (path, date, author, subject) in commits {
+        let contents = std::fs::read_to_string(dir.join(path)).unwrap_or_default();
+        std::fs::write(dir.join(path), format!("{contents}{subject}\n"))?;
+        git(&["add", path], date, author)?;
+        git(&["commit", "--quiet", "-m", subject], date, author)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_history() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("aipr-git-{}", std::process::id()));
+        init_test_repo(&dir)?;
+        let lora = dir.join("nlp/lora.md");
+        let date = |date| NaiveDate::parse_from_str(date, "%Y-%m-%d");
+
+        assert_eq!(last_modified(&lora), Some(date("2024-05-06")?));
+        assert_eq!(first_committed(&lora), Some(date("2024-01-02")?));
+        assert_eq!(
+            last_modified_under(&dir),
+            Some(HashMap::from([
+                (PathBuf::from("nlp/lora.md"), date("2024-05-06")?),
+                (PathBuf::from("nlp/qlora.md"), date("2024-06-07")?),
+            ]))
+        );
+        assert_eq!(
+            contributors(&lora),
+            Some(vec![
+                Contributor {
+                    name: "Jane Doe".to_string(),
+                    email: "jane@example.com".to_string(),
+                    commits: 2,
+                },
+                Contributor {
+                    name: "The Octocat".to_string(),
+                    email: "1+octocat@users.noreply.github.com".to_string(),
+                    commits: 1,
+                },
+            ])
+        );
+        assert_eq!(
+            revisions(&lora, 2),
+            Some(vec![
+                Revision {
+                    hash: "cb41f69f6570aff7dcb2a7d08b9643ba148948a3".to_string(),
+                    date: date("2024-05-06")?,
+                    author: "Jane Doe".to_string(),
+                    subject: "Expand LoRA".to_string(),
+                },
+                Revision {
+                    hash: "1a0aed8fe10101c5477b8ee9e0d1a45f176a5a13".to_string(),
+                    date: date("2024-03-04")?,
+                    author: "The Octocat".to_string(),
+                    subject: "Fix typo in LoRA".to_string(),
+                },
+            ])
+        );
+
+        let missing = dir.join("nlp/does-not-exist.md");
+        assert_eq!(last_modified(&missing), None);
+        assert_eq!(first_committed(&missing), None);
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("12345+octocat@users.noreply.github.com", Some("octocat"))]
+    #[case("octocat@users.noreply.github.com", Some("octocat"))]
+    #[case("octocat@github.com", None)]
+    fn test_contributor_username(
+        #[case] email: &str,
+        #[case] expected: Option<&str>,
+    ) -> Result<()> {
+        let contributor = Contributor {
+            name: "The Octocat".to_string(),
+            email: email.to_string(),
+            commits: 1,
+        };
+        assert_eq!(contributor.username(&Forge::default()), expected);
+
+        Ok(())
+    }
+}
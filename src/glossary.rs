@@ -0,0 +1,170 @@
+//! Glossary of terms read from a TOML file in the book root, mapping each
+//! term to its definition:
+//!
+//! ```toml
+//! LoRA = "Low-rank adaptation, a parameter-efficient fine-tuning method."
+//! PEFT = "Parameter-efficient fine-tuning."
+//! ```
+//!
+//! Terms used through `{{#aipr_term}}` are recorded so that the generated
+//! glossary chapter can link back to the chapters using them.
+
+use crate::generated::{generated_chapter, ChapterRef};
+use anyhow::Context;
+use mdbook::book::Chapter;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Path, relative to the book's `src`, of the generated glossary chapter.
+const GLOSSARY_PATH: &str = "glossary.md";
+
+/// A glossary term, its definition and the chapters using it.
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    name: String,
+    definition: String,
+    used_in: Vec<ChapterRef>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Glossary {
+    /// Terms sorted case-insensitively.
+    terms: Vec<Term>,
+}
+
+impl Glossary {
+    /// Read the glossary at `path`. A missing file is an empty glossary.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read glossary {}", path.display()))?;
+        Self::from_toml(&contents)
+            .with_context(|| format!("Failed to parse glossary {}", path.display()))
+    }
+
+    /// Parse a glossary from the contents of a TOML file.
+    pub fn from_toml(contents: &str) -> anyhow::Result<Self> {
+        let definitions: BTreeMap<String, String> = toml::from_str(contents)?;
+        let mut terms = definitions
+            .into_iter()
+            .map(|(name, definition)| Term {
+                name,
+                definition,
+                used_in: vec![],
+            })
+            .collect::<Vec<_>>();
+        terms.sort_by_key(|term| term.name.to_lowercase());
+        Ok(Self { terms })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    fn term(&self, name: &str) -> Option<&Term> {
+        self.terms
+            .iter()
+            .find(|term| term.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Definition of the term `name`, matched case-insensitively.
+    pub fn definition(&self, name: &str) -> Option<&str> {
+        self.term(name).map(|term| term.definition.as_str())
+    }
+
+    /// Link to the entry of the term `name` in the glossary chapter, from the
+    /// chapter at `from`.
+    pub fn href(&self, name: &str, from: Option<&Path>) -> String {
+        let path_to_root = from.map(mdbook::utils::fs::path_to_root);
+        format!(
+            "{}{}#{}",
+            path_to_root.unwrap_or_default(),
+            Path::new(GLOSSARY_PATH).with_extension("html").display(),
+            mdbook::utils::normalize_id(name.trim())
+        )
+    }
+
+    /// Record that the chapter `chapter_name` at `path` uses the term `name`.
+    pub fn record_usage(&mut self, name: &str, chapter_name: &str, path: &Path) {
+        let Some(term) = self
+            .terms
+            .iter_mut()
+            .find(|term| term.name.eq_ignore_ascii_case(name.trim()))
+        else {
+            return;
+        };
+        if !term.used_in.iter().any(|ch| ch.path == path) {
+            term.used_in.push(ChapterRef::new(chapter_name, path));
+        }
+    }
+
+    /// The generated glossary chapter, listing every term with links back to
+    /// the chapters using it.
+    pub fn to_chapter(&self) -> Chapter {
+        let path = PathBuf::from(GLOSSARY_PATH);
+        let mut content = String::from("# Glossary\n");
+        for term in &self.terms {
+            content.push_str(&format!("\n## {}\n\n{}\n", term.name, term.definition));
+            if !term.used_in.is_empty() {
+                let links = term
+                    .used_in
+                    .iter()
+                    .map(|ch| ch.md_link(&path))
+                    .collect::<Vec<_>>();
+                content.push_str(&format!("\nUsed in: {}\n", links.join(", ")));
+            }
+        }
+        generated_chapter("Glossary", content, path, vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_glossary_chapter() -> Result<()> {
+        let mut glossary = Glossary::from_toml(
+            "PEFT = \"Parameter-efficient fine-tuning.\"\n\
+             LoRA = \"Low-rank adaptation.\"\n\
+             attention = \"Weighting of tokens.\"\n",
+        )?;
+        glossary.record_usage("lora", "LoRA", Path::new("nlp/lora.md"));
+        glossary.record_usage("LoRA", "QLoRA", Path::new("nlp/qlora.md"));
+        glossary.record_usage("LoRA", "LoRA", Path::new("nlp/lora.md"));
+        glossary.record_usage("RLHF", "LoRA", Path::new("nlp/lora.md"));
+
+        assert_eq!(glossary.definition("lora"), Some("Low-rank adaptation."));
+        assert_eq!(glossary.definition("RLHF"), None);
+        assert_eq!(
+            glossary.href("LoRA", Some(Path::new("nlp/lora.md"))),
+            "../glossary.html#lora"
+        );
+
+        let chapter = glossary.to_chapter();
+        assert_eq!(chapter.path, Some(PathBuf::from("glossary.md")));
+        assert_eq!(
+            chapter.content,
+            "# Glossary\n\n\
+             ## attention\n\nWeighting of tokens.\n\n\
+             ## LoRA\n\nLow-rank adaptation.\n\n\
+             Used in: [LoRA](nlp/lora.md), [QLoRA](nlp/qlora.md)\n\n\
+             ## PEFT\n\nParameter-efficient fine-tuning.\n"
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_glossary_missing_file() -> Result<()> {
+        let glossary = Glossary::from_file(Path::new("does-not-exist.toml"))?;
+        assert!(glossary.is_empty());
+        assert!(Glossary::from_toml("LoRA = 1").is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,609 @@
+//! Helpers added by crates embedding the preprocessor, without changes to
+//! this crate: a [`HelperHandler`] registered with
+//! [`AIPRPreprocessor::with_helper`](crate::AIPRPreprocessor::with_helper)
+//! expands its `{{#aipr_<name> <params>}}` in chapters.
+//!
+//! ```
+//! use mdbook_ai_pocket_reference::helpers::{HelperContext, HelperHandler};
+//! use mdbook_ai_pocket_reference::AIPRPreprocessor;
+//!
+//! struct Badge {
+//!     text: String,
+//! }
+//!
+//! impl HelperHandler for Badge {
+//!     fn name() -> &'static str {
+//!         "aipr_badge"
+//!     }
+//!
+//!     fn parse(params: &str) -> anyhow::Result<Self> {
+//!         Ok(Self {
+//!             text: params.to_string(),
+//!         })
+//!     }
+//!
+//!     fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+//!         Ok(format!("<span class=\"badge\">{}</span>", ctx.translate(&self.text)))
+//!     }
+//! }
+//!
+//! let preprocessor = AIPRPreprocessor::new().with_helper::<Badge>();
+//! ```
+
+pub(crate) mod arxiv;
+pub(crate) mod authors;
+pub(crate) mod benchmark;
+pub(crate) mod callout;
+pub(crate) mod cards;
+pub(crate) mod changelog;
+pub(crate) mod chapter;
+pub(crate) mod citation;
+pub(crate) mod cite;
+pub(crate) mod code;
+pub(crate) mod comments;
+pub(crate) mod contributors;
+pub(crate) mod doi;
+pub(crate) mod equation;
+pub(crate) mod figure;
+pub(crate) mod footer;
+pub(crate) mod header;
+pub(crate) mod hf;
+pub(crate) mod last_updated;
+pub(crate) mod license;
+pub(crate) mod link;
+pub(crate) mod params;
+pub(crate) mod prereqs;
+pub(crate) mod quiz;
+pub(crate) mod related;
+pub(crate) mod series_nav;
+pub(crate) mod tags;
+pub(crate) mod term;
+pub(crate) mod toc;
+pub(crate) mod total_reading_time;
+pub(crate) mod version_banner;
+pub(crate) mod video;
+
+use crate::ai_pocket_reference::{BookInfo, ChapterInfo, ChapterSummary};
+use crate::config::AIPRConfig;
+use crate::git;
+use crate::helpers::arxiv::AIPRArxivSettings;
+use crate::helpers::authors::AIPRAuthorsSettings;
+use crate::helpers::benchmark::AIPRBenchmarkSettings;
+use crate::helpers::callout::AIPRCalloutSettings;
+use crate::helpers::cards::{AIPRDatasetSettings, AIPRModelCardSettings};
+use crate::helpers::changelog::AIPRChangelogSettings;
+use crate::helpers::chapter::AIPRChapterSettings;
+use crate::helpers::citation::AIPRCitationSettings;
+use crate::helpers::cite::AIPRCiteSettings;
+use crate::helpers::code::AIPRCodeSettings;
+use crate::helpers::comments::AIPRCommentsSettings;
+use crate::helpers::contributors::AIPRContributorsSettings;
+use crate::helpers::doi::AIPRDoiSettings;
+use crate::helpers::equation::{AIPREqRefSettings, AIPREquationSettings};
+use crate::helpers::figure::{AIPRFigureSettings, AIPRRefSettings};
+use crate::helpers::footer::{AIPRFooterSettings, AIPRNoFooterSettings};
+use crate::helpers::header::AIPRHeaderSettings;
+use crate::helpers::hf::AIPRHfSettings;
+use crate::helpers::last_updated::{AIPRLastUpdatedSettings, LastUpdated};
+use crate::helpers::license::AIPRLicenseSettings;
+use crate::helpers::link::AIPRLinkSettings;
+use crate::helpers::prereqs::AIPRPrereqsSettings;
+use crate::helpers::quiz::AIPRQuizSettings;
+use crate::helpers::related::AIPRRelatedSettings;
+use crate::helpers::series_nav::AIPRSeriesNavSettings;
+use crate::helpers::tags::AIPRTagsSettings;
+use crate::helpers::term::AIPRTermSettings;
+use crate::helpers::toc::AIPRTocSettings;
+use crate::helpers::total_reading_time::AIPRTotalReadingTimeSettings;
+use crate::helpers::version_banner::AIPRVersionBannerSettings;
+use crate::helpers::video::AIPRVideoSettings;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) enum AIPRLinkType {
+    Header(Box<AIPRHeaderSettings>),
+    Footer(AIPRFooterSettings),
+    NoFooter(AIPRNoFooterSettings),
+    Authors(AIPRAuthorsSettings),
+    Citation(AIPRCitationSettings),
+    LastUpdated(AIPRLastUpdatedSettings),
+    Comments(AIPRCommentsSettings),
+    VersionBanner(AIPRVersionBannerSettings),
+    Contributors(AIPRContributorsSettings),
+    Toc(AIPRTocSettings),
+    Related(AIPRRelatedSettings),
+    Prereqs(AIPRPrereqsSettings),
+    Tags(AIPRTagsSettings),
+    Term(AIPRTermSettings),
+    Link(AIPRLinkSettings),
+    Callout(AIPRCalloutSettings),
+    Figure(AIPRFigureSettings),
+    Ref(AIPRRefSettings),
+    Equation(AIPREquationSettings),
+    EqRef(AIPREqRefSettings),
+    Chapter(AIPRChapterSettings),
+    Cite(AIPRCiteSettings),
+    Video(AIPRVideoSettings),
+    Quiz(AIPRQuizSettings),
+    Code(AIPRCodeSettings),
+    Hf(AIPRHfSettings),
+    Arxiv(AIPRArxivSettings),
+    Doi(AIPRDoiSettings),
+    Dataset(AIPRDatasetSettings),
+    ModelCard(AIPRModelCardSettings),
+    Benchmark(AIPRBenchmarkSettings),
+    SeriesNav(AIPRSeriesNavSettings),
+    License(AIPRLicenseSettings),
+    Changelog(AIPRChangelogSettings),
+    TotalReadingTime(AIPRTotalReadingTimeSettings),
+    /// A helper escaped with a backslash, rendered as its literal text.
+    Escaped,
+    /// A helper this crate doesn't know, rendered if added to the
+    /// preprocessor.
+    Custom {
+        name: String,
+        params: String,
+    },
+}
+
+impl AIPRLinkType {
+    /// Name of the closing `{{/name}}` tag, for block helpers wrapping content.
+    pub(crate) fn block_name(&self) -> Option<&'static str> {
+        match self {
+            AIPRLinkType::Callout(_) => Some("aipr_callout"),
+            AIPRLinkType::Equation(_) => Some("aipr_eq"),
+            // quizzes read from a file have no content
+            AIPRLinkType::Quiz(settings) if settings.file.is_none() => Some("aipr_quiz"),
+            _ => None,
+        }
+    }
+
+    /// Sets the content wrapped by a block helper.
+    pub(crate) fn set_body(&mut self, body: &str) {
+        match self {
+            AIPRLinkType::Callout(settings) => settings.body = body.to_string(),
+            AIPRLinkType::Equation(settings) => settings.body = body.to_string(),
+            AIPRLinkType::Quiz(settings) => settings.body = body.to_string(),
+            _ => {}
+        }
+    }
+}
+
+/// A built-in helper: its params, for checking chapters, and how it's
+/// parsed. [`HelperRegistry`] registers all of them.
+#[derive(Debug)]
+pub(crate) struct BuiltInHelper {
+    pub(crate) name: &'static str,
+    /// Names of its `key=value` params, if it takes them. The params of card
+    /// helpers are free-form fields instead.
+    pub(crate) params: Option<&'static [&'static str]>,
+    /// Its params that are `true` or `false`.
+    pub(crate) boolean_params: &'static [&'static str],
+    /// Whether its first param is a path rather than `key=value`.
+    pub(crate) path_first: bool,
+    /// The helper with its trimmed params, empty if it has none, or `None`
+    /// if it needs params it doesn't have, or has params it doesn't take.
+    pub(crate) parse: fn(&str) -> Option<AIPRLinkType>,
+}
+
+impl BuiltInHelper {
+    const fn new(name: &'static str, parse: fn(&str) -> Option<AIPRLinkType>) -> Self {
+        Self {
+            name,
+            params: None,
+            boolean_params: &[],
+            path_first: false,
+            parse,
+        }
+    }
+
+    const fn with_params(self, params: &'static [&'static str]) -> Self {
+        Self {
+            params: Some(params),
+            ..self
+        }
+    }
+
+    const fn with_boolean_params(self, boolean_params: &'static [&'static str]) -> Self {
+        Self {
+            boolean_params,
+            ..self
+        }
+    }
+
+    const fn with_path_first(self) -> Self {
+        Self {
+            path_first: true,
+            ..self
+        }
+    }
+}
+
+/// The supported helpers.
+pub(crate) static BUILT_IN_HELPERS: Lazy<Vec<BuiltInHelper>> = Lazy::new(|| {
+    vec![
+        BuiltInHelper::new(AIPRArxivSettings::name(), |params| {
+            AIPRArxivSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Arxiv)
+        }),
+        BuiltInHelper::new(AIPRAuthorsSettings::name(), |params| {
+            AIPRAuthorsSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Authors)
+        })
+        .with_params(&["authors"]),
+        BuiltInHelper::new(AIPRBenchmarkSettings::name(), |params| {
+            AIPRBenchmarkSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Benchmark)
+        })
+        .with_params(&["caption", "columns", "highlight", "lower_is_better"])
+        .with_path_first(),
+        BuiltInHelper::new(AIPRCalloutSettings::name(), |params| {
+            AIPRCalloutSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Callout)
+        })
+        .with_params(&["type", "title"]),
+        BuiltInHelper::new(AIPRChangelogSettings::name(), |params| {
+            AIPRChangelogSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Changelog)
+        })
+        .with_params(&["max", "title"]),
+        BuiltInHelper::new(AIPRChapterSettings::name(), |params| {
+            AIPRChapterSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Chapter)
+        }),
+        BuiltInHelper::new(AIPRCitationSettings::name(), |params| {
+            AIPRCitationSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Citation)
+        })
+        .with_params(&["apa", "authors", "year"])
+        .with_boolean_params(AIPRCitationSettings::BOOLEAN_PARAMS),
+        BuiltInHelper::new(AIPRCiteSettings::name(), |params| {
+            AIPRCiteSettings::parse(params).ok().map(AIPRLinkType::Cite)
+        }),
+        BuiltInHelper::new(AIPRCodeSettings::name(), |params| {
+            AIPRCodeSettings::parse(params).ok().map(AIPRLinkType::Code)
+        })
+        .with_params(&["lang", "lines"])
+        .with_path_first(),
+        BuiltInHelper::new(AIPRCommentsSettings::name(), |params| {
+            AIPRCommentsSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Comments)
+        }),
+        BuiltInHelper::new(AIPRContributorsSettings::name(), |params| {
+            AIPRContributorsSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Contributors)
+        })
+        .with_params(&["max"]),
+        BuiltInHelper::new(AIPRDatasetSettings::name(), |params| {
+            AIPRDatasetSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Dataset)
+        }),
+        BuiltInHelper::new(AIPRDoiSettings::name(), |params| {
+            AIPRDoiSettings::parse(params).ok().map(AIPRLinkType::Doi)
+        }),
+        BuiltInHelper::new(AIPREquationSettings::name(), |params| {
+            AIPREquationSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Equation)
+        })
+        .with_params(&["label"]),
+        BuiltInHelper::new(AIPREqRefSettings::name(), |params| {
+            AIPREqRefSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::EqRef)
+        }),
+        BuiltInHelper::new(AIPRFigureSettings::name(), |params| {
+            AIPRFigureSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Figure)
+        })
+        .with_params(&["alt", "caption", "id", "src"]),
+        BuiltInHelper::new(AIPRFooterSettings::name(), |params| {
+            AIPRFooterSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Footer)
+        })
+        .with_params(&["feedback", "license", "links", "logo"])
+        .with_boolean_params(AIPRFooterSettings::BOOLEAN_PARAMS),
+        BuiltInHelper::new(AIPRHeaderSettings::name(), |params| {
+            AIPRHeaderSettings::parse(params)
+                .ok()
+                .map(|settings| AIPRLinkType::Header(Box::new(settings)))
+        })
+        .with_params(&[
+            "abbreviations",
+            "author",
+            "binder",
+            "colab",
+            "description",
+            "difficulty",
+            "download",
+            "edit_link",
+            "gpu",
+            "issue_template",
+            "kaggle",
+            "last_updated",
+            "reading_time",
+            "reading_time_display",
+            "reading_time_format",
+            "reading_time_min",
+            "reading_time_rounding",
+            "runtime",
+            "sagemaker",
+            "show_word_count",
+            "submit_issue",
+            "updated",
+            "vram",
+        ])
+        .with_boolean_params(AIPRHeaderSettings::BOOLEAN_PARAMS),
+        BuiltInHelper::new(AIPRHfSettings::name(), |params| {
+            AIPRHfSettings::parse(params).ok().map(AIPRLinkType::Hf)
+        })
+        .with_params(&["card", "dataset", "model", "space"])
+        .with_boolean_params(AIPRHfSettings::BOOLEAN_PARAMS),
+        BuiltInHelper::new(AIPRLastUpdatedSettings::name(), |params| {
+            AIPRLastUpdatedSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::LastUpdated)
+        }),
+        BuiltInHelper::new(AIPRLicenseSettings::name(), |params| {
+            AIPRLicenseSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::License)
+        }),
+        BuiltInHelper::new(AIPRLinkSettings::name(), |params| {
+            AIPRLinkSettings::parse(params).ok().map(AIPRLinkType::Link)
+        }),
+        BuiltInHelper::new(AIPRModelCardSettings::name(), |params| {
+            AIPRModelCardSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::ModelCard)
+        }),
+        BuiltInHelper::new(AIPRNoFooterSettings::name(), |params| {
+            AIPRNoFooterSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::NoFooter)
+        }),
+        BuiltInHelper::new(AIPRPrereqsSettings::name(), |params| {
+            AIPRPrereqsSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Prereqs)
+        })
+        .with_params(&["prereqs", "title"]),
+        BuiltInHelper::new(AIPRQuizSettings::name(), |params| {
+            AIPRQuizSettings::parse(params).ok().map(AIPRLinkType::Quiz)
+        })
+        .with_params(&["file"]),
+        BuiltInHelper::new(AIPRRefSettings::name(), |params| {
+            AIPRRefSettings::parse(params).ok().map(AIPRLinkType::Ref)
+        }),
+        BuiltInHelper::new(AIPRRelatedSettings::name(), |params| {
+            AIPRRelatedSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Related)
+        })
+        .with_params(&["related", "title"]),
+        BuiltInHelper::new(AIPRSeriesNavSettings::name(), |params| {
+            AIPRSeriesNavSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::SeriesNav)
+        }),
+        BuiltInHelper::new(AIPRTagsSettings::name(), |params| {
+            AIPRTagsSettings::parse(params).ok().map(AIPRLinkType::Tags)
+        }),
+        BuiltInHelper::new(AIPRTermSettings::name(), |params| {
+            AIPRTermSettings::parse(params).ok().map(AIPRLinkType::Term)
+        }),
+        BuiltInHelper::new(AIPRTocSettings::name(), |params| {
+            AIPRTocSettings::parse(params).ok().map(AIPRLinkType::Toc)
+        })
+        .with_params(&["depth", "title"]),
+        BuiltInHelper::new(AIPRTotalReadingTimeSettings::name(), |params| {
+            AIPRTotalReadingTimeSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::TotalReadingTime)
+        })
+        .with_params(&["by_part", "part"])
+        .with_boolean_params(AIPRTotalReadingTimeSettings::BOOLEAN_PARAMS),
+        BuiltInHelper::new(AIPRVersionBannerSettings::name(), |params| {
+            AIPRVersionBannerSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::VersionBanner)
+        }),
+        BuiltInHelper::new(AIPRVideoSettings::name(), |params| {
+            AIPRVideoSettings::parse(params)
+                .ok()
+                .map(AIPRLinkType::Video)
+        })
+        .with_params(&["thumbnail", "title", "vimeo", "youtube"])
+        .with_boolean_params(AIPRVideoSettings::BOOLEAN_PARAMS),
+    ]
+});
+
+/// A helper `{{#<name> <params>}}`, parsed from its params when found in a
+/// chapter and replaced by what it renders.
+pub trait HelperHandler {
+    /// Name of the helper, starting with `aipr_`.
+    fn name() -> &'static str
+    where
+        Self: Sized;
+
+    /// The helper with the params `params`, trimmed, or empty if it has none.
+    fn parse(params: &str) -> anyhow::Result<Self>
+    where
+        Self: Sized;
+
+    /// HTML or Markdown the helper is replaced by in the chapter.
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String>;
+}
+
+/// What a [`HelperHandler`] knows about the book and chapter it renders in.
+#[derive(Debug, Clone, Copy)]
+pub struct HelperContext<'a> {
+    pub(crate) config: &'a AIPRConfig,
+    pub(crate) book: &'a BookInfo,
+    pub(crate) chapter: &'a ChapterInfo,
+}
+
+impl HelperContext<'_> {
+    /// Options of the book's `[preprocessor.ai-pocket-reference]` table.
+    pub fn config(&self) -> &AIPRConfig {
+        self.config
+    }
+
+    pub fn chapter_name(&self) -> &str {
+        &self.chapter.name
+    }
+
+    /// Path of the chapter, relative to the book's `src` directory. Drafts
+    /// have none.
+    pub fn chapter_path(&self) -> Option<&Path> {
+        self.chapter.path.as_deref()
+    }
+
+    /// Top-level value of `key` in the chapter's front matter.
+    pub fn front_matter(&self, key: &str) -> Option<&str> {
+        self.chapter.front_matter.get(key).map(String::as_str)
+    }
+
+    /// Translation of the message `msgid` into the book's language.
+    pub fn translate<'b>(&'b self, msgid: &'b str) -> &'b str {
+        self.book.catalog.get(msgid)
+    }
+
+    /// Renders the built-in template `name` with `data`.
+    pub(crate) fn render(&self, name: &str, data: &impl Serialize) -> anyhow::Result<String> {
+        Ok(self.book.templates().render(name, data)?)
+    }
+
+    /// Location of the chapter's source file on disk.
+    pub(crate) fn chapter_source_file(&self) -> Option<PathBuf> {
+        self.chapter
+            .source_path
+            .as_ref()
+            .map(|path| self.book.src_dir.join(path))
+    }
+
+    /// Link from the current chapter to the chapter at `path`, relative to
+    /// the `src` directory, as rendered to HTML.
+    pub(crate) fn chapter_href(&self, path: &str) -> String {
+        let path_to_root = self
+            .chapter
+            .path
+            .as_ref()
+            .map(mdbook::utils::fs::path_to_root)
+            .unwrap_or_default();
+        let html_path = Path::new(path.trim().trim_start_matches("./")).with_extension("html");
+        let html_path = html_path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        format!("{path_to_root}{html_path}")
+    }
+
+    /// Summary of the chapter at `path` referenced by the current chapter.
+    /// Unknown chapters are an error in strict mode, and skipped otherwise.
+    pub(crate) fn resolve_chapter(&self, path: &str) -> anyhow::Result<Option<&ChapterSummary>> {
+        if let Some(summary) = self.book.chapter(path) {
+            return Ok(Some(summary));
+        }
+        if self.config.strict {
+            anyhow::bail!("Chapter `{path}` is not in the book");
+        }
+        log::warn!(
+            "Chapter `{path}` referenced by `{}` is not in the book, skipping.",
+            self.chapter.name
+        );
+        Ok(None)
+    }
+
+    /// Date of the last commit touching the chapter's source file.
+    pub(crate) fn last_updated(&self) -> Option<LastUpdated> {
+        let date = git::last_modified(&self.chapter_source_file()?)?;
+        Some(LastUpdated {
+            date: date.format("%Y-%m-%d").to_string(),
+        })
+    }
+}
+
+type ParseFn = fn(&str) -> anyhow::Result<Box<dyn HelperHandler>>;
+
+fn parse_boxed<H: HelperHandler + 'static>(params: &str) -> anyhow::Result<Box<dyn HelperHandler>> {
+    Ok(Box::new(H::parse(params)?))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Helper {
+    BuiltIn(&'static BuiltInHelper),
+    Added(ParseFn),
+}
+
+/// Helpers known to the preprocessor, by name: the built-in ones and those
+/// added with [`HelperRegistry::register`].
+#[derive(Debug, Clone)]
+pub(crate) struct HelperRegistry(BTreeMap<&'static str, Helper>);
+
+impl Default for HelperRegistry {
+    fn default() -> Self {
+        Self(
+            BUILT_IN_HELPERS
+                .iter()
+                .map(|helper| (helper.name, Helper::BuiltIn(helper)))
+                .collect(),
+        )
+    }
+}
+
+impl HelperRegistry {
+    /// The built-in helpers alone.
+    pub fn built_ins() -> &'static Self {
+        static BUILT_INS: Lazy<HelperRegistry> = Lazy::new(HelperRegistry::default);
+        &BUILT_INS
+    }
+
+    pub fn register<H: HelperHandler + 'static>(&mut self) {
+        self.0.insert(H::name(), Helper::Added(parse_boxed::<H>));
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.0.keys().copied()
+    }
+
+    /// The built-in helper `name`, if any.
+    pub fn built_in(&self, name: &str) -> Option<&'static BuiltInHelper> {
+        match self.0.get(name)? {
+            Helper::BuiltIn(helper) => Some(helper),
+            Helper::Added(_) => None,
+        }
+    }
+
+    /// The added helper `name` with the params `params`, if any.
+    pub fn parse(
+        &self,
+        name: &str,
+        params: &str,
+    ) -> Option<anyhow::Result<Box<dyn HelperHandler>>> {
+        match self.0.get(name)? {
+            Helper::BuiltIn(_) => None,
+            Helper::Added(parse) => Some(parse(params)),
+        }
+    }
+}
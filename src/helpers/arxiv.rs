@@ -0,0 +1,126 @@
+//! The `{{#aipr_arxiv}}` helper citing arXiv papers.
+
+use crate::arxiv::{self, ArxivPaper};
+use crate::config;
+use crate::fetch::fetch_cached;
+use crate::helpers::params::required_params;
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRArxivSettings {
+    id: String,
+}
+
+impl HelperHandler for AIPRArxivSettings {
+    fn name() -> &'static str {
+        "aipr_arxiv"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: required_params(Self::name(), params)?.to_string(),
+        })
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let id = self.id.trim_start_matches("arXiv:");
+        anyhow::ensure!(arxiv::is_arxiv_id(id), "Invalid arXiv id `{id}`");
+
+        // create data for rendering handlebar
+        let mut data = Map::new();
+        data.insert("id".to_string(), to_json(id));
+        data.insert("url".to_string(), to_json(arxiv::abs_url(id)));
+        let paper = fetch_cached(
+            ctx.config,
+            &ctx.book.root,
+            &arxiv::api_url(id),
+            &arxiv::cache_key(id),
+        )
+        .and_then(|xml| ArxivPaper::from_atom(&xml));
+        let paper = match paper {
+            Ok(paper) => paper,
+            Err(e) => {
+                // fall back to a plain link, e.g. when building offline
+                log::warn!("No metadata for arXiv:{id}, rendering a link instead: {e:#}");
+                return ctx.render("aipr_arxiv_link", &data);
+            }
+        };
+        let badge_url = format!(
+            "https://img.shields.io/badge/arXiv-{}-b31b1b?logo=arxiv",
+            config::percent_encode(&id.replace('-', "--"))
+        );
+        data.insert("badge_url".to_string(), to_json(badge_url));
+        data.insert("title".to_string(), to_json(paper.title));
+        data.insert("authors".to_string(), to_json(paper.authors));
+        data.insert("year".to_string(), to_json(paper.year));
+        data.insert("abstract".to_string(), to_json(paper.summary));
+
+        // render
+        let html_string = ctx.render("aipr_arxiv", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_arxiv_render() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aipr-arxiv-{}", std::process::id()));
+        std::fs::create_dir_all(root.join(".aipr-cache/arxiv"))?;
+        std::fs::write(
+            root.join(".aipr-cache/arxiv/2106.09685.xml"),
+            "<feed><entry><published>2021-06-17T17:37:18Z</published>\
+            <title>LoRA</title><summary>Low-rank.</summary>\
+            <author><name>Edward J. Hu</name></author></entry></feed>",
+        )?;
+        let config = AIPRConfig {
+            offline: true,
+            ..Default::default()
+        };
+        let book_info = BookInfo {
+            root: root.clone(),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &book_info,
+            chapter: &ChapterInfo::default(),
+        };
+
+        let html_string = AIPRArxivSettings {
+            id: "2106.09685".to_string(),
+        }
+        .render(&ctx)?;
+        assert!(html_string.contains("<p class=\"aipr-arxiv-meta\">Edward J. Hu (2021)</p>"));
+        assert!(html_string.contains("<p>Low-rank.</p>"));
+        assert!(html_string.contains("https://img.shields.io/badge/arXiv-2106.09685-b31b1b"));
+
+        // uncached papers fall back to a link when offline
+        let html_string = AIPRArxivSettings {
+            id: "1706.03762".to_string(),
+        }
+        .render(&ctx)?;
+        assert_eq!(
+            html_string,
+            "<a href=\"https://arxiv.org/abs/1706.03762\" target=\"_blank\" \
+            rel=\"noopener noreferrer\">arXiv:1706.03762</a>"
+        );
+        assert!(AIPRArxivSettings {
+            id: "not-an-id".to_string()
+        }
+        .render(&ctx)
+        .is_err());
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+//! The `{{#aipr_authors}}` helper rendering author bylines.
+
+use crate::forge::Forge;
+use crate::helpers::params::{_parse_list, _parse_param_str, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde::Serialize;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct AIPRAuthorsSettings {
+    pub(crate) authors: Vec<String>,
+}
+
+impl AIPRAuthorsSettings {
+    pub(crate) fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, &[]);
+        let authors = param_map
+            .get("authors")
+            .map(|s| _parse_list(s))
+            .unwrap_or_default();
+
+        Self { authors }
+    }
+}
+
+impl HelperHandler for AIPRAuthorsSettings {
+    fn name() -> &'static str {
+        "aipr_authors"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(required_params(Self::name(), params)?))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        // create data for rendering handlebar
+        let authors = self
+            .authors
+            .iter()
+            .map(|username| GitHubAuthor::new(username, &ctx.config.forge))
+            .collect::<Vec<_>>();
+        let mut data = Map::new();
+        data.insert("authors".to_string(), to_json(authors));
+
+        // render
+        let html_string = ctx.render("aipr_authors", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct GitHubAuthor {
+    username: String,
+    url: String,
+    avatar_url: Option<String>,
+}
+
+impl GitHubAuthor {
+    /// The user `username` of `forge`.
+    pub(crate) fn new(username: &str, forge: &Forge) -> Self {
+        let username = username.trim_start_matches('@');
+        Self {
+            username: username.to_string(),
+            url: forge.user_url(username),
+            avatar_url: forge.avatar_url(username),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_authors_render() -> Result<()> {
+        let settings = AIPRAuthorsSettings::from_param_str("authors=octocat,@nerdai");
+
+        let html_string = settings.render(&HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        })?;
+        let expected = "<div class=\"aipr-authors\">\n  \
+        <a class=\"aipr-author\" target=\"_blank\" href=\"https://github.com/octocat\">\n    \
+        <img src=\"https://github.com/octocat.png\" alt=\"octocat\" width=\"24\" height=\"24\"/>\n    \
+        <small>octocat</small>\n  </a>\n  \
+        <a class=\"aipr-author\" target=\"_blank\" href=\"https://github.com/nerdai\">\n    \
+        <img src=\"https://github.com/nerdai.png\" alt=\"nerdai\" width=\"24\" height=\"24\"/>\n    \
+        <small>nerdai</small>\n  </a>\n</div>\n";
+
+        assert_eq!(html_string, expected);
+
+        Ok(())
+    }
+}
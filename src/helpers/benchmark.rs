@@ -0,0 +1,147 @@
+//! The `{{#aipr_benchmark}}` helper rendering results tables.
+
+use crate::benchmark::Table;
+use crate::helpers::params::{_parse_list, _parse_param_str, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde::Serialize;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRBenchmarkSettings {
+    /// Results file, relative to the book root.
+    path: String,
+    /// Columns shown, in order. All columns if empty.
+    columns: Vec<String>,
+    /// Whether the best value of each numeric column is highlighted.
+    highlight_best: bool,
+    /// Columns whose best value is the lowest one.
+    lower_is_better: Vec<String>,
+    caption: Option<String>,
+}
+
+impl AIPRBenchmarkSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        let (path, param_str) = param_str
+            .split_once(char::is_whitespace)
+            .unwrap_or((param_str, ""));
+        let param_map = _parse_param_str(param_str, &[]);
+
+        Self {
+            path: path.to_string(),
+            columns: param_map
+                .get("columns")
+                .map(|columns| _parse_list(columns))
+                .unwrap_or_default(),
+            highlight_best: matches!(param_map.get("highlight"), Some(value) if value == "best"),
+            lower_is_better: param_map
+                .get("lower_is_better")
+                .map(|columns| _parse_list(columns))
+                .unwrap_or_default(),
+            caption: param_map.get("caption").cloned(),
+        }
+    }
+}
+
+impl HelperHandler for AIPRBenchmarkSettings {
+    fn name() -> &'static str {
+        "aipr_benchmark"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(required_params(Self::name(), params)?))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let mut table = Table::from_file(&ctx.book.root.join(&self.path))?;
+        if !self.columns.is_empty() {
+            table = table.select(&self.columns)?;
+        }
+        let best = match self.highlight_best {
+            true => table.best_cells(&self.lower_is_better),
+            false => vec![vec![false; table.columns.len()]; table.rows.len()],
+        };
+
+        // create data for rendering handlebar
+        let rows = table
+            .rows
+            .iter()
+            .zip(best)
+            .map(|(row, best)| {
+                row.iter()
+                    .zip(best)
+                    .map(|(value, best)| BenchmarkCell {
+                        value: value.clone(),
+                        best,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut data = Map::new();
+        data.insert("columns".to_string(), to_json(&table.columns));
+        data.insert("rows".to_string(), to_json(rows));
+        data.insert("caption".to_string(), to_json(&self.caption));
+
+        // render
+        let html_string = ctx.render("aipr_benchmark", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct BenchmarkCell {
+    value: String,
+    best: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_benchmark_render() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aipr-benchmark-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("results"))?;
+        std::fs::write(
+            root.join("results/glue.csv"),
+            "Model,MNLI,QQP\nBERT,84.6,71.2\nRoBERTa,87.6,91.9\n",
+        )?;
+        let book_info = BookInfo {
+            root: root.clone(),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book_info,
+            chapter: &ChapterInfo::default(),
+        };
+
+        let settings = AIPRBenchmarkSettings::from_param_str(
+            "results/glue.csv highlight=best columns=Model,MNLI caption=\"GLUE dev\"",
+        );
+        assert_eq!(settings.columns, vec!["Model", "MNLI"]);
+        let html_string = settings.render(&ctx)?;
+        let expected = "<table class=\"aipr-benchmark\">\n  \
+            <caption>GLUE dev</caption>\n  \
+            <thead>\n    <tr><th>Model</th><th>MNLI</th></tr>\n  </thead>\n  \
+            <tbody>\n    \
+            <tr><td>BERT</td><td>84.6</td></tr>\n    \
+            <tr><td>RoBERTa</td><td class=\"aipr-benchmark-best\"><strong>87.6</strong></td></tr>\n  \
+            </tbody>\n</table>\n";
+        assert_eq!(html_string, expected);
+
+        let html_string = AIPRBenchmarkSettings::from_param_str("results/glue.csv").render(&ctx)?;
+        assert!(!html_string.contains("aipr-benchmark-best"));
+        assert!(AIPRBenchmarkSettings::from_param_str("results/missing.csv")
+            .render(&ctx)
+            .is_err());
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
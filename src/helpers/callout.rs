@@ -0,0 +1,68 @@
+//! The `{{#aipr_callout}}` block helper rendering note, tip, warning and
+//! danger boxes.
+
+use crate::ai_pocket_reference::replace_all;
+use crate::helpers::params::_parse_param_str;
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRCalloutSettings {
+    pub(crate) kind: String,
+    pub(crate) title: String,
+    pub(crate) body: String,
+}
+
+impl AIPRCalloutSettings {
+    const KINDS: [&'static str; 4] = ["note", "tip", "warning", "danger"];
+
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, &[]);
+        let kind = match param_map.get("type").map(|s| s.to_lowercase()) {
+            Some(kind) if Self::KINDS.contains(&kind.as_str()) => kind,
+            Some(kind) => {
+                log::warn!("Unknown callout type `{kind}`, using `note`.");
+                "note".to_string()
+            }
+            None => "note".to_string(),
+        };
+        let title = param_map.get("title").cloned().unwrap_or_else(|| {
+            let mut chars = kind.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        });
+
+        Self {
+            kind,
+            title,
+            body: String::new(),
+        }
+    }
+}
+
+impl HelperHandler for AIPRCalloutSettings {
+    fn name() -> &'static str {
+        "aipr_callout"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(params))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        // create data for rendering handlebar
+        let body = replace_all(self.body.trim(), ctx)?;
+        let mut data = Map::new();
+        data.insert("kind".to_string(), to_json(&self.kind));
+        data.insert("title".to_string(), to_json(ctx.translate(&self.title)));
+        data.insert("body".to_string(), to_json(body));
+
+        // render
+        let html_string = ctx.render("aipr_callout", &data)?;
+
+        Ok(html_string)
+    }
+}
@@ -0,0 +1,190 @@
+//! The `{{#aipr_dataset}}` and `{{#aipr_model_card}}` helpers rendering the
+//! cards of `cards_dir`.
+
+use crate::cards;
+use crate::helpers::params::{_parse_param_str, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Params of the card helpers: a card definition and inline fields.
+#[derive(Debug, Clone, PartialEq)]
+struct CardParams {
+    /// Card definition, relative to `cards_dir`.
+    file: Option<String>,
+    /// Inline fields, taking precedence over the card definition's.
+    fields: HashMap<String, String>,
+}
+
+impl CardParams {
+    fn from_param_str(param_str: &str) -> Self {
+        let mut fields = _parse_param_str(param_str, &[]);
+        Self {
+            file: fields.remove("file"),
+            fields,
+        }
+    }
+
+    /// Fields of the card, from its definition and the inline params.
+    fn fields(&self, ctx: &HelperContext) -> anyhow::Result<HashMap<String, String>> {
+        let mut fields = match &self.file {
+            Some(file) => cards::read_card(&ctx.book.root.join(&ctx.config.cards_dir).join(file))?,
+            None => HashMap::new(),
+        };
+        fields.extend(self.fields.clone());
+        Ok(fields)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRDatasetSettings {
+    card: CardParams,
+}
+
+impl AIPRDatasetSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        Self {
+            card: CardParams::from_param_str(param_str),
+        }
+    }
+}
+
+impl HelperHandler for AIPRDatasetSettings {
+    fn name() -> &'static str {
+        "aipr_dataset"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(required_params(Self::name(), params)?))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let mut fields = self.card.fields(ctx)?;
+        let Some(name) = fields.remove("name") else {
+            anyhow::bail!("Dataset card has no `name`");
+        };
+        let card = DatasetCard {
+            name,
+            size: fields.remove("size"),
+            license: fields.remove("license"),
+            link: fields.remove("link"),
+            description: fields.remove("description"),
+        };
+
+        // render
+        let html_string = ctx.render("aipr_dataset", &card)?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRModelCardSettings {
+    card: CardParams,
+}
+
+impl AIPRModelCardSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        Self {
+            card: CardParams::from_param_str(param_str),
+        }
+    }
+}
+
+impl HelperHandler for AIPRModelCardSettings {
+    fn name() -> &'static str {
+        "aipr_model_card"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(required_params(Self::name(), params)?))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let mut fields = self.card.fields(ctx)?;
+        let Some(name) = fields.remove("name") else {
+            anyhow::bail!("Model card has no `name`");
+        };
+        let card = ModelCard {
+            name,
+            parameters: fields.remove("parameters"),
+            context_length: fields.remove("context_length"),
+            license: fields.remove("license"),
+            paper: fields.remove("paper"),
+            checkpoint: fields.remove("checkpoint"),
+            description: fields.remove("description"),
+        };
+
+        // render
+        let html_string = ctx.render("aipr_model_card", &card)?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct ModelCard {
+    name: String,
+    parameters: Option<String>,
+    context_length: Option<String>,
+    license: Option<String>,
+    paper: Option<String>,
+    checkpoint: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct DatasetCard {
+    name: String,
+    size: Option<String>,
+    license: Option<String>,
+    link: Option<String>,
+    description: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_dataset_render() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aipr-dataset-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("data/cards"))?;
+        std::fs::write(
+            root.join("data/cards/squad.toml"),
+            "name = \"SQuAD\"\nsize = \"100k questions\"\nlicense = \"CC BY-SA 4.0\"\n",
+        )?;
+        let book_info = BookInfo {
+            root: root.clone(),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book_info,
+            chapter: &ChapterInfo::default(),
+        };
+
+        let html_string =
+            AIPRDatasetSettings::from_param_str("file=squad.toml size=\"107,785 questions\"")
+                .render(&ctx)?;
+        assert!(html_string.contains("<strong>SQuAD</strong> <small>Dataset</small>"));
+        assert!(html_string.contains("<tr><th>Size</th><td>107,785 questions</td></tr>"));
+        assert!(html_string.contains("<tr><th>License</th><td>CC BY-SA 4.0</td></tr>"));
+
+        let html_string =
+            AIPRDatasetSettings::from_param_str("name=GLUE,link=https://gluebenchmark.com")
+                .render(&ctx)?;
+        assert!(html_string.contains("<a href=\"https://gluebenchmark.com\""));
+        assert!(!html_string.contains("<table>"));
+        assert!(AIPRDatasetSettings::from_param_str("size=1")
+            .render(&ctx)
+            .is_err());
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+//! The `{{#aipr_changelog}}` helper rendering a chapter's revision history.
+
+use crate::git;
+use crate::helpers::params::_parse_param_str;
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde::Serialize;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRChangelogSettings {
+    max: usize,
+    title: String,
+}
+
+impl Default for AIPRChangelogSettings {
+    fn default() -> Self {
+        Self {
+            max: 10,
+            title: "Revision history".to_string(),
+        }
+    }
+}
+
+impl AIPRChangelogSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, &[]);
+        let default = Self::default();
+        let max = param_map
+            .get("max")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(default.max);
+        let title = param_map.get("title").cloned().unwrap_or(default.title);
+
+        Self { max, title }
+    }
+}
+
+impl HelperHandler for AIPRChangelogSettings {
+    fn name() -> &'static str {
+        "aipr_changelog"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(params))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let revisions = ctx
+            .chapter_source_file()
+            .and_then(|f| git::revisions(&f, self.max))
+            .unwrap_or_default();
+        if revisions.is_empty() {
+            log::warn!(
+                "No git history for chapter `{}`, skipping changelog.",
+                ctx.chapter.name
+            );
+            return Ok(String::new());
+        }
+
+        // create data for rendering handlebar
+        let revisions = revisions
+            .iter()
+            .map(|revision| RevisionEntry {
+                date: revision.date.format("%Y-%m-%d").to_string(),
+                short_hash: revision.hash.chars().take(7).collect(),
+                url: ctx.config.commit_url(&revision.hash),
+                author: revision.author.clone(),
+                subject: revision.subject.clone(),
+            })
+            .collect::<Vec<_>>();
+        let mut data = Map::new();
+        data.insert("title".to_string(), to_json(ctx.translate(&self.title)));
+        data.insert("revisions".to_string(), to_json(revisions));
+
+        // render
+        let html_string = ctx.render("aipr_changelog", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct RevisionEntry {
+    date: String,
+    short_hash: String,
+    url: String,
+    author: String,
+    subject: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+    use std::path::PathBuf;
+
+    #[rstest]
+    fn test_changelog_render() -> Result<()> {
+        let src_dir = std::env::temp_dir().join(format!("aipr-changelog-{}", std::process::id()));
+        git::init_test_repo(&src_dir)?;
+        let book = BookInfo {
+            src_dir: src_dir.clone(),
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            source_path: Some(PathBuf::from("nlp/lora.md")),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book,
+            chapter: &chapter,
+        };
+        let settings = AIPRChangelogSettings::from_param_str("max=1,title=History");
+        assert_eq!(settings.max, 1);
+
+        let html_string = settings.render(&ctx)?;
+        assert_eq!(html_string, concat!(
+                "<details class=\"aipr-changelog\">\n",
+                "  <summary>History</summary>\n",
+                "  <ul>\n",
+                "    <li>\n",
+                "      <time datetime=\"2024-05-06\">2024-05-06</time>\n",
+                "      <a target=\"_blank\" href=\"https://github.com/VectorInstitute/ai-pocket-reference/commit/cb41f69f6570aff7dcb2a7d08b9643ba148948a3\"><code>cb41f69</code></a>\n",
+                "      Expand LoRA <span class=\"aipr-changelog-author\">(Jane Doe)</span>\n",
+                "    </li>\n",
+                "  </ul>\n",
+                "</details>\n"
+            ));
+
+        let chapter = ChapterInfo {
+            source_path: Some(PathBuf::from("does-not-exist.md")),
+            ..Default::default()
+        };
+        let html_string = settings.render(&HelperContext {
+            chapter: &chapter,
+            ..ctx
+        })?;
+        assert_eq!(html_string, "");
+
+        std::fs::remove_dir_all(&src_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+//! The `{{#aipr_chapter}}` helper linking other chapters by their title.
+
+use crate::helpers::params::required_params;
+use crate::helpers::{HelperContext, HelperHandler};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRChapterSettings {
+    /// Path of the chapter, optionally followed by a `#fragment`.
+    pub(crate) path: String,
+}
+
+impl HelperHandler for AIPRChapterSettings {
+    fn name() -> &'static str {
+        "aipr_chapter"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            path: required_params(Self::name(), params)?.to_string(),
+        })
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let (path, fragment) = match self.path.split_once('#') {
+            Some((path, fragment)) => (path, Some(fragment)),
+            None => (self.path.as_str(), None),
+        };
+        let Some(summary) = ctx.resolve_chapter(path)? else {
+            return Ok(self.path.clone());
+        };
+        let mut url = ctx.chapter_href(path);
+        if let Some(fragment) = fragment {
+            url.push('#');
+            url.push_str(fragment);
+        }
+
+        // render
+        let html_string = ctx.render(
+            "aipr_chapter",
+            &ChapterLink {
+                title: summary.name.clone(),
+                url,
+            },
+        )?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct ChapterLink {
+    pub(crate) title: String,
+    pub(crate) url: String,
+}
@@ -0,0 +1,259 @@
+//! The `{{#aipr_citation}}` helper rendering how to cite a chapter, as text
+//! and BibTeX.
+
+use crate::git;
+use crate::helpers::params::{_parse_list, _parse_param_str};
+use crate::helpers::{HelperContext, HelperHandler};
+use chrono::Datelike;
+use handlebars::to_json;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct AIPRCitationSettings {
+    authors: Vec<String>,
+    year: Option<i32>,
+    apa: bool,
+}
+
+impl AIPRCitationSettings {
+    /// Params that are `true` or `false`, and can be given as bare flags.
+    pub(crate) const BOOLEAN_PARAMS: &'static [&'static str] = &["apa"];
+
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, Self::BOOLEAN_PARAMS);
+        let authors = param_map
+            .get("authors")
+            .map(|s| _parse_list(s))
+            .unwrap_or_default();
+        let year = param_map.get("year").and_then(|s| s.parse().ok());
+        let apa = matches!(param_map.get("apa"), Some(bool_str) if (bool_str == "true"));
+
+        Self { authors, year, apa }
+    }
+}
+
+impl HelperHandler for AIPRCitationSettings {
+    fn name() -> &'static str {
+        "aipr_citation"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(params))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        // create data for rendering handlebar
+        let authors = if self.authors.is_empty() {
+            &ctx.book.authors
+        } else {
+            &self.authors
+        };
+        let citation = Citation {
+            title: ctx.chapter.name.clone(),
+            book_title: ctx.book.title.clone(),
+            authors: authors.clone(),
+            year: self.year.or_else(|| {
+                git::last_modified(&ctx.chapter_source_file()?).map(|date| date.year())
+            }),
+            url: ctx
+                .chapter
+                .path
+                .as_ref()
+                .and_then(|path| ctx.config.chapter_url(path)),
+        };
+        let mut data = Map::new();
+        data.insert("bibtex".to_string(), to_json(citation.to_bibtex()));
+        if self.apa {
+            data.insert("apa".to_string(), to_json(citation.to_apa()));
+        }
+
+        // render
+        let html_string = ctx.render("aipr_citation", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+struct Citation {
+    title: String,
+    book_title: Option<String>,
+    authors: Vec<String>,
+    /// Year of the last commit touching the chapter, unless overridden.
+    /// Uncommitted chapters have none.
+    year: Option<i32>,
+    url: Option<String>,
+}
+
+impl Citation {
+    /// BibTeX key made up of the alphanumeric characters of the title and the
+    /// year, if any.
+    fn key(&self) -> String {
+        let slug = self
+            .title
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_lowercase();
+        match self.year {
+            Some(year) => format!("{slug}{year}"),
+            None => slug,
+        }
+    }
+
+    fn to_bibtex(&self) -> String {
+        let mut fields = vec![("title", _escape_bibtex(&self.title))];
+        if !self.authors.is_empty() {
+            fields.push(("author", _escape_bibtex(&self.authors.join(" and "))));
+        }
+        if let Some(book_title) = &self.book_title {
+            fields.push(("booktitle", _escape_bibtex(book_title)));
+        }
+        if let Some(year) = self.year {
+            fields.push(("year", year.to_string()));
+        }
+        if let Some(url) = &self.url {
+            fields.push(("url", url.clone()));
+        }
+
+        let body = fields
+            .iter()
+            .map(|(name, value)| format!("  {name} = {{{value}}}"))
+            .collect::<Vec<_>>()
+            .join(",\n");
+        let entry_type = if self.book_title.is_some() {
+            "incollection"
+        } else {
+            "misc"
+        };
+        format!("@{entry_type}{{{},\n{body}\n}}", self.key())
+    }
+
+    fn to_apa(&self) -> String {
+        let authors = match self.authors.as_slice() {
+            [] => None,
+            [author] => Some(author.clone()),
+            [init @ .., last] => Some(format!("{}, & {last}", init.join(", "))),
+        };
+        let year = self
+            .year
+            .map_or_else(|| "n.d.".to_string(), |year| year.to_string());
+        let mut apa = match authors {
+            Some(authors) => format!("{authors} ({year}). {}.", self.title),
+            None => format!("{} ({year}).", self.title),
+        };
+        if let Some(book_title) = &self.book_title {
+            apa.push_str(&format!(" In {book_title}."));
+        }
+        if let Some(url) = &self.url {
+            apa.push_str(&format!(" {url}"));
+        }
+        apa
+    }
+}
+
+/// `value` with the characters special to BibTeX escaped.
+fn _escape_bibtex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '{' | '}' | '%' | '&' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+    use std::path::PathBuf;
+
+    #[rstest]
+    fn test_citation_render() -> Result<()> {
+        let settings = AIPRCitationSettings::from_param_str("year=2025,apa=true");
+        let config = AIPRConfig {
+            site_url: Some("https://example.org/book/".to_string()),
+            ..Default::default()
+        };
+        let book = BookInfo {
+            title: Some("AI Pocket Reference".to_string()),
+            authors: vec!["Jane Doe".to_string(), "John Roe".to_string()],
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            name: "LoRA".to_string(),
+            path: Some(PathBuf::from("nlp/lora.md")),
+            ..Default::default()
+        };
+
+        let html_string = settings.render(&HelperContext {
+            config: &config,
+            book: &book,
+            chapter: &chapter,
+        })?;
+        let expected = "<details class=\"aipr-citation\">\n  \
+        <summary>Cite this chapter</summary>\n  \
+        <pre><code class=\"language-bibtex\">@incollection{lora2025,\n  \
+        title &#x3D; {LoRA},\n  author &#x3D; {Jane Doe and John Roe},\n  \
+        booktitle &#x3D; {AI Pocket Reference},\n  year &#x3D; {2025},\n  \
+        url &#x3D; {https://example.org/book/nlp/lora.html}\n}</code></pre>\n  \
+        <p>Jane Doe, &amp; John Roe (2025). LoRA. In AI Pocket Reference. \
+        https://example.org/book/nlp/lora.html</p>\n</details>\n";
+
+        assert_eq!(html_string, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_citation_bibtex_without_book() -> Result<()> {
+        let citation = Citation {
+            title: "Attention Is All You Need".to_string(),
+            book_title: None,
+            authors: vec![],
+            year: Some(2017),
+            url: None,
+        };
+
+        assert_eq!(
+            citation.to_bibtex(),
+            "@misc{attentionisallyouneed2017,\n  title = {Attention Is All You Need},\n  year = {2017}\n}"
+        );
+        assert_eq!(citation.to_apa(), "Attention Is All You Need (2017).");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_citation_bibtex_escaped_without_year() -> Result<()> {
+        let citation = Citation {
+            title: "Q&A at 100% with {sets} and C:\\paths".to_string(),
+            book_title: Some("R&D".to_string()),
+            authors: vec!["Smith & Wesson".to_string()],
+            year: None,
+            url: None,
+        };
+
+        assert_eq!(
+            citation.to_bibtex(),
+            "@incollection{qaat100withsetsandcpaths,\n  \
+            title = {Q\\&A at 100\\% with \\{sets\\} and C:\\textbackslash{}paths},\n  \
+            author = {Smith \\& Wesson},\n  booktitle = {R\\&D}\n}"
+        );
+        assert_eq!(
+            citation.to_apa(),
+            "Smith & Wesson (n.d.). Q&A at 100% with {sets} and C:\\paths. In R&D."
+        );
+
+        Ok(())
+    }
+}
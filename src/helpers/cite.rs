@@ -0,0 +1,116 @@
+//! The `{{#aipr_cite}}` helper numbering citations of the bibliography, and
+//! the references list of each chapter.
+
+use crate::helpers::params::{_parse_list, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde::Serialize;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRCiteSettings {
+    pub(crate) keys: Vec<String>,
+}
+
+impl HelperHandler for AIPRCiteSettings {
+    fn name() -> &'static str {
+        "aipr_cite"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            keys: _parse_list(required_params(Self::name(), params)?),
+        })
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        // create data for rendering handlebar
+        let formatter = ctx.config.citation_style.formatter();
+        let mut citations = vec![];
+        for key in &self.keys {
+            if ctx.book.bibliography.get(key).is_none() {
+                if ctx.config.strict {
+                    anyhow::bail!("Citation `{key}` is not in the bibliography");
+                }
+                log::warn!(
+                    "Citation `{key}` of `{}` is not in the bibliography.",
+                    ctx.chapter.name
+                );
+            }
+            let number = ctx.chapter.citations.iter().position(|k| k == key);
+            citations.push(CitationMarker {
+                id: reference_anchor(key),
+                label: number.map_or("?".to_string(), |index| {
+                    formatter.label(key, index + 1, ctx.book.bibliography.get(key))
+                }),
+            });
+        }
+        let mut data = Map::new();
+        data.insert("numeric".to_string(), to_json(formatter.numeric()));
+        data.insert("citations".to_string(), to_json(citations));
+
+        // render
+        let html_string = ctx.render("aipr_cite", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct CitationMarker {
+    id: String,
+    label: String,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct Reference {
+    id: String,
+    authors: Option<String>,
+    year: Option<String>,
+    title: String,
+    venue: Option<String>,
+    url: Option<String>,
+}
+
+/// HTML id of the reference with key `key`.
+fn reference_anchor(key: &str) -> String {
+    format!("ref-{key}")
+}
+
+/// The "References" list of the chapter's cited entries, in citation order
+/// or sorted as the citation style lists them.
+pub(crate) fn render_references(ctx: &HelperContext) -> anyhow::Result<String> {
+    // create data for rendering handlebar
+    let formatter = ctx.config.citation_style.formatter();
+    let mut keys = ctx.chapter.citations.iter().collect::<Vec<_>>();
+    keys.sort_by_cached_key(|key| formatter.sort_key(key, ctx.book.bibliography.get(key)));
+    let references = keys
+        .into_iter()
+        .map(|key| match ctx.book.bibliography.get(key) {
+            Some(entry) => Reference {
+                id: reference_anchor(key),
+                authors: formatter.authors(entry),
+                year: entry.field("year").map(str::to_string),
+                title: entry.field("title").unwrap_or(key).to_string(),
+                venue: entry.venue().map(str::to_string),
+                url: entry.url(),
+            },
+            None => Reference {
+                id: reference_anchor(key),
+                authors: None,
+                year: None,
+                title: key.clone(),
+                venue: None,
+                url: None,
+            },
+        })
+        .collect::<Vec<_>>();
+    let mut data = Map::new();
+    data.insert("numeric".to_string(), to_json(formatter.numeric()));
+    data.insert("references".to_string(), to_json(references));
+
+    // render
+    let html_string = ctx.render("aipr_references", &data)?;
+
+    Ok(html_string)
+}
@@ -0,0 +1,141 @@
+//! The `{{#aipr_code}}` helper embedding files of the companion code
+//! repository.
+
+use crate::code;
+use crate::helpers::params::{_parse_param_str, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRCodeSettings {
+    /// Path of the file within the code repository.
+    path: String,
+    /// First and last line to include, 1-based and inclusive.
+    lines: (Option<usize>, Option<usize>),
+    /// Language of the code block, overriding the one from the extension.
+    lang: Option<String>,
+}
+
+impl AIPRCodeSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        let (path, param_str) = param_str
+            .split_once(char::is_whitespace)
+            .unwrap_or((param_str, ""));
+        let param_map = _parse_param_str(param_str, &[]);
+        let lines = match param_map.get("lines") {
+            Some(lines) => {
+                let (start, end) = lines.split_once(':').unwrap_or((lines, lines));
+                (start.trim().parse().ok(), end.trim().parse().ok())
+            }
+            None => (None, None),
+        };
+
+        Self {
+            path: path.to_string(),
+            lines,
+            lang: param_map.get("lang").cloned(),
+        }
+    }
+}
+
+impl HelperHandler for AIPRCodeSettings {
+    fn name() -> &'static str {
+        "aipr_code"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(required_params(Self::name(), params)?))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let contents = code::read_code_file(ctx.config, &ctx.book.root, &self.path)?;
+        let code = code::select_lines(&contents, self.lines.0, self.lines.1)?;
+
+        // the fence must be longer than any run of backticks in the code
+        let longest_run = code
+            .split(|c| c != '`')
+            .map(str::len)
+            .max()
+            .unwrap_or_default();
+        let fence = "`".repeat(longest_run.max(2) + 1);
+
+        // create data for rendering handlebar
+        let mut data = Map::new();
+        data.insert("fence".to_string(), to_json(fence));
+        data.insert(
+            "lang".to_string(),
+            to_json(
+                self.lang
+                    .as_deref()
+                    .unwrap_or_else(|| code::language(&self.path)),
+            ),
+        );
+        data.insert("code".to_string(), to_json(code));
+
+        // render
+        let html_string = ctx.render("aipr_code", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+    use std::path::PathBuf;
+
+    #[rstest]
+    #[case("nlp/lora/train.py lines=10:42", "nlp/lora/train.py", (Some(10), Some(42)), None)]
+    #[case("train.py lines=3", "train.py", (Some(3), Some(3)), None)]
+    #[case("train.py lines=3: lang=text", "train.py", (Some(3), None), Some("text"))]
+    #[case("train.py", "train.py", (None, None), None)]
+    fn test_code_settings_from_param_str(
+        #[case] param_str: &str,
+        #[case] path: &str,
+        #[case] lines: (Option<usize>, Option<usize>),
+        #[case] lang: Option<&str>,
+    ) -> Result<()> {
+        let settings = AIPRCodeSettings::from_param_str(param_str);
+        assert_eq!(
+            settings,
+            AIPRCodeSettings {
+                path: path.to_string(),
+                lines,
+                lang: lang.map(str::to_string),
+            }
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_code_render() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aipr-code-render-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("code"))?;
+        std::fs::write(root.join("code/doc.py"), "x = 1\n\"\"\"```\"\"\"\ny = 2\n")?;
+        let config = AIPRConfig {
+            code_repo_path: Some(PathBuf::from("code")),
+            ..Default::default()
+        };
+        let book_info = BookInfo {
+            root: root.clone(),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &book_info,
+            chapter: &ChapterInfo::default(),
+        };
+
+        let html_string = AIPRCodeSettings::from_param_str("doc.py lines=2:3").render(&ctx)?;
+        assert_eq!(html_string, "````python\n\"\"\"```\"\"\"\ny = 2\n````\n");
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
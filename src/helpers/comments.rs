@@ -0,0 +1,148 @@
+//! The `{{#aipr_comments}}` helper embedding giscus or utterances comments.
+
+use crate::config::CommentsProvider;
+use crate::helpers::params::no_params;
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde_json::value::Map;
+
+/// An `{{#aipr_comments}}` helper, which takes no params.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct AIPRCommentsSettings;
+
+impl HelperHandler for AIPRCommentsSettings {
+    fn name() -> &'static str {
+        "aipr_comments"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        no_params(Self::name(), params)?;
+        Ok(Self)
+    }
+
+    /// Embed of the giscus discussion or utterances issue of the chapter, from
+    /// the `comments` config.
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let comments = &ctx.config.comments;
+        let missing = match comments.provider {
+            CommentsProvider::Giscus => [
+                ("repo", &comments.repo),
+                ("repo_id", &comments.repo_id),
+                ("category_id", &comments.category_id),
+            ]
+            .into_iter()
+            .filter(|(_, value)| value.is_none())
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>(),
+            CommentsProvider::Utterances => match comments.repo {
+                Some(_) => vec![],
+                None => vec!["repo"],
+            },
+        };
+        if !missing.is_empty() {
+            let missing = missing.join("`, `");
+            anyhow::ensure!(
+                !ctx.config.strict,
+                "`aipr_comments` requires `{missing}` under `comments`"
+            );
+            log::warn!(
+                "`aipr_comments` of `{}` requires `{missing}` under `comments`, skipping.",
+                ctx.chapter.name
+            );
+            return Ok(String::new());
+        }
+
+        // create data for rendering handlebar
+        let mut data = Map::new();
+        data.insert(
+            "giscus".to_string(),
+            to_json(comments.provider == CommentsProvider::Giscus),
+        );
+        data.insert("repo".to_string(), to_json(&comments.repo));
+        data.insert("repo_id".to_string(), to_json(&comments.repo_id));
+        data.insert("category".to_string(), to_json(&comments.category));
+        data.insert("category_id".to_string(), to_json(&comments.category_id));
+        data.insert("mapping".to_string(), to_json(&comments.mapping));
+        data.insert("label".to_string(), to_json(&comments.label));
+        data.insert("theme".to_string(), to_json(comments.theme()));
+        data.insert(
+            "lang".to_string(),
+            to_json(ctx.book.language.as_deref().unwrap_or("en")),
+        );
+
+        // render
+        let html_string = ctx.render("aipr_comments", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config;
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_render_comments(#[case] strict: bool) -> Result<()> {
+        let book = BookInfo {
+            language: Some("fr".to_string()),
+            ..Default::default()
+        };
+        let chapter = ChapterInfo::default();
+        let render = |comments: config::CommentsConfig| -> Result<String> {
+            let config = AIPRConfig {
+                strict,
+                comments,
+                ..Default::default()
+            };
+            let ctx = HelperContext {
+                config: &config,
+                book: &book,
+                chapter: &chapter,
+            };
+            AIPRCommentsSettings.render(&ctx)
+        };
+
+        let html_string = render(config::CommentsConfig {
+            repo: Some("VectorInstitute/ai-pocket-reference".to_string()),
+            repo_id: Some("R_1".to_string()),
+            category_id: Some("DIC_1".to_string()),
+            ..Default::default()
+        })?;
+        assert!(html_string.contains("src=\"https://giscus.app/client.js\""));
+        assert!(html_string.contains("data-repo=\"VectorInstitute/ai-pocket-reference\""));
+        assert!(html_string.contains("data-category=\"General\""));
+        assert!(html_string.contains("data-theme=\"preferred_color_scheme\""));
+        assert!(html_string.contains("data-lang=\"fr\""));
+
+        let html_string = render(config::CommentsConfig {
+            provider: CommentsProvider::Utterances,
+            repo: Some("VectorInstitute/ai-pocket-reference".to_string()),
+            label: Some("comments".to_string()),
+            theme: Some("github-dark".to_string()),
+            ..Default::default()
+        })?;
+        assert!(html_string.contains("src=\"https://utteranc.es/client.js\""));
+        assert!(html_string.contains("issue-term=\"pathname\""));
+        assert!(html_string.contains("label=\"comments\""));
+        assert!(html_string.contains("theme=\"github-dark\""));
+
+        // giscus needs the ids of the repo and category
+        let comments = config::CommentsConfig {
+            repo: Some("VectorInstitute/ai-pocket-reference".to_string()),
+            ..Default::default()
+        };
+        match strict {
+            true => assert!(render(comments).is_err()),
+            false => assert_eq!(render(comments)?, ""),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+//! The `{{#aipr_contributors}}` helper listing chapter contributors from git
+//! history.
+
+use crate::forge::Forge;
+use crate::git;
+use crate::helpers::params::_parse_param_str;
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde::Serialize;
+use serde_json::value::Map;
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct AIPRContributorsSettings {
+    max: Option<usize>,
+}
+
+impl AIPRContributorsSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, &[]);
+        let max = param_map.get("max").and_then(|s| s.parse().ok());
+
+        Self { max }
+    }
+}
+
+impl HelperHandler for AIPRContributorsSettings {
+    fn name() -> &'static str {
+        "aipr_contributors"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(params))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let Some(contributors) = ctx
+            .chapter_source_file()
+            .and_then(|f| git::contributors(&f))
+        else {
+            log::warn!(
+                "No git history for chapter `{}`, skipping contributors.",
+                ctx.chapter.name
+            );
+            return Ok(String::new());
+        };
+
+        // create data for rendering handlebar
+        let exclude = &ctx.config.contributors_exclude;
+        let contributors = contributors
+            .iter()
+            .filter(|c| {
+                !exclude.iter().any(|excluded| {
+                    excluded == &c.name
+                        || excluded.eq_ignore_ascii_case(&c.email)
+                        || Some(excluded.as_str()) == c.username(&ctx.config.forge)
+                })
+            })
+            .take(self.max.unwrap_or(ctx.config.contributors_max))
+            .map(|c| ContributorAvatar::new(c, &ctx.config.forge))
+            .collect::<Vec<_>>();
+        let mut data = Map::new();
+        data.insert("contributors".to_string(), to_json(contributors));
+
+        // render
+        let html_string = ctx.render("aipr_contributors", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct ContributorAvatar {
+    name: String,
+    url: Option<String>,
+    avatar_url: String,
+}
+
+impl ContributorAvatar {
+    /// Links users of `forge` to their profile; others get a Gravatar, if any.
+    fn new(contributor: &git::Contributor, forge: &Forge) -> Self {
+        let username = contributor.username(forge);
+        match username.and_then(|username| forge.avatar_url(username)) {
+            Some(avatar_url) => Self {
+                name: contributor.name.clone(),
+                url: username.map(|username| forge.user_url(username)),
+                avatar_url,
+            },
+            None => {
+                let email = contributor.email.trim().to_lowercase();
+                let hash = Sha256::digest(email.as_bytes())
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect::<String>();
+                Self {
+                    name: contributor.name.clone(),
+                    url: None,
+                    avatar_url: format!("https://gravatar.com/avatar/{hash}?d=identicon"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+    use std::path::PathBuf;
+
+    #[rstest]
+    fn test_contributor_avatar() -> Result<()> {
+        let github_user = git::Contributor {
+            name: "The Octocat".to_string(),
+            email: "1+octocat@users.noreply.github.com".to_string(),
+            commits: 3,
+        };
+        let other_user = git::Contributor {
+            name: "Jane Doe".to_string(),
+            email: " Jane@Example.com".to_string(),
+            commits: 1,
+        };
+
+        assert_eq!(
+            ContributorAvatar::new(&github_user, &Forge::default()),
+            ContributorAvatar {
+                name: "The Octocat".to_string(),
+                url: Some("https://github.com/octocat".to_string()),
+                avatar_url: "https://github.com/octocat.png".to_string(),
+            }
+        );
+        assert_eq!(
+            ContributorAvatar::new(&other_user, &Forge::default()).avatar_url,
+            "https://gravatar.com/avatar/\
+            8c87b489ce35cf2e2f39f80e282cb2e804932a56a213983eeeb428407d43b52d?d=identicon"
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_contributors_render() -> Result<()> {
+        let src_dir =
+            std::env::temp_dir().join(format!("aipr-contributors-{}", std::process::id()));
+        git::init_test_repo(&src_dir)?;
+        let book = BookInfo {
+            src_dir: src_dir.clone(),
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            source_path: Some(PathBuf::from("nlp/lora.md")),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book,
+            chapter: &chapter,
+        };
+        let settings = AIPRContributorsSettings::from_param_str("max=2");
+
+        let html_string = settings.render(&ctx)?;
+
+        assert_eq!(html_string, concat!(
+                "<div class=\"aipr-contributors\">\n",
+                "  <span class=\"aipr-contributor\" title=\"Jane Doe\">\n",
+                "    <img src=\"https://gravatar.com/avatar/8c87b489ce35cf2e2f39f80e282cb2e804932a56a213983eeeb428407d43b52d?d&#x3D;identicon\" alt=\"Jane Doe\" width=\"24\" height=\"24\"/>\n",
+                "  </span>\n",
+                "  <a class=\"aipr-contributor\" target=\"_blank\" href=\"https://github.com/octocat\" title=\"The Octocat\">\n",
+                "    <img src=\"https://github.com/octocat.png\" alt=\"The Octocat\" width=\"24\" height=\"24\"/>\n",
+                "  </a>\n",
+                "</div>\n"
+            ));
+
+        std::fs::remove_dir_all(&src_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+//! The `{{#aipr_doi}}` helper citing works by DOI.
+
+use crate::doi::{self, Work};
+use crate::fetch::fetch_cached;
+use crate::helpers::params::required_params;
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRDoiSettings {
+    doi: String,
+}
+
+impl HelperHandler for AIPRDoiSettings {
+    fn name() -> &'static str {
+        "aipr_doi"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            doi: required_params(Self::name(), params)?.to_string(),
+        })
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let doi = self.doi.trim_start_matches("https://doi.org/");
+        anyhow::ensure!(doi::is_doi(doi), "Invalid DOI `{doi}`");
+
+        // create data for rendering handlebar
+        let mut data = Map::new();
+        data.insert("url".to_string(), to_json(doi::doi_url(doi)));
+        let work = fetch_cached(
+            ctx.config,
+            &ctx.book.root,
+            &doi::api_url(&ctx.config.crossref_api, doi),
+            &doi::cache_key(doi),
+        )
+        .and_then(|json| Work::from_csl_json(&json));
+        match work {
+            Ok(work) => {
+                data.insert("work".to_string(), to_json(work));
+            }
+            // fall back to the bare link, e.g. when building offline
+            Err(e) => log::warn!("No metadata for DOI {doi}, rendering a link instead: {e:#}"),
+        }
+
+        // render
+        let html_string = ctx.render("aipr_doi", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_doi_render() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aipr-doi-{}", std::process::id()));
+        std::fs::create_dir_all(root.join(".aipr-cache/doi"))?;
+        std::fs::write(
+            root.join(".aipr-cache/doi/10.1038%2Fnature14539.json"),
+            r#"{"title": "Deep learning", "author": [{"family": "LeCun", "given": "Yann"}],
+            "issued": {"date-parts": [[2015]]}, "container-title": "Nature"}"#,
+        )?;
+        let config = AIPRConfig {
+            offline: true,
+            ..Default::default()
+        };
+        let book_info = BookInfo {
+            root: root.clone(),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &book_info,
+            chapter: &ChapterInfo::default(),
+        };
+
+        let html_string = AIPRDoiSettings {
+            doi: "10.1038/nature14539".to_string(),
+        }
+        .render(&ctx)?;
+        assert_eq!(
+            html_string,
+            "<span class=\"aipr-doi\">LeCun, Y. (2015). <em>Deep learning</em>. Nature. \
+            <a href=\"https://doi.org/10.1038/nature14539\" target=\"_blank\" \
+            rel=\"noopener noreferrer\">https://doi.org/10.1038/nature14539</a></span>"
+        );
+
+        // uncached works fall back to a link when offline
+        let html_string = AIPRDoiSettings {
+            doi: "https://doi.org/10.1145/3442188.3445922".to_string(),
+        }
+        .render(&ctx)?;
+        assert!(html_string.starts_with(
+            "<span class=\"aipr-doi\"><a href=\"https://doi.org/10.1145/3442188.3445922\""
+        ));
+        assert!(AIPRDoiSettings {
+            doi: "nature14539".to_string()
+        }
+        .render(&ctx)
+        .is_err());
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_doi_render_unreachable() -> Result<()> {
+        // an API accepting connections but never responding
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let config = AIPRConfig {
+            crossref_api: format!("http://{}", listener.local_addr()?),
+            fetch_timeout: 1,
+            cache_dir: std::env::temp_dir()
+                .join(format!("aipr-doi-timeout-{}", std::process::id())),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+
+        let start = std::time::Instant::now();
+        let html_string = AIPRDoiSettings {
+            doi: "10.1038/nature14539".to_string(),
+        }
+        .render(&ctx)?;
+        assert!(html_string.starts_with(
+            "<span class=\"aipr-doi\"><a href=\"https://doi.org/10.1038/nature14539\""
+        ));
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+        Ok(())
+    }
+}
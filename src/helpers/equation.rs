@@ -0,0 +1,97 @@
+//! The `{{#aipr_eq}}` block helper numbering display math, and
+//! `{{#aipr_eqref}}` referencing equations.
+
+use crate::helpers::params::{_parse_param_str, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct AIPREquationSettings {
+    pub(crate) label: Option<String>,
+    pub(crate) body: String,
+}
+
+impl AIPREquationSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, &[]);
+        Self {
+            label: param_map.get("label").cloned(),
+            body: String::new(),
+        }
+    }
+}
+
+impl HelperHandler for AIPREquationSettings {
+    fn name() -> &'static str {
+        "aipr_eq"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(params))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let counter = &ctx.chapter.equation_counter;
+        counter.set(counter.get() + 1);
+
+        // create data for rendering handlebar, the math is left as is
+        let mut data = Map::new();
+        data.insert("number".to_string(), to_json(counter.get()));
+        if let Some(label) = &self.label {
+            data.insert("id".to_string(), to_json(equation_anchor(label)));
+        }
+        data.insert("body".to_string(), to_json(self.body.trim()));
+
+        // render
+        let html_string = ctx.render("aipr_eq", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+/// HTML id of the equation labelled `label`.
+fn equation_anchor(label: &str) -> String {
+    format!("eq-{label}")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPREqRefSettings {
+    label: String,
+}
+
+impl HelperHandler for AIPREqRefSettings {
+    fn name() -> &'static str {
+        "aipr_eqref"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            label: required_params(Self::name(), params)?.to_string(),
+        })
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let Some(number) = ctx.chapter.equations.get(&self.label) else {
+            if ctx.config.strict {
+                anyhow::bail!("Equation `{}` is not in the chapter", self.label);
+            }
+            log::warn!(
+                "Equation `{}` referenced by `{}` is not in the chapter.",
+                self.label,
+                ctx.chapter.name
+            );
+            return Ok(self.label.clone());
+        };
+
+        // create data for rendering handlebar
+        let mut data = Map::new();
+        data.insert("id".to_string(), to_json(equation_anchor(&self.label)));
+        data.insert("number".to_string(), to_json(number));
+
+        // render
+        let html_string = ctx.render("aipr_eqref", &data)?;
+
+        Ok(html_string)
+    }
+}
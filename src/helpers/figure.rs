@@ -0,0 +1,118 @@
+//! The `{{#aipr_figure}}` helper numbering figures, and `{{#aipr_ref}}`
+//! referencing them.
+
+use crate::helpers::params::{_parse_param_str, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde_json::value::Map;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRFigureSettings {
+    /// Id that `aipr_ref` refers to, `fig:<file-stem>` unless given.
+    pub(crate) id: String,
+    src: String,
+    caption: String,
+    alt: Option<String>,
+}
+
+impl AIPRFigureSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, &[]);
+        let src = param_map.get("src").cloned().unwrap_or_default();
+        let id = param_map.get("id").cloned().unwrap_or_else(|| {
+            let stem = Path::new(&src).file_stem().unwrap_or_default();
+            format!("fig:{}", stem.to_string_lossy())
+        });
+        let caption = param_map.get("caption").cloned().unwrap_or_default();
+        let alt = param_map.get("alt").cloned();
+
+        Self {
+            id,
+            src,
+            caption,
+            alt,
+        }
+    }
+}
+
+impl HelperHandler for AIPRFigureSettings {
+    fn name() -> &'static str {
+        "aipr_figure"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(required_params(Self::name(), params)?))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        if self.src.is_empty() {
+            anyhow::bail!("`aipr_figure` requires a `src`");
+        }
+
+        // create data for rendering handlebar
+        let mut data = Map::new();
+        data.insert("id".to_string(), to_json(figure_anchor(&self.id)));
+        data.insert("src".to_string(), to_json(&self.src));
+        data.insert("caption".to_string(), to_json(&self.caption));
+        data.insert(
+            "alt".to_string(),
+            to_json(self.alt.as_ref().unwrap_or(&self.caption)),
+        );
+        data.insert(
+            "number".to_string(),
+            to_json(ctx.chapter.figures.get(&self.id)),
+        );
+
+        // render
+        let html_string = ctx.render("aipr_figure", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+/// HTML id of the figure with id `id`, e.g. `fig-lora` for `fig:lora`.
+fn figure_anchor(id: &str) -> String {
+    id.replace(':', "-")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRRefSettings {
+    pub(crate) id: String,
+}
+
+impl HelperHandler for AIPRRefSettings {
+    fn name() -> &'static str {
+        "aipr_ref"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: required_params(Self::name(), params)?.to_string(),
+        })
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let Some(number) = ctx.chapter.figures.get(&self.id) else {
+            if ctx.config.strict {
+                anyhow::bail!("Figure `{}` is not in the chapter", self.id);
+            }
+            log::warn!(
+                "Figure `{}` referenced by `{}` is not in the chapter.",
+                self.id,
+                ctx.chapter.name
+            );
+            return Ok(self.id.clone());
+        };
+
+        // create data for rendering handlebar
+        let mut data = Map::new();
+        data.insert("id".to_string(), to_json(figure_anchor(&self.id)));
+        data.insert("number".to_string(), to_json(number));
+
+        // render
+        let html_string = ctx.render("aipr_ref", &data)?;
+
+        Ok(html_string)
+    }
+}
@@ -0,0 +1,493 @@
+//! The `{{#aipr_footer}}` helper composing chapter footers, and
+//! `{{#aipr_no_footer}}` leaving the default one out.
+
+use crate::ai_pocket_reference::AIPR_FEEDBACK_SCRIPT;
+use crate::config::AIPRConfig;
+use crate::generated;
+use crate::helpers::license::License;
+use crate::helpers::params::{_parse_list, _parse_param_str, no_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use anyhow::Context;
+use handlebars::to_json;
+use serde::{Deserialize, Serialize};
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRFooterSettings {
+    pub(crate) logo: bool,
+    pub(crate) links: Vec<String>,
+    /// Whether to show the book's configured `license`.
+    pub(crate) license: bool,
+    /// Whether to end with the configured `feedback` widget.
+    pub(crate) feedback: bool,
+}
+
+impl Default for AIPRFooterSettings {
+    fn default() -> Self {
+        Self {
+            logo: true,
+            links: vec![],
+            license: true,
+            feedback: true,
+        }
+    }
+}
+
+impl AIPRFooterSettings {
+    /// Params that are `true` or `false`, and can be given as bare flags.
+    pub(crate) const BOOLEAN_PARAMS: &'static [&'static str] = &["feedback", "license", "logo"];
+
+    pub(crate) fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, Self::BOOLEAN_PARAMS);
+        let logo = !matches!(param_map.get("logo"), Some(bool_str) if (bool_str == "false"));
+        let links = param_map
+            .get("links")
+            .map(|s| _parse_list(s))
+            .unwrap_or_default();
+        let license = !matches!(param_map.get("license"), Some(bool_str) if (bool_str == "false"));
+        let feedback =
+            !matches!(param_map.get("feedback"), Some(bool_str) if (bool_str == "false"));
+
+        Self {
+            logo,
+            links,
+            license,
+            feedback,
+        }
+    }
+}
+
+impl HelperHandler for AIPRFooterSettings {
+    fn name() -> &'static str {
+        "aipr_footer"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(params))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        // create data for rendering handlebar
+        let links = self
+            .links
+            .iter()
+            .filter_map(|name| {
+                let link = FooterLink::from_name(name, ctx.config);
+                if link.is_none() {
+                    log::warn!("Unknown footer link `{name}`, skipping.");
+                }
+                link
+            })
+            .chain(ctx.config.footer.variables.links.iter().cloned())
+            .collect::<Vec<_>>();
+        let variables = &ctx.config.footer.variables;
+        let (logos, logo_link) = match &variables.logo {
+            Some(logo) => (
+                FooterLogo::themed(logo, variables.logo_dark.as_deref()),
+                variables.logo_link.as_deref(),
+            ),
+            None => (
+                FooterLogo::themed(VECTOR_LOGO, Some(VECTOR_LOGO_DARK)),
+                Some("https://vectorinstitute.ai/"),
+            ),
+        };
+        let mut data = Map::new();
+        data.insert("logo".to_string(), to_json(self.logo));
+        data.insert("logos".to_string(), to_json(logos));
+        data.insert("logo_link".to_string(), to_json(logo_link));
+        data.insert("tagline".to_string(), to_json(&variables.tagline));
+        data.insert("links".to_string(), to_json(links));
+        if let Some(id) = ctx.config.license.as_ref().filter(|_| self.license) {
+            data.insert("license".to_string(), to_json(License::from_id(id)));
+        }
+
+        // render
+        let mut html_string = match &ctx.book.footer_template {
+            Some(template) => ctx
+                .book
+                .templates()
+                .render_template(template, &data)
+                .context("Failed to render footer template")?,
+            None => ctx.render("aipr_footer", &data)?,
+        };
+        if self.feedback && ctx.config.feedback.enabled {
+            html_string.push_str(&render_feedback(ctx)?);
+        }
+
+        Ok(html_string)
+    }
+}
+
+/// "Was this page helpful?" widget of the chapter, posting votes to the
+/// configured `endpoint` or opening a prefilled issue. Drafts have none.
+fn render_feedback(ctx: &HelperContext) -> anyhow::Result<String> {
+    let Some(path) = &ctx.chapter.path else {
+        return Ok(String::new());
+    };
+    let path = generated::markdown_path(path);
+    let feedback = &ctx.config.feedback;
+
+    // create data for rendering handlebar
+    let issue_url = |verdict: &str| {
+        ctx.config.forge.prefilled_issue_url(
+            &ctx.config.issue_repo,
+            &format!("Feedback on {path}: {verdict}"),
+            &format!("Page: {path}\n\n<!-- What could be improved? -->\n"),
+        )
+    };
+    let mut data = Map::new();
+    data.insert("path".to_string(), to_json(&path));
+    data.insert("endpoint".to_string(), to_json(&feedback.endpoint));
+    if feedback.endpoint.is_none() {
+        data.insert("helpful_url".to_string(), to_json(issue_url("helpful")));
+        data.insert(
+            "unhelpful_url".to_string(),
+            to_json(issue_url("not helpful")),
+        );
+    }
+
+    // render
+    let mut html_string = ctx.render("aipr_feedback", &data)?;
+    if feedback.endpoint.is_some() {
+        html_string.push_str(&format!("<script>\n{AIPR_FEEDBACK_SCRIPT}</script>\n"));
+    }
+
+    Ok(html_string)
+}
+
+const VECTOR_LOGO: &str =
+    "https://d3ddy8balm3goa.cloudfront.net/vector-ai-pocket-refs/vector-logo-default.png";
+
+const VECTOR_LOGO_DARK: &str =
+    "https://d3ddy8balm3goa.cloudfront.net/vector-ai-pocket-refs/vector-logo-dark.png";
+
+/// Logo image of a footer, with the `aipr-logo` classes showing it in light or
+/// dark themes.
+#[derive(PartialEq, Debug, Clone, Serialize)]
+struct FooterLogo {
+    src: String,
+    class: &'static str,
+}
+
+impl FooterLogo {
+    /// Logos of a footer, swapped with mdBook's theme if a `dark` one is given.
+    fn themed(light: &str, dark: Option<&str>) -> Vec<Self> {
+        let Some(dark) = dark else {
+            return vec![Self {
+                src: light.to_string(),
+                class: "aipr-logo",
+            }];
+        };
+        vec![
+            Self {
+                src: light.to_string(),
+                class: "aipr-logo light-logo",
+            },
+            Self {
+                src: dark.to_string(),
+                class: "aipr-logo dark-logo",
+            },
+        ]
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct FooterLink {
+    label: String,
+    url: String,
+}
+
+impl FooterLink {
+    /// The link named `name` by the `links` param of `aipr_footer`: one of the
+    /// configured `named_links` of footers, or the built-in `github` and
+    /// `issues` of `issue_repo` on the `forge`, and `website`.
+    pub(crate) fn from_name(name: &str, config: &AIPRConfig) -> Option<Self> {
+        if let Some(link) = config.footer.variables.named_links.get(name) {
+            return Some(link.clone());
+        }
+        let (label, url) = match name {
+            "github" => (
+                config.forge.name(),
+                config.forge.repo_url(&config.issue_repo),
+            ),
+            "issues" => ("Issues", config.forge.issues_url(&config.issue_repo)),
+            "website" => ("Vector Institute", "https://vectorinstitute.ai".to_string()),
+            _ => return None,
+        };
+        Some(Self {
+            label: label.to_string(),
+            url,
+        })
+    }
+}
+
+/// An `{{#aipr_no_footer}}` helper, leaving the default footer out of the
+/// chapter. It takes no params.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct AIPRNoFooterSettings;
+
+impl HelperHandler for AIPRNoFooterSettings {
+    fn name() -> &'static str {
+        "aipr_no_footer"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        no_params(Self::name(), params)?;
+        Ok(Self)
+    }
+
+    fn render(&self, _: &HelperContext) -> anyhow::Result<String> {
+        Ok(String::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config;
+    use anyhow::Result;
+    use rstest::*;
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    #[rstest]
+    #[case(
+        "logo=false,links=github,website",
+        AIPRFooterSettings {
+            logo: false,
+            links: vec!["github".to_string(), "website".to_string()],
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "links=github, issues,logo=true",
+        AIPRFooterSettings {
+            logo: true,
+            links: vec!["github".to_string(), "issues".to_string()],
+            ..Default::default()
+        }
+    )]
+    #[case("logo=nope", AIPRFooterSettings::default())]
+    #[case(
+        "license=false",
+        AIPRFooterSettings {
+            license: false,
+            ..Default::default()
+        }
+    )]
+    fn test_aipr_footer_settings(
+        #[case] param_str: &str,
+        #[case] expected_setting: AIPRFooterSettings,
+    ) -> Result<()> {
+        let setting = AIPRFooterSettings::from_param_str(param_str);
+        assert_eq!(setting, expected_setting);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_footer_render_default() -> Result<()> {
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let html_string = AIPRFooterSettings::default().render(&ctx)?;
+
+        assert!(html_string.starts_with("<div class=\"vector-logo\">"));
+        assert!(!html_string.contains("aipr-footer-links"));
+        assert!(!html_string.contains("aipr-license"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_footer_render_links_no_logo() -> Result<()> {
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let settings = AIPRFooterSettings::from_param_str("logo=false,links=github,unknown");
+
+        let html_string = settings.render(&ctx)?;
+        let expected = "<div class=\"aipr-footer-links\">\n    <a href=\"https://github.com/\
+        VectorInstitute/ai-pocket-reference\" target=\"_blank\" rel=\"noopener noreferrer\">\
+        GitHub</a>\n</div>\n";
+
+        assert_eq!(html_string, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_footer_render_variables() -> Result<()> {
+        let config = AIPRConfig {
+            footer: config::FooterConfig {
+                enabled: true,
+                variables: config::FooterVariables {
+                    logo: Some("https://fake.io/logo.png".to_string()),
+                    logo_dark: None,
+                    logo_link: None,
+                    tagline: Some("Pocket-sized ML".to_string()),
+                    links: vec![FooterLink {
+                        label: "Blog".to_string(),
+                        url: "https://fake.io/blog".to_string(),
+                    }],
+                    named_links: BTreeMap::from([(
+                        "discord".to_string(),
+                        FooterLink {
+                            label: "Discord".to_string(),
+                            url: "https://discord.gg/fake".to_string(),
+                        },
+                    )]),
+                },
+            },
+            forge: "gitlab".parse()?,
+            issue_repo: "fake/book".to_string(),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+
+        let html_string =
+            AIPRFooterSettings::from_param_str("links=github,issues,discord").render(&ctx)?;
+        let expected =
+            "<div class=\"vector-logo\">\n    <img src=\"https://fake.io/logo.png\" alt=\"\" \
+        class=\"aipr-logo\">\n\
+        </div>\n<p class=\"aipr-footer-tagline\">Pocket-sized ML</p>\n\
+        <div class=\"aipr-footer-links\">\n    <a href=\"https://gitlab.com/fake/book\" \
+        target=\"_blank\" rel=\"noopener noreferrer\">GitLab</a>\n    \
+        <a href=\"https://gitlab.com/fake/book/-/issues\" target=\"_blank\" \
+        rel=\"noopener noreferrer\">Issues</a>\n    <a href=\"https://discord.gg/fake\" \
+        target=\"_blank\" rel=\"noopener noreferrer\">Discord</a>\n    \
+        <a href=\"https://fake.io/blog\" target=\"_blank\" \
+        rel=\"noopener noreferrer\">Blog</a>\n</div>\n";
+        assert_eq!(html_string, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_footer_render_dark_logo() -> Result<()> {
+        let config = AIPRConfig {
+            footer: config::FooterConfig {
+                enabled: true,
+                variables: config::FooterVariables {
+                    logo: Some("https://fake.io/light.png".to_string()),
+                    logo_dark: Some("https://fake.io/dark.png".to_string()),
+                    logo_link: Some("https://fake.io".to_string()),
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+
+        let html_string = AIPRFooterSettings::default().render(&ctx)?;
+        let expected = "<div class=\"vector-logo\">\n    <a href=\"https://fake.io\">\n        \
+        <img src=\"https://fake.io/light.png\" alt=\"\" class=\"aipr-logo light-logo\">\n    \
+        </a>\n    <a href=\"https://fake.io\">\n        <img src=\"https://fake.io/dark.png\" \
+        alt=\"\" class=\"aipr-logo dark-logo\">\n    </a>\n</div>\n";
+        assert_eq!(html_string, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_footer_render_template() -> Result<()> {
+        let config = AIPRConfig {
+            license: Some("MIT".to_string()),
+            ..Default::default()
+        };
+        let book = BookInfo {
+            footer_template: Some(
+                "<footer>{{#each links}}{{label}} {{/each}}{{license.name}}</footer>".to_string(),
+            ),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &book,
+            chapter: &ChapterInfo::default(),
+        };
+
+        let html_string = AIPRFooterSettings::from_param_str("links=issues").render(&ctx)?;
+        assert_eq!(html_string, "<footer>Issues the MIT License</footer>");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_footer_render_feedback() -> Result<()> {
+        let chapter = ChapterInfo {
+            path: Some(PathBuf::from("nlp/lora.md")),
+            ..Default::default()
+        };
+        let render = |endpoint: Option<&str>, param_str: &str| -> Result<String> {
+            let config = AIPRConfig {
+                feedback: config::FeedbackConfig {
+                    enabled: true,
+                    endpoint: endpoint.map(str::to_string),
+                },
+                ..Default::default()
+            };
+            let ctx = HelperContext {
+                config: &config,
+                book: &BookInfo::default(),
+                chapter: &chapter,
+            };
+            AIPRFooterSettings::from_param_str(param_str).render(&ctx)
+        };
+
+        let html_string = render(None, "logo=false")?;
+        assert!(html_string.starts_with("<div class=\"aipr-feedback\" data-path=\"nlp/lora.md\">"));
+        assert!(html_string.contains(
+            "href=\"https://github.com/VectorInstitute/ai-pocket-reference/issues/new\
+            ?title&#x3D;Feedback%20on%20nlp%2Flora.md%3A%20helpful&amp;"
+        ));
+        assert!(!html_string.contains("<script>"));
+
+        let html_string = render(Some("https://fake.io/feedback"), "logo=false")?;
+        assert!(html_string.contains("data-endpoint=\"https://fake.io/feedback\""));
+        assert!(html_string.contains("<button type=\"button\" class=\"aipr-feedback-vote\""));
+        assert!(html_string.contains("<script>\n(function () {"));
+
+        assert_eq!(render(None, "logo=false,feedback=false")?, "");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_footer_render_license() -> Result<()> {
+        let config = AIPRConfig {
+            license: Some("CC-BY-4.0".to_string()),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+
+        let html_string = AIPRFooterSettings::default().render(&ctx)?;
+        assert!(html_string.contains("<div class=\"aipr-license\">"));
+        assert!(html_string.contains(
+            "<a href=\"https://creativecommons.org/licenses/by/4.0/\" target=\"_blank\" \
+            rel=\"license noopener noreferrer\">Creative Commons Attribution 4.0 International</a>"
+        ));
+
+        let html_string = AIPRFooterSettings::from_param_str("license=false").render(&ctx)?;
+        assert!(!html_string.contains("aipr-license"));
+
+        Ok(())
+    }
+}
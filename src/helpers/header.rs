@@ -0,0 +1,977 @@
+//! The `{{#aipr_header}}` helper: reading time, notebook badges, compute
+//! requirements and the other badges at the top of a chapter.
+
+use crate::ai_pocket_reference::WORDS_PER_MINUTE;
+use crate::config::{self, AIPRConfig, ReadingTimeFormat, Rounding};
+use crate::git;
+use crate::helpers::authors::GitHubAuthor;
+use crate::helpers::params::{_parse_list, _parse_param_str};
+use crate::helpers::{HelperContext, HelperHandler};
+use crate::word_count::WordCount;
+use handlebars::to_json;
+use serde::Serialize;
+use serde_json::value::Map;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Settings of an `{{#aipr_header}}` helper, parsed from its params.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AIPRHeaderSettings {
+    pub(crate) reading_time: bool,
+    pub(crate) submit_issue: bool,
+    pub(crate) issue_template: Option<String>,
+    /// Summary of the chapter for search engines, in its `description` meta
+    /// tag.
+    pub(crate) description: Option<String>,
+    /// Whether to link to the chapter's source file, `edit_link` if unset.
+    pub(crate) edit_link: Option<bool>,
+    /// GitHub usernames of the chapter's authors, linked in the header.
+    pub(crate) author: Vec<String>,
+    pub(crate) colab: Option<String>,
+    /// Whether to add a download link to the `colab` notebook.
+    pub(crate) download: bool,
+    pub(crate) kaggle: Option<String>,
+    pub(crate) binder: Option<String>,
+    pub(crate) sagemaker: Option<String>,
+    pub(crate) difficulty: Option<String>,
+    pub(crate) gpu: Option<String>,
+    pub(crate) vram: Option<String>,
+    pub(crate) runtime: Option<String>,
+    pub(crate) reading_time_rounding: Option<Rounding>,
+    pub(crate) reading_time_min: Option<usize>,
+    pub(crate) reading_time_display: Option<String>,
+    pub(crate) reading_time_format: Option<ReadingTimeFormat>,
+    /// Whether to show the chapter's word count after its reading time.
+    pub(crate) show_word_count: bool,
+    pub(crate) last_updated: bool,
+    /// Whether to show how long ago the chapter was last updated.
+    pub(crate) updated: bool,
+    pub(crate) abbreviations: bool,
+}
+
+impl Default for AIPRHeaderSettings {
+    fn default() -> Self {
+        Self {
+            reading_time: true,
+            submit_issue: true,
+            issue_template: None,
+            description: None,
+            edit_link: None,
+            author: vec![],
+            colab: None,
+            download: false,
+            kaggle: None,
+            binder: None,
+            sagemaker: None,
+            difficulty: None,
+            gpu: None,
+            vram: None,
+            runtime: None,
+            reading_time_rounding: None,
+            reading_time_min: None,
+            reading_time_display: None,
+            reading_time_format: None,
+            show_word_count: false,
+            last_updated: false,
+            updated: false,
+            abbreviations: true,
+        }
+    }
+}
+
+/// Notebooks of a `colab` value, e.g. `nlp/lora_train.ipynb;nlp/lora_eval.ipynb`.
+pub(crate) fn _split_notebooks(value: &str) -> Vec<&str> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .collect()
+}
+
+impl AIPRHeaderSettings {
+    /// Params that are `true` or `false`, and can be given as bare flags.
+    pub(crate) const BOOLEAN_PARAMS: &'static [&'static str] = &[
+        "abbreviations",
+        "download",
+        "edit_link",
+        "last_updated",
+        "reading_time",
+        "show_word_count",
+        "submit_issue",
+        "updated",
+    ];
+
+    /// Settings of a header with the params `param_str`, e.g.
+    /// `colab=nlp/lora.ipynb,difficulty=beginner`.
+    pub fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, Self::BOOLEAN_PARAMS);
+        let author = param_map
+            .get("author")
+            .map(|s| _parse_list(s))
+            .unwrap_or_default();
+        let colab = param_map.get("colab").map(|s| s.to_owned());
+        let download = matches!(param_map.get("download"), Some(bool_str) if (bool_str == "true"));
+        let kaggle = param_map.get("kaggle").map(|s| s.to_owned());
+        let binder = param_map.get("binder").map(|s| s.to_owned());
+        let sagemaker = param_map.get("sagemaker").map(|s| s.to_owned());
+        let difficulty = param_map.get("difficulty").map(|s| s.to_lowercase());
+        let gpu = param_map.get("gpu").map(|s| s.to_owned());
+        let vram = param_map.get("vram").map(|s| s.to_owned());
+        let runtime = param_map.get("runtime").map(|s| s.to_owned());
+        let reading_time =
+            !matches!(param_map.get("reading_time"), Some(bool_str) if (bool_str == "false"));
+        let submit_issue =
+            !matches!(param_map.get("submit_issue"), Some(bool_str) if (bool_str == "false"));
+        let issue_template = param_map.get("issue_template").map(|s| s.to_owned());
+        let description = param_map.get("description").map(|s| s.to_owned());
+        let edit_link = param_map.get("edit_link").map(|s| s == "true");
+        let reading_time_rounding = param_map
+            .get("reading_time_rounding")
+            .and_then(|s| s.parse().ok());
+        let reading_time_min = param_map
+            .get("reading_time_min")
+            .and_then(|s| s.parse().ok());
+        let reading_time_display = param_map.get("reading_time_display").map(|s| s.to_owned());
+        let reading_time_format = param_map
+            .get("reading_time_format")
+            .and_then(|s| s.parse().ok());
+        let show_word_count =
+            matches!(param_map.get("show_word_count"), Some(bool_str) if (bool_str == "true"));
+        let last_updated =
+            matches!(param_map.get("last_updated"), Some(bool_str) if (bool_str == "true"));
+        let updated = matches!(param_map.get("updated"), Some(bool_str) if (bool_str == "true"));
+        let abbreviations =
+            !matches!(param_map.get("abbreviations"), Some(bool_str) if (bool_str == "false"));
+
+        Self {
+            reading_time,
+            submit_issue,
+            issue_template,
+            description,
+            edit_link,
+            author,
+            colab,
+            download,
+            kaggle,
+            binder,
+            sagemaker,
+            difficulty,
+            gpu,
+            vram,
+            runtime,
+            reading_time_rounding,
+            reading_time_min,
+            reading_time_display,
+            reading_time_format,
+            show_word_count,
+            last_updated,
+            updated,
+            abbreviations,
+        }
+    }
+
+    /// Fill the values this helper doesn't set from the chapter's front
+    /// matter, so chapters can declare them there instead of in params.
+    pub(crate) fn with_front_matter(self, front_matter: &HashMap<String, String>) -> Self {
+        let get = |key: &str| front_matter.get(key).cloned();
+        Self {
+            colab: self.colab.or_else(|| get("colab")),
+            download: self.download || get("download").is_some_and(|s| s == "true"),
+            kaggle: self.kaggle.or_else(|| get("kaggle")),
+            binder: self.binder.or_else(|| get("binder")),
+            sagemaker: self.sagemaker.or_else(|| get("sagemaker")),
+            difficulty: self
+                .difficulty
+                .or_else(|| get("difficulty").map(|s| s.to_lowercase())),
+            gpu: self.gpu.or_else(|| get("gpu")),
+            vram: self.vram.or_else(|| get("vram")),
+            runtime: self.runtime.or_else(|| get("runtime")),
+            issue_template: self.issue_template.or_else(|| get("issue_template")),
+            description: self.description.or_else(|| get("description")),
+            ..self
+        }
+    }
+
+    /// Resolve `colab=auto`, the default with `colab_auto`, to the chapter's
+    /// notebook at `source_path`, and drop `colab=none`. Drafts have no
+    /// notebook of their own.
+    pub(crate) fn with_colab_auto(self, source_path: Option<&Path>, config: &AIPRConfig) -> Self {
+        let colab = match self.colab.as_deref() {
+            None if config.colab_auto => Some("auto"),
+            Some("none") => None,
+            colab => colab,
+        };
+        let colab = colab
+            .map(|colab| {
+                _split_notebooks(colab)
+                    .into_iter()
+                    .filter_map(|path| match path {
+                        "auto" => source_path.map(|source_path| config.auto_notebook(source_path)),
+                        path => Some(path.to_string()),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(";")
+            })
+            .filter(|colab| !colab.is_empty());
+        Self { colab, ..self }
+    }
+
+    /// Notebooks of the `colab` param, separated by `;`.
+    pub(crate) fn colab_notebooks(&self) -> Vec<&str> {
+        self.colab.as_deref().map_or(vec![], _split_notebooks)
+    }
+
+    /// Compute requirements of this helper.
+    fn requirements(&self) -> Vec<Requirement> {
+        [
+            ("GPU", &self.gpu),
+            ("VRAM", &self.vram),
+            ("Runtime", &self.runtime),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| {
+            Some(Requirement {
+                label: label.to_string(),
+                value: value.clone()?,
+            })
+        })
+        .collect()
+    }
+
+    /// Reading-time options of this helper, falling back to the book's.
+    fn reading_time_options(&self, config: &AIPRConfig) -> ReadingTimeOptions {
+        let default = ReadingTimeOptions::from_config(config);
+        ReadingTimeOptions {
+            rounding: self.reading_time_rounding.unwrap_or(default.rounding),
+            min: self.reading_time_min.unwrap_or(default.min),
+            display: self.reading_time_display.clone().unwrap_or(default.display),
+            format: self.reading_time_format.unwrap_or(default.format),
+            ..default
+        }
+    }
+}
+
+impl HelperHandler for AIPRHeaderSettings {
+    fn name() -> &'static str {
+        "aipr_header"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(params))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let config = ctx.config;
+
+        // create data for rendering handlebar
+        let mut data = Map::new();
+        if !self.author.is_empty() {
+            let authors = self
+                .author
+                .iter()
+                .map(|username| GitHubAuthor::new(username, &config.forge))
+                .collect::<Vec<_>>();
+            data.insert("authors".to_string(), to_json(authors));
+        }
+        let notebooks = self.colab_notebooks();
+        // badges are labeled by notebook when there are several
+        let label = |path: &str| {
+            (notebooks.len() > 1).then(|| {
+                Path::new(path)
+                    .file_stem()
+                    .map_or(path.into(), |stem| stem.to_string_lossy())
+                    .into_owned()
+            })
+        };
+        let mut colab_nbs = vec![];
+        for colab_path in &notebooks {
+            match config.colab_url(colab_path) {
+                Some(url) => colab_nbs.push(ColabNB {
+                    url,
+                    badge_url: config.colab_badge_url(ctx.translate("Open In Colab")),
+                    label: label(colab_path),
+                }),
+                None if config::is_notebook_url(colab_path) => log::warn!(
+                    "`{colab_path}` of `{}` isn't a Colab link or a notebook on github.com, \
+                    skipping its Colab badge.",
+                    ctx.chapter.name
+                ),
+                None => log::warn!(
+                    "Colab only opens notebooks hosted on github.com, skipping the Colab badge \
+                    of `{}`.",
+                    ctx.chapter.name
+                ),
+            }
+        }
+        data.insert("colab_nbs".to_string(), to_json(colab_nbs));
+        match (notebooks.is_empty(), self.download) {
+            (false, true) => {
+                // notebooks given by URL may not be files to download
+                let download_nbs = notebooks
+                    .iter()
+                    .filter(|colab_path| !config::is_notebook_url(colab_path))
+                    .map(|colab_path| DownloadNB {
+                        url: config.notebook_raw_url(colab_path),
+                        file_name: colab_path
+                            .rsplit('/')
+                            .next()
+                            .unwrap_or(colab_path)
+                            .to_string(),
+                        label: label(colab_path),
+                    })
+                    .collect::<Vec<_>>();
+                data.insert("download_nbs".to_string(), to_json(download_nbs));
+            }
+            (true, true) => log::warn!(
+                "`download=true` without `colab` in `{}`, skipping the download link.",
+                ctx.chapter.name
+            ),
+            _ => {}
+        }
+        if let Some(kaggle_path) = &self.kaggle {
+            let kaggle_nb = KaggleNB {
+                url: config.kaggle_url(kaggle_path),
+            };
+            data.insert("kaggle_nb".to_string(), to_json(kaggle_nb));
+        }
+        if let Some(binder_path) = &self.binder {
+            let binder_nb = BinderNB {
+                url: config.binder_url(binder_path),
+            };
+            data.insert("binder_nb".to_string(), to_json(binder_nb));
+        }
+        if let Some(sagemaker_path) = &self.sagemaker {
+            match config.sagemaker_url(sagemaker_path) {
+                Some(url) => {
+                    let sagemaker_nb = SageMakerNB { url };
+                    data.insert("sagemaker_nb".to_string(), to_json(sagemaker_nb));
+                }
+                None => log::warn!(
+                    "SageMaker Studio Lab only imports notebooks hosted on github.com, skipping \
+                    the SageMaker badge of `{}`.",
+                    ctx.chapter.name
+                ),
+            }
+        }
+        if self.submit_issue {
+            let submit_issue = SubmitIssue {
+                url: config.issue_url(self.issue_template.as_deref()),
+                badge_url: config.edit_badge_url(ctx.translate("Suggest an Edit")),
+            };
+            data.insert("submit_issue".to_string(), to_json(submit_issue));
+        }
+        if let Some(source_path) = ctx
+            .chapter
+            .source_path
+            .as_ref()
+            .filter(|_| self.edit_link.unwrap_or(config.edit_link))
+        {
+            let edit_page = EditPage {
+                url: config.edit_url(source_path),
+                badge_url: config.edit_page_badge_url(ctx.translate("Edit this page")),
+            };
+            data.insert("edit_page".to_string(), to_json(edit_page));
+        }
+        if let Some(level) = &self.difficulty {
+            match config.difficulty_color(level) {
+                Some(color) => {
+                    let difficulty = Difficulty::new(ctx.translate("Difficulty"), level, color);
+                    data.insert("difficulty".to_string(), to_json(difficulty));
+                }
+                None => log::warn!(
+                    "Unknown difficulty `{level}` in `{}`, skipping its badge.",
+                    ctx.chapter.name
+                ),
+            }
+        }
+        let requirements = self.requirements();
+        if !requirements.is_empty() {
+            data.insert("requirements".to_string(), to_json(requirements));
+        }
+        if self.reading_time {
+            let options = self.reading_time_options(config);
+            let rt = ReadingTime {
+                value: options.format(&ctx.chapter.word_count),
+                word_count: self
+                    .show_word_count
+                    .then(|| thousands(ctx.chapter.num_words)),
+            };
+            data.insert("reading_time".to_string(), to_json(rt));
+        }
+        if self.last_updated {
+            if let Some(last_updated) = ctx.last_updated() {
+                data.insert("last_updated".to_string(), to_json(last_updated));
+            }
+        }
+        if self.updated {
+            if let Some(date) = ctx
+                .chapter_source_file()
+                .and_then(|f| git::last_modified(&f))
+            {
+                let today = chrono::Local::now().date_naive();
+                data.insert("updated".to_string(), to_json(updated(ctx, date, today)));
+            }
+        }
+
+        // render
+        let mut html_string = ctx.render("aipr_header", &data)?;
+
+        // warn readers of chapters left untouched for `stale_after_days`
+        let last_modified = config
+            .stale_after_days
+            .and_then(|_| git::last_modified(&ctx.chapter_source_file()?));
+        if let Some(last_modified) = last_modified {
+            let today = chrono::Local::now().date_naive();
+            html_string.push_str(&render_stale_notice(ctx, last_modified, today)?);
+        }
+
+        Ok(html_string)
+    }
+}
+
+/// "This chapter may be outdated" notice of a chapter last modified on
+/// `last_modified`, if that's over `stale_after_days` before `today`.
+fn render_stale_notice(
+    ctx: &HelperContext,
+    last_modified: chrono::NaiveDate,
+    today: chrono::NaiveDate,
+) -> anyhow::Result<String> {
+    let Some(days) = ctx.config.stale_after_days else {
+        return Ok(String::new());
+    };
+    if (today - last_modified).num_days() <= days {
+        return Ok(String::new());
+    }
+
+    // create data for rendering handlebar
+    let mut data = Map::new();
+    data.insert(
+        "date".to_string(),
+        to_json(last_modified.format("%Y-%m-%d").to_string()),
+    );
+    data.insert("days".to_string(), to_json(days));
+
+    // render
+    let html_string = ctx.render("aipr_stale_notice", &data)?;
+
+    Ok(html_string)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ReadingTimeOptions {
+    rounding: Rounding,
+    min: usize,
+    display: String,
+    format: ReadingTimeFormat,
+    slow_wpm: usize,
+    fast_wpm: usize,
+    count_code: bool,
+    code_line_words: f32,
+    equation_words: f32,
+    cjk_chars_per_minute: usize,
+}
+
+impl ReadingTimeOptions {
+    /// The book's reading-time options.
+    pub(crate) fn from_config(config: &AIPRConfig) -> Self {
+        Self {
+            rounding: config.reading_time_rounding,
+            min: config.reading_time_min,
+            display: config.reading_time_display.clone(),
+            format: config.reading_time_format,
+            slow_wpm: config.reading_time_slow_wpm,
+            fast_wpm: config.reading_time_fast_wpm,
+            count_code: config.count_code,
+            code_line_words: config.reading_time_code_line_words,
+            equation_words: config.reading_time_equation_words,
+            cjk_chars_per_minute: config.cjk_chars_per_minute,
+        }
+    }
+
+    /// Words the reading time of a chapter made of `counts` is based on, with
+    /// code lines and equations weighted as words. Characters of mostly CJK
+    /// chapters weigh as the words read in the time they're read in.
+    fn weighted_words(&self, counts: &WordCount) -> f32 {
+        let cjk_char_words = if counts.is_cjk() {
+            WORDS_PER_MINUTE as f32 / self.cjk_chars_per_minute.max(1) as f32
+        } else {
+            1.0
+        };
+        (counts.words(self.count_code) - counts.cjk_chars) as f32
+            + counts.cjk_chars as f32 * cjk_char_words
+            + counts.code_lines as f32 * self.code_line_words
+            + counts.equations as f32 * self.equation_words
+    }
+
+    pub(crate) fn minutes(&self, counts: &WordCount) -> usize {
+        self.minutes_at(counts, WORDS_PER_MINUTE)
+    }
+
+    fn minutes_at(&self, counts: &WordCount, words_per_minute: usize) -> usize {
+        let mins = self
+            .rounding
+            .apply(self.weighted_words(counts) / words_per_minute.max(1) as f32)
+            as usize;
+        mins.max(self.min)
+    }
+
+    pub(crate) fn format(&self, counts: &WordCount) -> String {
+        self.format_total(&[counts])
+    }
+
+    /// Reading time of chapters made of `counts`, summing the reading time
+    /// of each chapter.
+    pub(crate) fn format_total(&self, counts: &[&WordCount]) -> String {
+        let total = |words_per_minute: usize| -> usize {
+            counts
+                .iter()
+                .map(|counts| self.minutes_at(counts, words_per_minute))
+                .sum()
+        };
+        let minutes = match self.format {
+            ReadingTimeFormat::Point => total(WORDS_PER_MINUTE).to_string(),
+            ReadingTimeFormat::Range => {
+                let fast = total(self.fast_wpm);
+                let slow = total(self.slow_wpm);
+                if fast == slow {
+                    fast.to_string()
+                } else {
+                    format!("{fast}–{slow}")
+                }
+            }
+        };
+        self.display.replace("{}", &minutes)
+    }
+}
+
+/// `count` with thousands separated by commas, e.g. `1,240`.
+fn thousands(count: usize) -> String {
+    let digits = count.to_string();
+    let mut separated = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            separated.push(',');
+        }
+        separated.push(digit);
+    }
+    separated
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct SubmitIssue {
+    pub url: String,
+    pub badge_url: String,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct EditPage {
+    pub url: String,
+    pub badge_url: String,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct ColabNB {
+    pub url: String,
+    pub badge_url: String,
+    /// Name of the notebook, when the header has several.
+    pub label: Option<String>,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct DownloadNB {
+    pub url: String,
+    pub file_name: String,
+    /// Name of the notebook, when the header has several.
+    pub label: Option<String>,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct KaggleNB {
+    pub url: String,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct BinderNB {
+    pub url: String,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct SageMakerNB {
+    pub url: String,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct Requirement {
+    pub label: String,
+    pub value: String,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct Difficulty {
+    pub level: String,
+    pub badge_url: String,
+}
+
+impl Difficulty {
+    fn new(label: &str, level: &str, color: &str) -> Self {
+        let mut chars = level.chars();
+        let level: String = chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default();
+        // shields.io escapes `-` and `_` by doubling them
+        let escape =
+            |text: &str| config::percent_encode(&text.replace('-', "--").replace('_', "__"));
+        Self {
+            badge_url: format!(
+                "https://img.shields.io/badge/{}-{}-{}",
+                escape(label),
+                escape(&level),
+                config::percent_encode(color)
+            ),
+            level,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct Updated {
+    pub date: String,
+    /// How long ago `date` is, e.g. "Updated 3 weeks ago".
+    pub text: String,
+}
+
+/// How long before `today` a chapter last modified on `date` was updated, in
+/// the book's language.
+fn updated(ctx: &HelperContext, date: chrono::NaiveDate, today: chrono::NaiveDate) -> Updated {
+    let days = (today - date).num_days().max(0);
+    let (msgid, n) = match days {
+        0 => ("Updated today", 0),
+        1 => ("Updated yesterday", 1),
+        2..=6 => ("Updated {n} days ago", days),
+        7..=13 => ("Updated a week ago", 1),
+        14..=29 => ("Updated {n} weeks ago", days / 7),
+        30..=59 => ("Updated a month ago", 1),
+        60..=364 => ("Updated {n} months ago", days / 30),
+        365..=729 => ("Updated a year ago", 1),
+        _ => ("Updated {n} years ago", days / 365),
+    };
+    Updated {
+        date: date.format("%Y-%m-%d").to_string(),
+        text: ctx.translate(msgid).replace("{n}", &n.to_string()),
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct ReadingTime {
+    pub value: String,
+    /// Formatted word count, shown after the reading time.
+    pub word_count: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{parse_front_matter, BookInfo, ChapterInfo};
+    use crate::i18n::Catalog;
+    use anyhow::Result;
+    use rstest::*;
+    use std::collections::BTreeMap;
+
+    #[rstest]
+    #[case(
+        "submit_issue=false,colab=nlp/lora.ipynb,reading_time=false",
+        AIPRHeaderSettings {
+            colab: Some("nlp/lora.ipynb".to_string()),
+            submit_issue: false,
+            issue_template: None,
+            reading_time: false,
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "no_reading_time no_issue colab=nlp/lora.ipynb,download",
+        AIPRHeaderSettings {
+            colab: Some("nlp/lora.ipynb".to_string()),
+            download: true,
+            submit_issue: false,
+            reading_time: false,
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "colab=nlp/lora.ipynb",
+        AIPRHeaderSettings {
+            colab: Some("nlp/lora.ipynb".to_string()),
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "colab=nlp/lora.ipynb kaggle=nlp/lora.ipynb",
+        AIPRHeaderSettings {
+            colab: Some("nlp/lora.ipynb".to_string()),
+            kaggle: Some("nlp/lora.ipynb".to_string()),
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "binder=nlp/lora.ipynb",
+        AIPRHeaderSettings {
+            binder: Some("nlp/lora.ipynb".to_string()),
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "difficulty=Advanced",
+        AIPRHeaderSettings {
+            difficulty: Some("advanced".to_string()),
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "gpu=T4,vram=16GB,runtime=20min",
+        AIPRHeaderSettings {
+            gpu: Some("T4".to_string()),
+            vram: Some("16GB".to_string()),
+            runtime: Some("20min".to_string()),
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "colab=nlp/lora.ipynb,download=true",
+        AIPRHeaderSettings {
+            colab: Some("nlp/lora.ipynb".to_string()),
+            download: true,
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "sagemaker=nlp/lora.ipynb",
+        AIPRHeaderSettings {
+            sagemaker: Some("nlp/lora.ipynb".to_string()),
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "author=nerdai,@octocat colab=nlp/lora.ipynb",
+        AIPRHeaderSettings {
+            author: vec!["nerdai".to_string(), "@octocat".to_string()],
+            colab: Some("nlp/lora.ipynb".to_string()),
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "issue_template=typo.yml",
+        AIPRHeaderSettings {
+            issue_template: Some("typo.yml".to_string()),
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "reading_time_rounding=ceil,reading_time_min=1,reading_time_display=~{} min read",
+        AIPRHeaderSettings {
+            reading_time_rounding: Some(Rounding::Ceil),
+            reading_time_min: Some(1),
+            reading_time_display: Some("~{} min read".to_string()),
+            ..Default::default()
+        }
+    )]
+    #[case(
+        "reading_time=falsee",
+        AIPRHeaderSettings {
+            ..Default::default()
+        }
+    )]
+    fn test_aipr_header_settings(
+        #[case] param_str: &str,
+        #[case] expected_setting: AIPRHeaderSettings,
+    ) -> Result<()> {
+        let setting = AIPRHeaderSettings::from_param_str(param_str);
+        assert_eq!(setting, expected_setting);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("", 20, "0 min")]
+    #[case("reading_time_min=1", 20, "1 min")]
+    #[case("reading_time_rounding=ceil", 201, "2 min")]
+    #[case("reading_time_rounding=floor", 399, "1 min")]
+    #[case("reading_time_display=~{} min read", 400, "~2 min read")]
+    #[case("reading_time_format=range", 1000, "4–7 min")]
+    #[case(
+        "reading_time_format=range,reading_time_rounding=floor",
+        1000,
+        "4–6 min"
+    )]
+    #[case("reading_time_format=range", 100, "0–1 min")]
+    #[case("reading_time_format=range,reading_time_min=1", 100, "1 min")]
+    fn test_reading_time_options(
+        #[case] param_str: &str,
+        #[case] num_words: usize,
+        #[case] expected: &str,
+    ) -> Result<()> {
+        let settings = AIPRHeaderSettings::from_param_str(param_str);
+        let options = settings.reading_time_options(&AIPRConfig::default());
+        let counts = WordCount {
+            prose_words: num_words,
+            ..Default::default()
+        };
+
+        assert_eq!(options.format(&counts), expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(0, 600, 2)]
+    #[case(400, 600, 4)]
+    #[case(150, 150, 2)]
+    fn test_reading_time_cjk(
+        #[case] prose_words: usize,
+        #[case] cjk_chars: usize,
+        #[case] expected: usize,
+    ) -> Result<()> {
+        let counts = WordCount {
+            prose_words,
+            cjk_chars,
+            ..Default::default()
+        };
+
+        let options = ReadingTimeOptions::from_config(&AIPRConfig::default());
+        assert_eq!(options.minutes(&counts), expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(0.0, 0.0, 1)]
+    #[case(4.0, 0.0, 3)]
+    #[case(4.0, 50.0, 4)]
+    fn test_reading_time_weights(
+        #[case] code_line_words: f32,
+        #[case] equation_words: f32,
+        #[case] expected: usize,
+    ) -> Result<()> {
+        let config = AIPRConfig {
+            reading_time_code_line_words: code_line_words,
+            reading_time_equation_words: equation_words,
+            ..Default::default()
+        };
+        let counts = WordCount {
+            prose_words: 200,
+            cjk_chars: 0,
+            code_words: 300,
+            code_lines: 100,
+            equations: 4,
+            images: 0,
+        };
+
+        assert_eq!(
+            ReadingTimeOptions::from_config(&config).minutes(&counts),
+            expected
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("en", "2026-03-01", "Updated today")]
+    #[case("en", "2026-02-28", "Updated yesterday")]
+    #[case("en", "2026-02-24", "Updated 5 days ago")]
+    #[case("en", "2026-02-20", "Updated a week ago")]
+    #[case("en", "2026-02-08", "Updated 3 weeks ago")]
+    #[case("en", "2025-11-01", "Updated 4 months ago")]
+    #[case("en", "2023-01-01", "Updated 3 years ago")]
+    #[case("fr", "2026-02-08", "Mis à jour il y a 3 semaines")]
+    fn test_updated(
+        #[case] language: &str,
+        #[case] date: &str,
+        #[case] expected: &str,
+    ) -> Result<()> {
+        let mut book = BookInfo::default();
+        book.set_catalog(Catalog::new(language, &[], None, &BTreeMap::new()));
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book,
+            chapter: &ChapterInfo::default(),
+        };
+        let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+
+        let updated = updated(&ctx, date, today);
+        assert_eq!(updated.text, expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_render_stale_notice() -> Result<()> {
+        let config = AIPRConfig {
+            stale_after_days: Some(365),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let date = |s: &str| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d");
+        let today = date("2026-03-01")?;
+
+        let html_string = render_stale_notice(&ctx, date("2025-01-15")?, today)?;
+        assert!(html_string.contains("<strong>This chapter may be outdated</strong>"));
+        assert!(html_string.contains("It was last updated on 2025-01-15, more than 365 days ago."));
+        assert_eq!(render_stale_notice(&ctx, date("2025-06-01")?, today)?, "");
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("colab=auto", false, Some("nlp/lora.ipynb"))]
+    #[case(
+        "colab=auto;nlp/lora_eval.ipynb",
+        false,
+        Some("nlp/lora.ipynb;nlp/lora_eval.ipynb")
+    )]
+    #[case("", false, None)]
+    #[case("", true, Some("nlp/lora.ipynb"))]
+    #[case("colab=nlp/qlora.ipynb", true, Some("nlp/qlora.ipynb"))]
+    #[case("colab=none", true, None)]
+    fn test_header_settings_with_colab_auto(
+        #[case] params: &str,
+        #[case] colab_auto: bool,
+        #[case] expected: Option<&str>,
+    ) -> Result<()> {
+        let config = AIPRConfig {
+            colab_auto,
+            ..Default::default()
+        };
+        let settings = AIPRHeaderSettings::from_param_str(params)
+            .with_colab_auto(Some(Path::new("nlp/lora.md")), &config);
+        assert_eq!(settings.colab.as_deref(), expected);
+
+        // drafts have no notebook of their own
+        let settings = AIPRHeaderSettings::from_param_str(params).with_colab_auto(None, &config);
+        let notebooks = settings.colab_notebooks();
+        assert!(!notebooks.contains(&"nlp/lora.ipynb") && !notebooks.contains(&"auto"));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_header_settings_with_front_matter() -> Result<()> {
+        let front_matter = parse_front_matter(
+            "---\ncolab: nlp/lora.ipynb\nkaggle: lora.ipynb\ndownload: true\n---\n",
+        );
+        let settings = AIPRHeaderSettings::from_param_str("colab=nlp/qlora.ipynb")
+            .with_front_matter(&front_matter);
+
+        assert_eq!(settings.colab.as_deref(), Some("nlp/qlora.ipynb"));
+        assert_eq!(settings.kaggle.as_deref(), Some("lora.ipynb"));
+        assert!(settings.download);
+        assert!(settings.reading_time);
+
+        Ok(())
+    }
+}
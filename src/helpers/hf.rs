@@ -0,0 +1,147 @@
+//! The `{{#aipr_hf}}` helper linking Hugging Face Hub models, datasets and
+//! spaces.
+
+use crate::config;
+use crate::fetch::fetch_cached;
+use crate::helpers::params::{_parse_param_str, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use crate::huggingface::{HubKind, HubMetadata, HubRepo};
+use handlebars::to_json;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRHfSettings {
+    repo: Option<HubRepo>,
+    /// Whether to show a card with metadata fetched from the Hub.
+    card: bool,
+}
+
+impl AIPRHfSettings {
+    /// Params that are `true` or `false`, and can be given as bare flags.
+    pub(crate) const BOOLEAN_PARAMS: &'static [&'static str] = &["card"];
+
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, Self::BOOLEAN_PARAMS);
+        let repo = [
+            ("model", HubKind::Model),
+            ("dataset", HubKind::Dataset),
+            ("space", HubKind::Space),
+        ]
+        .into_iter()
+        .find_map(|(key, kind)| {
+            Some(HubRepo {
+                kind,
+                id: param_map.get(key)?.to_owned(),
+            })
+        });
+        let card = matches!(param_map.get("card"), Some(bool_str) if (bool_str == "true"));
+
+        Self { repo, card }
+    }
+}
+
+impl HelperHandler for AIPRHfSettings {
+    fn name() -> &'static str {
+        "aipr_hf"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(required_params(Self::name(), params)?))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let Some(repo) = &self.repo else {
+            anyhow::bail!("`aipr_hf` requires a `model`, `dataset` or `space` id");
+        };
+
+        // shields.io escapes `-` and `_` by doubling them
+        let message = repo.id.replace('-', "--").replace('_', "__");
+        let badge_url = format!(
+            "https://img.shields.io/badge/{}-{}-yellow?logo=huggingface",
+            repo.kind.label(),
+            config::percent_encode(&message)
+        );
+
+        // create data for rendering handlebar
+        let mut data = Map::new();
+        data.insert("url".to_string(), to_json(repo.url()));
+        data.insert("badge_url".to_string(), to_json(badge_url));
+        data.insert("label".to_string(), to_json(repo.kind.label()));
+        data.insert("id".to_string(), to_json(&repo.id));
+        if self.card {
+            // the badge alone is enough when the Hub is unreachable
+            let metadata = fetch_cached(
+                ctx.config,
+                &ctx.book.root,
+                &repo.api_url(),
+                &repo.cache_key(),
+            )
+            .and_then(|json| HubMetadata::from_json(&json));
+            match metadata {
+                Ok(metadata) => {
+                    data.insert("card".to_string(), to_json(metadata));
+                }
+                Err(e) => log::warn!("No metadata for `{}`, skipping its card: {e:#}", repo.id),
+            }
+        }
+
+        // render
+        let html_string = ctx.render("aipr_hf", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_hf_render() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aipr-hf-{}", std::process::id()));
+        std::fs::create_dir_all(root.join(".aipr-cache/huggingface/models/meta-llama"))?;
+        std::fs::write(
+            root.join(".aipr-cache/huggingface/models/meta-llama/Llama-3-8B.json"),
+            r#"{"downloads": 1500, "likes": 20, "cardData": {"license": "llama3"}}"#,
+        )?;
+        let config = AIPRConfig {
+            offline: true,
+            ..Default::default()
+        };
+        let book_info = BookInfo {
+            root: root.clone(),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &book_info,
+            chapter: &ChapterInfo::default(),
+        };
+
+        let html_string =
+            AIPRHfSettings::from_param_str("model=meta-llama/Llama-3-8B card=true").render(&ctx)?;
+        assert_eq!(
+            html_string,
+            "<span class=\"aipr-hf\"><a href=\"https://huggingface.co/meta-llama/Llama-3-8B\" \
+            target=\"_blank\" rel=\"noopener noreferrer\"><img src=\"https://img.shields.io/badge/\
+            Model-meta--llama%2FLlama--3--8B-yellow?logo&#x3D;huggingface\" \
+            alt=\"Hugging Face Model: meta-llama/Llama-3-8B\"/></a><span class=\"aipr-hf-card\">\
+            <span>1.5k downloads</span><span>20 likes</span><span>llama3</span></span></span>"
+        );
+
+        // uncached metadata leaves out the card when offline
+        let html_string =
+            AIPRHfSettings::from_param_str("space=org/demo,card=true").render(&ctx)?;
+        assert!(!html_string.contains("aipr-hf-card"));
+        assert!(AIPRHfSettings::from_param_str("card=true")
+            .render(&ctx)
+            .is_err());
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+//! The `{{#aipr_last_updated}}` helper showing the date of the chapter's
+//! last commit.
+
+use crate::helpers::params::no_params;
+use crate::helpers::{HelperContext, HelperHandler};
+use serde::Serialize;
+
+/// An `{{#aipr_last_updated}}` helper, which takes no params.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct AIPRLastUpdatedSettings;
+
+impl HelperHandler for AIPRLastUpdatedSettings {
+    fn name() -> &'static str {
+        "aipr_last_updated"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        no_params(Self::name(), params)?;
+        Ok(Self)
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        match ctx.last_updated() {
+            Some(last_updated) => ctx.render("aipr_last_updated", &last_updated),
+            None => {
+                log::warn!(
+                    "No git history for chapter `{}`, skipping `aipr_last_updated`.",
+                    ctx.chapter.name
+                );
+                Ok(String::new())
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct LastUpdated {
+    pub date: String,
+}
@@ -0,0 +1,108 @@
+//! The `{{#aipr_license}}` helper adding a content license line.
+
+use crate::config;
+use crate::helpers::params::required_params;
+use crate::helpers::{HelperContext, HelperHandler};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRLicenseSettings {
+    /// SPDX id of the license.
+    id: String,
+}
+
+impl HelperHandler for AIPRLicenseSettings {
+    fn name() -> &'static str {
+        "aipr_license"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: required_params(Self::name(), params)?.to_string(),
+        })
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let html_string = ctx.render("aipr_license", &License::from_id(&self.id))?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct License {
+    name: String,
+    label: String,
+    url: Option<String>,
+    badge_url: Option<String>,
+}
+
+impl License {
+    /// The license with SPDX id `id`. Unknown licenses are shown by id, without
+    /// a link.
+    pub(crate) fn from_id(id: &str) -> Self {
+        let known = match id.trim() {
+            "CC-BY-4.0" => Some((
+                "Creative Commons Attribution 4.0 International",
+                "CC BY 4.0",
+                "https://creativecommons.org/licenses/by/4.0/",
+            )),
+            "CC-BY-SA-4.0" => Some((
+                "Creative Commons Attribution-ShareAlike 4.0 International",
+                "CC BY-SA 4.0",
+                "https://creativecommons.org/licenses/by-sa/4.0/",
+            )),
+            "CC-BY-NC-4.0" => Some((
+                "Creative Commons Attribution-NonCommercial 4.0 International",
+                "CC BY-NC 4.0",
+                "https://creativecommons.org/licenses/by-nc/4.0/",
+            )),
+            "CC-BY-NC-SA-4.0" => Some((
+                "Creative Commons Attribution-NonCommercial-ShareAlike 4.0 International",
+                "CC BY-NC-SA 4.0",
+                "https://creativecommons.org/licenses/by-nc-sa/4.0/",
+            )),
+            "CC-BY-ND-4.0" => Some((
+                "Creative Commons Attribution-NoDerivatives 4.0 International",
+                "CC BY-ND 4.0",
+                "https://creativecommons.org/licenses/by-nd/4.0/",
+            )),
+            "CC0-1.0" => Some((
+                "CC0 1.0 Universal",
+                "CC0 1.0",
+                "https://creativecommons.org/publicdomain/zero/1.0/",
+            )),
+            "MIT" => Some((
+                "the MIT License",
+                "MIT",
+                "https://opensource.org/license/mit",
+            )),
+            "Apache-2.0" => Some((
+                "the Apache License 2.0",
+                "Apache 2.0",
+                "https://www.apache.org/licenses/LICENSE-2.0",
+            )),
+            _ => None,
+        };
+        let Some((name, label, url)) = known else {
+            log::warn!("Unknown license `{id}`, showing it without a link.");
+            return Self {
+                name: id.trim().to_string(),
+                label: id.trim().to_string(),
+                url: None,
+                badge_url: None,
+            };
+        };
+        // shields.io escapes `-` by doubling it and shows `_` as a space
+        let message = label.replace('-', "--").replace(' ', "_");
+        Self {
+            name: name.to_string(),
+            label: label.to_string(),
+            url: Some(url.to_string()),
+            badge_url: Some(format!(
+                "https://img.shields.io/badge/License-{}-lightgrey",
+                config::percent_encode(&message)
+            )),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! The `{{#aipr_link}}` helper expanding the shortlinks of the `links`
+//! config.
+
+use crate::ai_pocket_reference::MDLink;
+use crate::helpers::params::{_unquote, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use crate::link_check;
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRLinkSettings {
+    /// Name of the link in the `links` config.
+    name: String,
+    text: Option<String>,
+}
+
+impl AIPRLinkSettings {
+    /// Settings of a link with the params `param_str`, e.g.
+    /// `llama3 "Llama 3 paper"`.
+    fn from_param_str(param_str: &str) -> Self {
+        let (name, text) = param_str
+            .split_once(char::is_whitespace)
+            .unwrap_or((param_str, ""));
+        let text = _unquote(text);
+        Self {
+            name: name.to_string(),
+            text: (!text.is_empty()).then_some(text),
+        }
+    }
+}
+
+impl HelperHandler for AIPRLinkSettings {
+    fn name() -> &'static str {
+        "aipr_link"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(required_params(Self::name(), params)?))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let Some(url) = ctx.config.links.get(&self.name) else {
+            if ctx.config.strict {
+                anyhow::bail!("Link `{}` is not in the `links` config", self.name);
+            }
+            log::warn!(
+                "Link `{}` used by `{}` is not in the `links` config.",
+                self.name,
+                ctx.chapter.name
+            );
+            return Ok(self.text.clone().unwrap_or_else(|| self.name.clone()));
+        };
+        let text = self.text.as_deref().unwrap_or(url);
+        if link_check::host_matches(url, &ctx.config.new_tab_exclude) {
+            return Ok(format!("[{text}]({url})"));
+        }
+
+        // render
+        let link = MDLink {
+            text,
+            url: Cow::Borrowed(url),
+            ..Default::default()
+        };
+        link.render(ctx)
+    }
+}
@@ -0,0 +1,319 @@
+//! Parsing of the params of helpers, `key=value` pairs and flags, and
+//! checking them for mistakes.
+
+use crate::config::{self, AIPRConfig};
+use crate::helpers::footer::FooterLink;
+use crate::helpers::header::_split_notebooks;
+use crate::helpers::BuiltInHelper;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Names of boolean params as bare flags, where they differ, e.g. `no_issue`
+/// for `submit_issue=false`.
+const FLAG_ALIASES: &[(&str, &str)] = &[("edit", "edit_link"), ("issue", "submit_issue")];
+
+/// The param a bare flag stands for, of the helper's `boolean_params`:
+/// `<param>` for `<param>=true` and `no_<param>` for `<param>=false`, or their
+/// `FLAG_ALIASES`.
+fn _parse_flag(
+    flag: &str,
+    boolean_params: &[&'static str],
+) -> Option<(&'static str, &'static str)> {
+    let (name, value) = match flag.strip_prefix("no_") {
+        Some(name) => (name, "false"),
+        None => (flag, "true"),
+    };
+    let name = FLAG_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, key)| *key);
+    let key = boolean_params.iter().find(|key| **key == name)?;
+    Some((key, value))
+}
+
+/// The params of a helper whose `boolean_params` can be given as bare flags.
+pub(crate) fn _parse_param_str(
+    param_str: &str,
+    boolean_params: &[&'static str],
+) -> HashMap<String, String> {
+    // Tokens without an `=` continue the value of the preceding key, so that
+    // list values such as `links=github,website` survive the split on `,`,
+    // unless they're flags such as `no_reading_time`.
+    let mut param_map = HashMap::new();
+    let mut last_key: Option<String> = None;
+    for token in _split_params(param_str) {
+        match token.split_once('=') {
+            Some((key, value)) => {
+                let key = key.trim().to_string();
+                param_map.insert(key.clone(), _unquote(value));
+                last_key = Some(key);
+            }
+            None => match _parse_flag(token.trim(), boolean_params) {
+                Some((key, value)) => {
+                    param_map.insert(key.to_string(), value.to_string());
+                    last_key = None;
+                }
+                None => {
+                    if let Some(value) = last_key.as_ref().and_then(|k| param_map.get_mut(k)) {
+                        value.push(',');
+                        value.push_str(token.trim_end());
+                    }
+                }
+            },
+        }
+    }
+    param_map
+}
+
+/// A param value with surrounding double quotes removed, and the `\"` and
+/// `\\` escapes within them resolved.
+pub(crate) fn _unquote(value: &str) -> String {
+    let value = value.trim();
+    let Some(quoted) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    else {
+        return value.to_string();
+    };
+    let mut unquoted = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(&escaped @ ('"' | '\\'))) => {
+                unquoted.push(escaped);
+                chars.next();
+            }
+            _ => unquoted.push(c),
+        }
+    }
+    unquoted
+}
+
+/// Whether a param-str has a double quote that isn't closed.
+fn _has_unclosed_quote(param_str: &str) -> bool {
+    let mut in_quotes = false;
+    let mut chars = param_str.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => {
+                chars.next();
+            }
+            _ => {}
+        }
+    }
+    in_quotes
+}
+
+/// Splits a param-str into tokens on commas, and on whitespace followed by a
+/// `key=` or before the first `key=`, outside of double quotes, within which
+/// `\"` escapes a quote. Both `a=1,b=2` and `a=1 b="x, y"` work, flags may
+/// precede the params, e.g. `no_issue colab=x`, and other whitespace stays
+/// within values.
+fn _split_params(param_str: &str) -> Vec<&str> {
+    static KEY_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-zA-Z_][a-zA-Z0-9_]*=").unwrap());
+
+    let mut tokens = vec![];
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (index, c) in param_str.char_indices() {
+        let next = index + c.len_utf8();
+        if std::mem::take(&mut escaped) {
+            continue;
+        }
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => escaped = true,
+            ',' if !in_quotes => {
+                tokens.push(&param_str[start..index]);
+                start = next;
+            }
+            c if c.is_whitespace()
+                && !in_quotes
+                && (KEY_RE.is_match(&param_str[next..]) || !param_str[..index].contains('=')) =>
+            {
+                tokens.push(&param_str[start..index]);
+                start = index;
+            }
+            _ => {}
+        }
+    }
+    tokens.push(&param_str[start..]);
+    tokens
+}
+
+/// Whether `token` is a name such as a param's, e.g. `no_reading_time`.
+fn _is_identifier(token: &str) -> bool {
+    token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `params` of the helper `name`, which needs some.
+pub(crate) fn required_params<'a>(name: &str, params: &'a str) -> anyhow::Result<&'a str> {
+    anyhow::ensure!(!params.is_empty(), "`{name}` needs params");
+    Ok(params)
+}
+
+/// Checks that the helper `name`, which takes no params, has none.
+pub(crate) fn no_params(name: &str, params: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(params.is_empty(), "`{name}` takes no params");
+    Ok(())
+}
+
+pub(crate) fn _parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Problems with the `key=value` params of the built-in `helper`: tokens
+/// that aren't `key=value`, unclosed quotes, keys the helper doesn't take and
+/// booleans that aren't `true` or `false`.
+pub(crate) fn find_param_problems(
+    helper: &BuiltInHelper,
+    param_str: &str,
+    config: &AIPRConfig,
+) -> Vec<String> {
+    let (name, Some(known)) = (helper.name, helper.params) else {
+        return vec![];
+    };
+    let param_str = match helper.path_first {
+        true => param_str
+            .trim()
+            .split_once(char::is_whitespace)
+            .map_or("", |(_, params)| params),
+        false => param_str,
+    };
+
+    let mut problems = vec![];
+    if _has_unclosed_quote(param_str) {
+        problems.push(format!("unclosed quote in the params of `{name}`"));
+    }
+    let mut after_key = false;
+    for token in _split_params(param_str) {
+        match token.split_once('=') {
+            Some((key, value)) => {
+                let key = key.trim();
+                let value = value.trim().trim_matches('"');
+                if !known.contains(&key) {
+                    problems.push(format!("unknown param `{key}` of `{name}`"));
+                } else if helper.boolean_params.contains(&key) && !matches!(value, "true" | "false")
+                {
+                    problems.push(format!(
+                        "param `{key}={value}` of `{name}` isn't `true` or `false`"
+                    ));
+                } else if key == "colab" {
+                    problems.extend(
+                        _split_notebooks(value)
+                            .into_iter()
+                            .filter(|path| {
+                                config::is_notebook_url(path) && config::colab_link(path).is_none()
+                            })
+                            .map(|url| {
+                                format!(
+                                    "`colab` URL `{url}` of `{name}` isn't a Colab link or a \
+                                    notebook on github.com"
+                                )
+                            }),
+                    );
+                }
+                after_key = true;
+            }
+            None => match _parse_flag(token.trim(), helper.boolean_params) {
+                Some(_) => after_key = false,
+                // other identifiers before the first `key=` are meant as flags
+                None if !after_key && _is_identifier(token.trim()) => {
+                    problems.push(format!("unknown flag `{}` of `{name}`", token.trim()))
+                }
+                // other tokens without `=` continue list values
+                None if after_key || token.trim().is_empty() => {}
+                None => problems.push(format!(
+                    "malformed param `{}` of `{name}`, expected `key=value` or a flag",
+                    token.trim()
+                )),
+            },
+        }
+    }
+    if name == "aipr_footer" {
+        let links = _parse_param_str(param_str, helper.boolean_params)
+            .remove("links")
+            .unwrap_or_default();
+        problems.extend(
+            _parse_list(&links)
+                .into_iter()
+                .filter(|link| FooterLink::from_name(link, config).is_none())
+                .map(|link| format!("unknown link `{link}` of `{name}`")),
+        );
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::footer::AIPRFooterSettings;
+    use crate::helpers::header::AIPRHeaderSettings;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    #[case("a=1,b=2", &[], &[("a", "1"), ("b", "2")])]
+    #[case("links=github,website", &[], &[("links", "github,website")])]
+    #[case("type=warning title=Heads up", &[], &[("type", "warning"), ("title", "Heads up")])]
+    #[case("src=img/lora.png caption=\"LoRA, decomposed\"", &[], &[("src", "img/lora.png"), ("caption", "LoRA, decomposed")])]
+    #[case("title=\"LoRA, explained\"", &[], &[("title", "LoRA, explained")])]
+    #[case("title=\"a = b\",type=tip", &[], &[("title", "a = b"), ("type", "tip")])]
+    #[case(r#"caption="The \"low-rank\" trick, with a \\ backslash""#, &[], &[("caption", r#"The "low-rank" trick, with a \ backslash"#)])]
+    #[case("no_reading_time no_issue colab=nlp/lora.ipynb", AIPRHeaderSettings::BOOLEAN_PARAMS, &[("reading_time", "false"), ("submit_issue", "false"), ("colab", "nlp/lora.ipynb")])]
+    #[case("colab=nlp/lora.ipynb,download,last_updated", AIPRHeaderSettings::BOOLEAN_PARAMS, &[("colab", "nlp/lora.ipynb"), ("download", "true"), ("last_updated", "true")])]
+    #[case("links=github,logo", &[], &[("links", "github,logo")])]
+    #[case("links=github,no_logo", AIPRFooterSettings::BOOLEAN_PARAMS, &[("links", "github"), ("logo", "false")])]
+    #[case("type=note,title=Check the license", &[], &[("type", "note"), ("title", "Check the license")])]
+    #[case("description=Why download matters,download", AIPRHeaderSettings::BOOLEAN_PARAMS, &[("description", "Why download matters"), ("download", "true")])]
+    #[case("caption=Why, and how, download matters", &[], &[("caption", "Why, and how, download matters")])]
+    #[case("title=license issue", AIPRFooterSettings::BOOLEAN_PARAMS, &[("title", "license issue")])]
+    fn test_parse_param_str(
+        #[case] param_str: &str,
+        #[case] boolean_params: &[&'static str],
+        #[case] expected: &[(&str, &str)],
+    ) -> Result<()> {
+        let expected = expected
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(_parse_param_str(param_str, boolean_params), expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("a=1,b=2", &["a=1", "b=2"])]
+    #[case("a=1 b=2", &["a=1", " b=2"])]
+    #[case("title=Heads up", &["title=Heads up"])]
+    #[case("title=\"x, y\" type=tip", &["title=\"x, y\"", " type=tip"])]
+    #[case(r#"title="say \"a, b=c\"",x=1"#, &[r#"title="say \"a, b=c\"""#, "x=1"])]
+    #[case("no_issue colab=x", &["no_issue", " colab=x"])]
+    #[case("no_issue download colab=x", &["no_issue", " download", " colab=x"])]
+    #[case("colab=x download", &["colab=x download"])]
+    fn test_split_params(#[case] param_str: &str, #[case] expected: &[&str]) -> Result<()> {
+        assert_eq!(_split_params(param_str), expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("title=\"x, y\"", false)]
+    #[case("title=\"x, y", true)]
+    #[case(r#"title="say \"hi\"""#, false)]
+    #[case(r#"title="say \"hi"#, true)]
+    fn test_has_unclosed_quote(#[case] param_str: &str, #[case] expected: bool) -> Result<()> {
+        assert_eq!(_has_unclosed_quote(param_str), expected);
+
+        Ok(())
+    }
+}
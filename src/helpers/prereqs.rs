@@ -0,0 +1,123 @@
+//! The `{{#aipr_prereqs}}` helper rendering a "Before you read this" list.
+
+use crate::helpers::chapter::ChapterLink;
+use crate::helpers::params::{_parse_list, _parse_param_str, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRPrereqsSettings {
+    pub(crate) prereqs: Vec<String>,
+    title: String,
+}
+
+impl AIPRPrereqsSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, &[]);
+        let prereqs = param_map
+            .get("prereqs")
+            .map(|s| _parse_list(s))
+            .unwrap_or_default();
+        let title = param_map
+            .get("title")
+            .cloned()
+            .unwrap_or_else(|| "Before you read this".to_string());
+
+        Self { prereqs, title }
+    }
+}
+
+impl HelperHandler for AIPRPrereqsSettings {
+    fn name() -> &'static str {
+        "aipr_prereqs"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(required_params(Self::name(), params)?))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        // create data for rendering handlebar
+        let mut chapters = vec![];
+        for path in &self.prereqs {
+            let Some(summary) = ctx.resolve_chapter(path)? else {
+                continue;
+            };
+            chapters.push(ChapterLink {
+                title: summary.name.clone(),
+                url: ctx.chapter_href(path),
+            });
+        }
+        if chapters.is_empty() {
+            return Ok(String::new());
+        }
+        let mut data = Map::new();
+        data.insert("title".to_string(), to_json(ctx.translate(&self.title)));
+        data.insert("chapters".to_string(), to_json(chapters));
+
+        // render
+        let html_string = ctx.render("aipr_prereqs", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo, ChapterSummary};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_prereqs_render(#[case] strict: bool) -> Result<()> {
+        let book = BookInfo {
+            chapters: HashMap::from([(
+                PathBuf::from("fundamentals/transformers.md"),
+                ChapterSummary {
+                    name: "Transformers".to_string(),
+                    num_words: 1000,
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            path: Some(PathBuf::from("nlp/lora.md")),
+            ..Default::default()
+        };
+        let config = AIPRConfig {
+            strict,
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &config,
+            book: &book,
+            chapter: &chapter,
+        };
+
+        let html_string =
+            AIPRPrereqsSettings::from_param_str("prereqs=fundamentals/transformers.md")
+                .render(&ctx)?;
+        let expected = "<div class=\"aipr-prereqs\">\n  <p><strong>Before you read this</strong></p>\n  \
+        <ul>\n    <li><a href=\"../fundamentals/transformers.html\">Transformers</a></li>\n  </ul>\n</div>\n";
+        assert_eq!(html_string, expected);
+
+        let result = AIPRPrereqsSettings::from_param_str(
+            "prereqs=fundamentals/transformers.md,nlp/atention.md",
+        )
+        .render(&ctx);
+        match strict {
+            true => assert!(result.is_err()),
+            false => assert_eq!(result?, expected),
+        }
+
+        Ok(())
+    }
+}
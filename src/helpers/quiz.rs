@@ -0,0 +1,83 @@
+//! The `{{#aipr_quiz}}` block helper rendering multiple-choice questions.
+
+use crate::helpers::params::_parse_param_str;
+use crate::helpers::{HelperContext, HelperHandler};
+use crate::quiz::Quiz;
+use handlebars::to_json;
+use serde::Serialize;
+use serde_json::value::Map;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRQuizSettings {
+    /// TOML file with the questions, relative to the chapter's source file.
+    pub(crate) file: Option<String>,
+    /// Inline TOML questions, when no `file` is given.
+    pub(crate) body: String,
+}
+
+impl AIPRQuizSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, &[]);
+        Self {
+            file: param_map.get("file").cloned(),
+            body: String::new(),
+        }
+    }
+}
+
+impl HelperHandler for AIPRQuizSettings {
+    fn name() -> &'static str {
+        "aipr_quiz"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(params))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let quiz = match &self.file {
+            Some(file) => {
+                let dir = ctx
+                    .chapter_source_file()
+                    .and_then(|path| path.parent().map(Path::to_path_buf))
+                    .unwrap_or_else(|| ctx.book.src_dir.clone());
+                Quiz::from_file(&dir.join(file))?
+            }
+            None => Quiz::from_toml(&self.body)?,
+        };
+        let counter = &ctx.chapter.quiz_counter;
+        counter.set(counter.get() + 1);
+
+        // create data for rendering handlebar, answers are 0-based like the
+        // radio button values
+        let questions = quiz
+            .questions
+            .into_iter()
+            .enumerate()
+            .map(|(index, question)| QuizQuestion {
+                id: format!("aipr-quiz-{}-{}", counter.get(), index + 1),
+                question: question.question,
+                choices: question.choices,
+                answer: question.answer - 1,
+                explanation: question.explanation,
+            })
+            .collect::<Vec<_>>();
+        let mut data = Map::new();
+        data.insert("questions".to_string(), to_json(questions));
+
+        // render
+        let html_string = ctx.render("aipr_quiz", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct QuizQuestion {
+    id: String,
+    question: String,
+    choices: Vec<String>,
+    answer: usize,
+    explanation: Option<String>,
+}
@@ -0,0 +1,131 @@
+//! The `{{#aipr_related}}` helper rendering cards for related chapters.
+
+use crate::helpers::header::ReadingTimeOptions;
+use crate::helpers::params::{_parse_list, _parse_param_str, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde::Serialize;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRRelatedSettings {
+    pub(crate) related: Vec<String>,
+    title: String,
+}
+
+impl AIPRRelatedSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, &[]);
+        let related = param_map
+            .get("related")
+            .map(|s| _parse_list(s))
+            .unwrap_or_default();
+        let title = param_map
+            .get("title")
+            .cloned()
+            .unwrap_or_else(|| "Related chapters".to_string());
+
+        Self { related, title }
+    }
+}
+
+impl HelperHandler for AIPRRelatedSettings {
+    fn name() -> &'static str {
+        "aipr_related"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(required_params(Self::name(), params)?))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        // create data for rendering handlebar
+        let reading_time = ReadingTimeOptions::from_config(ctx.config);
+        let mut chapters = vec![];
+        for path in &self.related {
+            let Some(summary) = ctx.resolve_chapter(path)? else {
+                continue;
+            };
+            chapters.push(RelatedChapter {
+                title: summary.name.clone(),
+                url: ctx.chapter_href(path),
+                reading_time: reading_time.format(&summary.word_count),
+            });
+        }
+        if chapters.is_empty() {
+            return Ok(String::new());
+        }
+        let mut data = Map::new();
+        data.insert("title".to_string(), to_json(ctx.translate(&self.title)));
+        data.insert("chapters".to_string(), to_json(chapters));
+
+        // render
+        let html_string = ctx.render("aipr_related", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct RelatedChapter {
+    title: String,
+    url: String,
+    reading_time: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo, ChapterSummary};
+    use crate::config::AIPRConfig;
+    use crate::word_count::WordCount;
+    use anyhow::Result;
+    use rstest::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    #[rstest]
+    fn test_related_render() -> Result<()> {
+        let book = BookInfo {
+            chapters: HashMap::from([(
+                PathBuf::from("nlp/qlora.md"),
+                ChapterSummary {
+                    name: "QLoRA".to_string(),
+                    num_words: 600,
+                    word_count: WordCount {
+                        prose_words: 600,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            path: Some(PathBuf::from("nlp/lora.md")),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &book,
+            chapter: &chapter,
+        };
+
+        let settings = AIPRRelatedSettings::from_param_str("related=./nlp/qlora.md,nlp/missing.md");
+        assert_eq!(
+            settings.related,
+            vec!["./nlp/qlora.md".to_string(), "nlp/missing.md".to_string()]
+        );
+        let html_string = settings.render(&ctx)?;
+        assert!(html_string.contains("<a class=\"aipr-related-card\" href=\"../nlp/qlora.html\">"));
+        assert!(html_string.contains("<span class=\"aipr-related-title\">QLoRA</span>"));
+        assert!(html_string.contains("<small>3 min</small>"));
+        assert!(!html_string.contains("missing"));
+
+        let html_string =
+            AIPRRelatedSettings::from_param_str("related=nlp/missing.md").render(&ctx)?;
+        assert_eq!(html_string, "");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+//! The `{{#aipr_series_nav}}` helper rendering "Part N of M" with links to
+//! the previous and next chapters of a series.
+
+use crate::helpers::chapter::ChapterLink;
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde_json::value::Map;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct AIPRSeriesNavSettings {
+    /// Name of the series in `book.toml`, or the first series containing the
+    /// chapter if unset.
+    series: Option<String>,
+}
+
+impl HelperHandler for AIPRSeriesNavSettings {
+    fn name() -> &'static str {
+        "aipr_series_nav"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            series: (!params.is_empty()).then(|| params.to_string()),
+        })
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let normalize = |path: &str| PathBuf::from(path.trim().trim_start_matches("./"));
+        let Some(chapter_path) = ctx.chapter.path.as_ref() else {
+            return Ok(String::new());
+        };
+        let is_chapter = |path: &String| normalize(path) == *chapter_path;
+
+        let series = match &self.series {
+            Some(name) => match ctx.config.series.get(name) {
+                Some(paths) => Some((name, paths)),
+                None => {
+                    anyhow::ensure!(!ctx.config.strict, "Series `{name}` is not configured");
+                    log::warn!(
+                        "Series `{name}` referenced by `{}` is not configured, skipping.",
+                        ctx.chapter.name
+                    );
+                    return Ok(String::new());
+                }
+            },
+            None => ctx
+                .config
+                .series
+                .iter()
+                .find(|(_, paths)| paths.iter().any(is_chapter)),
+        };
+        let part = series.and_then(|(_, paths)| paths.iter().position(is_chapter));
+        let (Some((name, paths)), Some(part)) = (series, part) else {
+            let name = self.series.as_deref().unwrap_or("any series");
+            anyhow::ensure!(
+                !ctx.config.strict,
+                "Chapter `{}` is not part of {name}",
+                ctx.chapter.name
+            );
+            log::warn!(
+                "Chapter `{}` is not part of {name}, skipping its series navigation.",
+                ctx.chapter.name
+            );
+            return Ok(String::new());
+        };
+
+        // create data for rendering handlebar
+        let chapter_link = |index: usize| -> anyhow::Result<Option<ChapterLink>> {
+            let Some(path) = paths.get(index) else {
+                return Ok(None);
+            };
+            Ok(ctx.resolve_chapter(path)?.map(|summary| ChapterLink {
+                title: summary.name.clone(),
+                url: ctx.chapter_href(path),
+            }))
+        };
+        let mut data = Map::new();
+        data.insert("series".to_string(), to_json(name));
+        data.insert("part".to_string(), to_json(part + 1));
+        data.insert("total".to_string(), to_json(paths.len()));
+        if let Some(prev) = part.checked_sub(1) {
+            data.insert("prev".to_string(), to_json(chapter_link(prev)?));
+        }
+        data.insert("next".to_string(), to_json(chapter_link(part + 1)?));
+
+        // render
+        let html_string = ctx.render("aipr_series_nav", &data)?;
+
+        Ok(html_string)
+    }
+}
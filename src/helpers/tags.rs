@@ -0,0 +1,56 @@
+//! The `{{#aipr_tags}}` helper rendering tag chips.
+
+use crate::helpers::params::{_parse_list, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use crate::tags;
+use handlebars::to_json;
+use serde::Serialize;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRTagsSettings {
+    pub(crate) tags: Vec<String>,
+}
+
+impl AIPRTagsSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        Self {
+            tags: _parse_list(param_str),
+        }
+    }
+}
+
+impl HelperHandler for AIPRTagsSettings {
+    fn name() -> &'static str {
+        "aipr_tags"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(required_params(Self::name(), params)?))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        // create data for rendering handlebar
+        let tags = self
+            .tags
+            .iter()
+            .map(|tag| TagChip {
+                name: tag.clone(),
+                url: ctx.chapter_href(&tags::tag_chapter_path(tag).to_string_lossy()),
+            })
+            .collect::<Vec<_>>();
+        let mut data = Map::new();
+        data.insert("tags".to_string(), to_json(tags));
+
+        // render
+        let html_string = ctx.render("aipr_tags", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct TagChip {
+    name: String,
+    url: String,
+}
@@ -0,0 +1,102 @@
+//! The `{{#aipr_term}}` helper showing glossary definitions as tooltips.
+
+use crate::helpers::params::required_params;
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRTermSettings {
+    pub(crate) term: String,
+}
+
+impl HelperHandler for AIPRTermSettings {
+    fn name() -> &'static str {
+        "aipr_term"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            term: required_params(Self::name(), params)?.to_string(),
+        })
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let glossary = &ctx.book.glossary;
+        let Some(definition) = glossary.definition(&self.term) else {
+            if ctx.config.strict {
+                anyhow::bail!("Term `{}` is not in the glossary", self.term);
+            }
+            log::warn!(
+                "Term `{}` used by `{}` is not in the glossary.",
+                self.term,
+                ctx.chapter.name
+            );
+            return Ok(self.term.clone());
+        };
+
+        // create data for rendering handlebar
+        let mut data = Map::new();
+        data.insert("term".to_string(), to_json(&self.term));
+        data.insert("definition".to_string(), to_json(definition));
+        data.insert(
+            "url".to_string(),
+            to_json(glossary.href(&self.term, ctx.chapter.path.as_deref())),
+        );
+
+        // render
+        let html_string = ctx.render("aipr_term", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use crate::glossary::Glossary;
+    use anyhow::Result;
+    use rstest::*;
+    use std::path::PathBuf;
+
+    #[rstest]
+    fn test_term_render() -> Result<()> {
+        let book = BookInfo {
+            glossary: Glossary::from_toml("LoRA = \"Low-rank adaptation.\"")?,
+            ..Default::default()
+        };
+        let chapter = ChapterInfo {
+            path: Some(PathBuf::from("nlp/qlora.md")),
+            ..Default::default()
+        };
+        let config = AIPRConfig::default();
+        let ctx = HelperContext {
+            config: &config,
+            book: &book,
+            chapter: &chapter,
+        };
+
+        let term = |term: &str| AIPRTermSettings {
+            term: term.to_string(),
+        };
+        assert_eq!(
+            term("LoRA").render(&ctx)?,
+            "<a class=\"aipr-term\" href=\"../glossary.html#lora\" title=\"Low-rank adaptation.\">LoRA</a>"
+        );
+        assert_eq!(term("RLHF").render(&ctx)?, "RLHF");
+
+        let strict_config = AIPRConfig {
+            strict: true,
+            ..Default::default()
+        };
+        let strict_ctx = HelperContext {
+            config: &strict_config,
+            ..ctx
+        };
+        assert!(term("RLHF").render(&strict_ctx).is_err());
+
+        Ok(())
+    }
+}
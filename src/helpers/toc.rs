@@ -0,0 +1,99 @@
+//! The `{{#aipr_toc}}` helper rendering an "On this page" table of contents.
+
+use crate::helpers::params::_parse_param_str;
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRTocSettings {
+    depth: usize,
+    title: String,
+}
+
+impl Default for AIPRTocSettings {
+    fn default() -> Self {
+        Self {
+            depth: 3,
+            title: "On this page".to_string(),
+        }
+    }
+}
+
+impl AIPRTocSettings {
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, &[]);
+        let default = Self::default();
+        let depth = param_map
+            .get("depth")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(default.depth);
+        let title = param_map.get("title").cloned().unwrap_or(default.title);
+
+        Self { depth, title }
+    }
+}
+
+impl HelperHandler for AIPRTocSettings {
+    fn name() -> &'static str {
+        "aipr_toc"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(params))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        // create data for rendering handlebar
+        let headings = ctx
+            .chapter
+            .headings
+            .iter()
+            .filter(|heading| (2..=self.depth).contains(&heading.level))
+            .collect::<Vec<_>>();
+        if headings.is_empty() {
+            return Ok(String::new());
+        }
+        let mut data = Map::new();
+        data.insert("title".to_string(), to_json(ctx.translate(&self.title)));
+        data.insert("headings".to_string(), to_json(headings));
+
+        // render
+        let html_string = ctx.render("aipr_toc", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{find_headings, BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_toc_render() -> Result<()> {
+        let chapter = ChapterInfo {
+            headings: find_headings("# T\n## A\n### B\n#### C\n"),
+            ..Default::default()
+        };
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &chapter,
+        };
+
+        let html_string = AIPRTocSettings::from_param_str("depth=2,title=Contents").render(&ctx)?;
+        let expected = "<nav class=\"aipr-toc\">\n  <p><strong>Contents</strong></p>\n  \
+        <ul>\n    <li class=\"aipr-toc-level-2\"><a href=\"#a\">A</a></li>\n  </ul>\n</nav>\n";
+        assert_eq!(html_string, expected);
+
+        let html_string = AIPRTocSettings::default().render(&ctx)?;
+        assert!(html_string.contains("<a href=\"#b\">B</a>"));
+        assert!(!html_string.contains("<a href=\"#c\">C</a>"));
+
+        Ok(())
+    }
+}
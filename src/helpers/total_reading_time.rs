@@ -0,0 +1,94 @@
+//! The `{{#aipr_total_reading_time}}` helper summing the reading time of the
+//! book or of a part.
+
+use crate::helpers::header::ReadingTimeOptions;
+use crate::helpers::params::_parse_param_str;
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde::Serialize;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct AIPRTotalReadingTimeSettings {
+    /// Title of the part whose chapters are summed, rather than the book's.
+    part: Option<String>,
+    /// Whether to list the reading time of every part above the total.
+    by_part: bool,
+}
+
+impl AIPRTotalReadingTimeSettings {
+    /// Params that are `true` or `false`, and can be given as bare flags.
+    pub(crate) const BOOLEAN_PARAMS: &'static [&'static str] = &["by_part"];
+
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, Self::BOOLEAN_PARAMS);
+        let part = param_map.get("part").cloned();
+        let by_part = matches!(param_map.get("by_part"), Some(bool_str) if bool_str == "true");
+
+        Self { part, by_part }
+    }
+}
+
+impl HelperHandler for AIPRTotalReadingTimeSettings {
+    fn name() -> &'static str {
+        "aipr_total_reading_time"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(params))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        if let Some(part) = &self.part {
+            if !ctx.book.parts.contains(part) {
+                anyhow::ensure!(!ctx.config.strict, "Part `{part}` is not in the book");
+                log::warn!(
+                    "Part `{part}` referenced by `{}` is not in the book, skipping its reading time.",
+                    ctx.chapter.name
+                );
+                return Ok(String::new());
+            }
+        }
+
+        // create data for rendering handlebar
+        let options = ReadingTimeOptions::from_config(ctx.config);
+        let reading_time = |part: Option<&String>| {
+            let counts = ctx
+                .book
+                .chapters
+                .values()
+                .filter(|summary| part.is_none() || summary.part.as_ref() == part)
+                .map(|summary| &summary.word_count)
+                .collect::<Vec<_>>();
+            options.format_total(&counts)
+        };
+        let mut data = Map::new();
+        data.insert(
+            "total".to_string(),
+            to_json(reading_time(self.part.as_ref())),
+        );
+        if self.by_part {
+            let parts = ctx
+                .book
+                .parts
+                .iter()
+                .map(|part| PartReadingTime {
+                    title: part.clone(),
+                    reading_time: reading_time(Some(part)),
+                })
+                .collect::<Vec<_>>();
+            data.insert("parts".to_string(), to_json(parts));
+        }
+
+        // render
+        let html_string = ctx.render("aipr_total_reading_time", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct PartReadingTime {
+    title: String,
+    reading_time: String,
+}
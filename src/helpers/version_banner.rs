@@ -0,0 +1,93 @@
+//! The `{{#aipr_version_banner}}` helper linking readers of older versions
+//! to the latest one.
+
+use crate::helpers::params::no_params;
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use serde_json::value::Map;
+
+/// An `{{#aipr_version_banner}}` helper, which takes no params.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct AIPRVersionBannerSettings;
+
+impl HelperHandler for AIPRVersionBannerSettings {
+    fn name() -> &'static str {
+        "aipr_version_banner"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        no_params(Self::name(), params)?;
+        Ok(Self)
+    }
+
+    /// Banner telling readers of an older version of the book, `current_version`,
+    /// where the latest one is.
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        let (Some(version), Some(latest_url)) =
+            (&ctx.config.current_version, &ctx.config.latest_url)
+        else {
+            anyhow::ensure!(
+                !ctx.config.strict,
+                "`aipr_version_banner` requires `current_version` and `latest_url`"
+            );
+            log::warn!(
+                "`aipr_version_banner` of `{}` requires `current_version` and `latest_url`, skipping.",
+                ctx.chapter.name
+            );
+            return Ok(String::new());
+        };
+
+        // create data for rendering handlebar
+        let mut data = Map::new();
+        data.insert("version".to_string(), to_json(version));
+        data.insert("latest_url".to_string(), to_json(latest_url));
+
+        // render
+        let html_string = ctx.render("aipr_version_banner", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_render_version_banner(#[case] strict: bool) -> Result<()> {
+        let render = |latest_url: Option<&str>| -> Result<String> {
+            let config = AIPRConfig {
+                strict,
+                current_version: Some("v0.3".to_string()),
+                latest_url: latest_url.map(str::to_string),
+                ..Default::default()
+            };
+            let ctx = HelperContext {
+                config: &config,
+                book: &BookInfo::default(),
+                chapter: &ChapterInfo::default(),
+            };
+            AIPRVersionBannerSettings.render(&ctx)
+        };
+
+        let html_string = render(Some("https://fake.io/latest/"))?;
+        assert!(html_string.contains(
+            "<span>You are viewing v0.3. <a href=\"https://fake.io/latest/\">\
+            Go to the latest version</a></span>"
+        ));
+        assert!(html_string.contains("class=\"aipr-version-banner-close\""));
+
+        match strict {
+            true => assert!(render(None).is_err()),
+            false => assert_eq!(render(None)?, ""),
+        }
+
+        Ok(())
+    }
+}
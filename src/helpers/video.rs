@@ -0,0 +1,157 @@
+//! The `{{#aipr_video}}` helper embedding YouTube and Vimeo videos.
+
+use crate::helpers::params::{_parse_param_str, required_params};
+use crate::helpers::{HelperContext, HelperHandler};
+use handlebars::to_json;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::value::Map;
+
+#[derive(Debug, Clone, PartialEq)]
+enum VideoSource {
+    YouTube(String),
+    Vimeo(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AIPRVideoSettings {
+    source: Option<VideoSource>,
+    title: String,
+    /// Render a thumbnail linking to the video instead of an embedded player.
+    thumbnail: bool,
+}
+
+impl AIPRVideoSettings {
+    /// Params that are `true` or `false`, and can be given as bare flags.
+    pub(crate) const BOOLEAN_PARAMS: &'static [&'static str] = &["thumbnail"];
+
+    fn from_param_str(param_str: &str) -> Self {
+        let param_map = _parse_param_str(param_str, Self::BOOLEAN_PARAMS);
+        let source = match (param_map.get("youtube"), param_map.get("vimeo")) {
+            (Some(id), _) => Some(VideoSource::YouTube(id.to_owned())),
+            (None, Some(id)) => Some(VideoSource::Vimeo(id.to_owned())),
+            (None, None) => None,
+        };
+        let title = param_map
+            .get("title")
+            .cloned()
+            .unwrap_or_else(|| "Video".to_string());
+        let thumbnail =
+            matches!(param_map.get("thumbnail"), Some(bool_str) if (bool_str == "true"));
+
+        Self {
+            source,
+            title,
+            thumbnail,
+        }
+    }
+}
+
+impl HelperHandler for AIPRVideoSettings {
+    fn name() -> &'static str {
+        "aipr_video"
+    }
+
+    fn parse(params: &str) -> anyhow::Result<Self> {
+        Ok(Self::from_param_str(required_params(Self::name(), params)?))
+    }
+
+    fn render(&self, ctx: &HelperContext) -> anyhow::Result<String> {
+        static YOUTUBE_ID_RE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"^[a-zA-Z0-9_-]{11}$").unwrap());
+        static VIMEO_ID_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9]+$").unwrap());
+
+        // create data for rendering handlebar, using privacy-enhanced players
+        let mut data = Map::new();
+        match &self.source {
+            Some(VideoSource::YouTube(id)) => {
+                anyhow::ensure!(
+                    YOUTUBE_ID_RE.is_match(id),
+                    "Invalid YouTube video id `{id}`"
+                );
+                data.insert(
+                    "embed_url".to_string(),
+                    to_json(format!("https://www.youtube-nocookie.com/embed/{id}")),
+                );
+                data.insert(
+                    "watch_url".to_string(),
+                    to_json(format!("https://www.youtube.com/watch?v={id}")),
+                );
+                data.insert(
+                    "thumbnail_url".to_string(),
+                    to_json(format!("https://i.ytimg.com/vi/{id}/hqdefault.jpg")),
+                );
+            }
+            Some(VideoSource::Vimeo(id)) => {
+                anyhow::ensure!(VIMEO_ID_RE.is_match(id), "Invalid Vimeo video id `{id}`");
+                data.insert(
+                    "embed_url".to_string(),
+                    to_json(format!("https://player.vimeo.com/video/{id}?dnt=1")),
+                );
+                data.insert(
+                    "watch_url".to_string(),
+                    to_json(format!("https://vimeo.com/{id}")),
+                );
+            }
+            None => anyhow::bail!("`aipr_video` requires a `youtube` or `vimeo` id"),
+        }
+        data.insert("title".to_string(), to_json(ctx.translate(&self.title)));
+        data.insert("thumbnail".to_string(), to_json(self.thumbnail));
+
+        // render
+        let html_string = ctx.render("aipr_video", &data)?;
+
+        Ok(html_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_pocket_reference::{BookInfo, ChapterInfo};
+    use crate::config::AIPRConfig;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    #[case(
+        "youtube=dQw4w9WgXcQ title=\"Attention explained\"",
+        "<iframe src=\"https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ\" title=\"Attention explained\""
+    )]
+    #[case(
+        "vimeo=76979871",
+        "<iframe src=\"https://player.vimeo.com/video/76979871?dnt=1\" title=\"Video\""
+    )]
+    #[case(
+        "youtube=dQw4w9WgXcQ,thumbnail=true",
+        "<img src=\"https://i.ytimg.com/vi/dQw4w9WgXcQ/hqdefault.jpg\" alt=\"Video\" loading=\"lazy\"/>"
+    )]
+    fn test_video_render(#[case] param_str: &str, #[case] expected: &str) -> Result<()> {
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        let html_string = AIPRVideoSettings::from_param_str(param_str).render(&ctx)?;
+        assert!(html_string.contains(expected), "{html_string}");
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("youtube=not-an-id")]
+    #[case("vimeo=abc")]
+    fn test_video_render_invalid(#[case] param_str: &str) -> Result<()> {
+        let ctx = HelperContext {
+            config: &AIPRConfig::default(),
+            book: &BookInfo::default(),
+            chapter: &ChapterInfo::default(),
+        };
+        assert!(AIPRVideoSettings::from_param_str(param_str)
+            .render(&ctx)
+            .is_err());
+
+        Ok(())
+    }
+}
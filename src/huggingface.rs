@@ -0,0 +1,167 @@
+//! Hugging Face Hub repositories linked by `{{#aipr_hf}}`, and the metadata
+//! shown on their cards, read from the Hub API.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Kind of a Hub repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HubKind {
+    Model,
+    Dataset,
+    Space,
+}
+
+impl HubKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            HubKind::Model => "Model",
+            HubKind::Dataset => "Dataset",
+            HubKind::Space => "Space",
+        }
+    }
+
+    /// Path segment of the kind in Hub URLs, e.g. `datasets/`.
+    fn url_prefix(self) -> &'static str {
+        match self {
+            HubKind::Model => "",
+            HubKind::Dataset => "datasets/",
+            HubKind::Space => "spaces/",
+        }
+    }
+}
+
+/// A repository on the Hub, e.g. the model `meta-llama/Llama-3-8B`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HubRepo {
+    pub kind: HubKind,
+    pub id: String,
+}
+
+impl HubRepo {
+    pub fn url(&self) -> String {
+        format!(
+            "https://huggingface.co/{}{}",
+            self.kind.url_prefix(),
+            self.id
+        )
+    }
+
+    pub fn api_url(&self) -> String {
+        let kind = match self.kind {
+            HubKind::Model => "models",
+            HubKind::Dataset => "datasets",
+            HubKind::Space => "spaces",
+        };
+        format!("https://huggingface.co/api/{kind}/{}", self.id)
+    }
+
+    /// Path, within the cache directory, of the repository's API response.
+    pub fn cache_key(&self) -> PathBuf {
+        let api_path = self.api_url().replace("https://huggingface.co/api/", "");
+        PathBuf::from("huggingface").join(format!("{api_path}.json"))
+    }
+}
+
+/// Metadata of a Hub repository shown on its card.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct HubMetadata {
+    pub downloads: Option<String>,
+    pub likes: Option<String>,
+    pub license: Option<String>,
+}
+
+impl HubMetadata {
+    /// Metadata from a Hub API response. The license comes from the model
+    /// card, falling back to the `license:` tag.
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let value: Value = serde_json::from_str(json)?;
+        let license = value
+            .pointer("/cardData/license")
+            .and_then(Value::as_str)
+            .or_else(|| {
+                value
+                    .get("tags")?
+                    .as_array()?
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .find_map(|tag| tag.strip_prefix("license:"))
+            })
+            .map(str::to_string);
+
+        Ok(Self {
+            downloads: value
+                .get("downloads")
+                .and_then(Value::as_u64)
+                .map(compact_number),
+            likes: value
+                .get("likes")
+                .and_then(Value::as_u64)
+                .map(compact_number),
+            license,
+        })
+    }
+}
+
+/// `1234567` as `1.2M`.
+fn compact_number(n: u64) -> String {
+    match n {
+        0..=999 => n.to_string(),
+        1_000..=999_999 => format!("{:.1}k", n as f64 / 1e3),
+        1_000_000..=999_999_999 => format!("{:.1}M", n as f64 / 1e6),
+        _ => format!("{:.1}B", n as f64 / 1e9),
+    }
+    .replace(".0", "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_hub_repo_urls() -> Result<()> {
+        let dataset = HubRepo {
+            kind: HubKind::Dataset,
+            id: "rajpurkar/squad_v1.1".to_string(),
+        };
+        assert_eq!(
+            dataset.url(),
+            "https://huggingface.co/datasets/rajpurkar/squad_v1.1"
+        );
+        assert_eq!(
+            dataset.cache_key(),
+            PathBuf::from("huggingface/datasets/rajpurkar/squad_v1.1.json")
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(
+        r#"{"downloads": 1234567, "likes": 950, "cardData": {"license": "llama3"}}"#,
+        HubMetadata {
+            downloads: Some("1.2M".to_string()),
+            likes: Some("950".to_string()),
+            license: Some("llama3".to_string()),
+        }
+    )]
+    #[case(
+        r#"{"likes": 12000, "tags": ["text-generation", "license:apache-2.0"]}"#,
+        HubMetadata {
+            downloads: None,
+            likes: Some("12k".to_string()),
+            license: Some("apache-2.0".to_string()),
+        }
+    )]
+    fn test_hub_metadata_from_json(
+        #[case] json: &str,
+        #[case] expected: HubMetadata,
+    ) -> Result<()> {
+        assert_eq!(HubMetadata::from_json(json)?, expected);
+
+        Ok(())
+    }
+}
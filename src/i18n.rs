@@ -0,0 +1,380 @@
+//! Translation of the text helpers add to chapters, such as the header's
+//! "Reading time" or a card's "License".
+//!
+//! Messages are identified by their English text, as in gettext catalogs, so
+//! templates write `{{t "Reading time"}}`. A book's messages are translated
+//! by, in order, its `translations`, the gettext catalog of its language in
+//! `po_dir` (shared with mdbook-i18n-helpers), and the built-in translations.
+
+use std::collections::BTreeMap;
+
+/// Messages of the Rust code, rather than templates, such as default titles.
+pub(crate) const MESSAGES: &[&str] = &[
+    "Before you read this",
+    "Danger",
+    "Difficulty",
+    "Edit this page",
+    "GPU",
+    "Note",
+    "On this page",
+    "Related chapters",
+    "Revision history",
+    "Runtime",
+    "Suggest an Edit",
+    "Tip",
+    "Updated a month ago",
+    "Updated a week ago",
+    "Updated a year ago",
+    "Updated today",
+    "Updated yesterday",
+    "Updated {n} days ago",
+    "Updated {n} months ago",
+    "Updated {n} weeks ago",
+    "Updated {n} years ago",
+    "VRAM",
+    "Video",
+    "Warning",
+];
+
+/// Translations of the header's messages into the languages supported out of
+/// the box.
+const BUILT_IN: &[(&str, &[(&str, &str)])] = &[
+    (
+        "de",
+        &[
+            ("By", "Von"),
+            ("Difficulty", "Schwierigkeit"),
+            ("Download .ipynb", ".ipynb herunterladen"),
+            ("Edit this page", "Diese Seite bearbeiten"),
+            ("Last updated", "Zuletzt aktualisiert"),
+            ("Open In Colab", "In Colab öffnen"),
+            ("Reading time", "Lesezeit"),
+            ("Suggest an Edit", "Änderung vorschlagen"),
+            ("Updated a month ago", "Vor einem Monat aktualisiert"),
+            ("Updated a week ago", "Vor einer Woche aktualisiert"),
+            ("Updated a year ago", "Vor einem Jahr aktualisiert"),
+            ("Updated today", "Heute aktualisiert"),
+            ("Updated yesterday", "Gestern aktualisiert"),
+            ("Updated {n} days ago", "Vor {n} Tagen aktualisiert"),
+            ("Updated {n} months ago", "Vor {n} Monaten aktualisiert"),
+            ("Updated {n} weeks ago", "Vor {n} Wochen aktualisiert"),
+            ("Updated {n} years ago", "Vor {n} Jahren aktualisiert"),
+            ("What you'll need", "Was Sie brauchen"),
+        ],
+    ),
+    (
+        "es",
+        &[
+            ("By", "Por"),
+            ("Difficulty", "Dificultad"),
+            ("Download .ipynb", "Descargar .ipynb"),
+            ("Edit this page", "Editar esta página"),
+            ("Last updated", "Última actualización"),
+            ("Open In Colab", "Abrir en Colab"),
+            ("Reading time", "Tiempo de lectura"),
+            ("Suggest an Edit", "Sugerir un cambio"),
+            ("Updated a month ago", "Actualizado hace un mes"),
+            ("Updated a week ago", "Actualizado hace una semana"),
+            ("Updated a year ago", "Actualizado hace un año"),
+            ("Updated today", "Actualizado hoy"),
+            ("Updated yesterday", "Actualizado ayer"),
+            ("Updated {n} days ago", "Actualizado hace {n} días"),
+            ("Updated {n} months ago", "Actualizado hace {n} meses"),
+            ("Updated {n} weeks ago", "Actualizado hace {n} semanas"),
+            ("Updated {n} years ago", "Actualizado hace {n} años"),
+            ("What you'll need", "Lo que necesitas"),
+        ],
+    ),
+    (
+        "fr",
+        &[
+            ("By", "Par"),
+            ("Difficulty", "Difficulté"),
+            ("Download .ipynb", "Télécharger le .ipynb"),
+            ("Edit this page", "Modifier cette page"),
+            ("Last updated", "Dernière mise à jour"),
+            ("Open In Colab", "Ouvrir dans Colab"),
+            ("Reading time", "Temps de lecture"),
+            ("Suggest an Edit", "Suggérer une modification"),
+            ("Updated a month ago", "Mis à jour il y a un mois"),
+            ("Updated a week ago", "Mis à jour il y a une semaine"),
+            ("Updated a year ago", "Mis à jour il y a un an"),
+            ("Updated today", "Mis à jour aujourd'hui"),
+            ("Updated yesterday", "Mis à jour hier"),
+            ("Updated {n} days ago", "Mis à jour il y a {n} jours"),
+            ("Updated {n} months ago", "Mis à jour il y a {n} mois"),
+            ("Updated {n} weeks ago", "Mis à jour il y a {n} semaines"),
+            ("Updated {n} years ago", "Mis à jour il y a {n} ans"),
+            ("What you'll need", "Ce qu'il vous faut"),
+        ],
+    ),
+    (
+        "zh",
+        &[
+            ("By", "作者"),
+            ("Difficulty", "难度"),
+            ("Download .ipynb", "下载 .ipynb"),
+            ("Edit this page", "编辑此页"),
+            ("Last updated", "最后更新"),
+            ("Open In Colab", "在 Colab 中打开"),
+            ("Reading time", "阅读时间"),
+            ("Suggest an Edit", "建议修改"),
+            ("Updated a month ago", "1 个月前更新"),
+            ("Updated a week ago", "1 周前更新"),
+            ("Updated a year ago", "1 年前更新"),
+            ("Updated today", "今天更新"),
+            ("Updated yesterday", "昨天更新"),
+            ("Updated {n} days ago", "{n} 天前更新"),
+            ("Updated {n} months ago", "{n} 个月前更新"),
+            ("Updated {n} weeks ago", "{n} 周前更新"),
+            ("Updated {n} years ago", "{n} 年前更新"),
+            ("What you'll need", "所需条件"),
+        ],
+    ),
+];
+
+/// Built-in translations into `language`, matching a tag such as `fr-CA` by
+/// its primary language.
+fn built_in(language: &str) -> &'static [(&'static str, &'static str)] {
+    let primary = language.split(['-', '_']).next().unwrap_or(language);
+    BUILT_IN
+        .iter()
+        .find(|(tag, _)| tag.eq_ignore_ascii_case(language) || tag.eq_ignore_ascii_case(primary))
+        .map_or(&[], |(_, translations)| *translations)
+}
+
+/// Translations of messages into a book's language, keyed by their English
+/// text. Messages without one are shown in English.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Catalog(BTreeMap<String, String>);
+
+impl Catalog {
+    /// Catalog of `language`, from the built-in translations, the entries of
+    /// the gettext catalog `po` translating `messages`, and `translations`.
+    pub fn new(
+        language: &str,
+        messages: &[String],
+        po: Option<&str>,
+        translations: &BTreeMap<String, BTreeMap<String, String>>,
+    ) -> Self {
+        let mut catalog: BTreeMap<String, String> = built_in(language)
+            .iter()
+            .map(|(msgid, msgstr)| (msgid.to_string(), msgstr.to_string()))
+            .collect();
+        if let Some(po) = po {
+            catalog.extend(
+                parse_po(po)
+                    .into_iter()
+                    .filter(|(msgid, _)| messages.contains(msgid)),
+            );
+        }
+        if let Some(overrides) = translations.get(language) {
+            catalog.extend(overrides.clone());
+        }
+        Self(catalog)
+    }
+
+    /// Translation of the message `msgid`.
+    pub fn get<'a>(&'a self, msgid: &'a str) -> &'a str {
+        self.0.get(msgid).map_or(msgid, String::as_str)
+    }
+}
+
+/// Unescapes the contents of a quoted gettext string.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+/// Escapes `value` as the contents of a quoted gettext string.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+}
+
+/// Translations of the gettext catalog `contents`, keyed by `msgid`. Fuzzy,
+/// untranslated, plural and context-bound entries are left out.
+pub(crate) fn parse_po(contents: &str) -> BTreeMap<String, String> {
+    #[derive(Default)]
+    struct Entry {
+        fuzzy: bool,
+        skipped: bool,
+        msgid: Option<String>,
+        msgstr: Option<String>,
+    }
+
+    let mut translations = BTreeMap::new();
+    let mut add = |entry: Entry| match entry {
+        Entry {
+            fuzzy: false,
+            skipped: false,
+            msgid: Some(msgid),
+            msgstr: Some(msgstr),
+        } if !msgid.is_empty() && !msgstr.is_empty() => {
+            translations.insert(msgid, msgstr);
+        }
+        _ => {}
+    };
+
+    let mut entry = Entry::default();
+    // whether lines holding only a quoted string continue the msgstr, rather
+    // than the msgid
+    let mut in_msgstr = false;
+    for line in contents.lines().map(str::trim) {
+        let quoted = |rest: &str| {
+            rest.trim()
+                .strip_prefix('"')
+                .and_then(|rest| rest.strip_suffix('"'))
+                .map(unescape)
+        };
+        if line.is_empty() {
+            add(std::mem::take(&mut entry));
+        } else if let Some(flags) = line.strip_prefix("#,") {
+            entry.fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+        } else if line.starts_with('#') {
+            // comments and references
+        } else if let Some(rest) = line.strip_prefix("msgid ") {
+            if entry.msgstr.is_some() {
+                add(std::mem::take(&mut entry));
+            }
+            entry.msgid = quoted(rest);
+            in_msgstr = false;
+        } else if let Some(rest) = line.strip_prefix("msgstr ") {
+            entry.msgstr = quoted(rest);
+            in_msgstr = true;
+        } else if line.starts_with("msgctxt")
+            || line.starts_with("msgid_plural")
+            || line.starts_with("msgstr[")
+        {
+            entry.skipped = true;
+        } else if let Some(value) = quoted(line) {
+            let string = if in_msgstr {
+                &mut entry.msgstr
+            } else {
+                &mut entry.msgid
+            };
+            if let Some(string) = string {
+                string.push_str(&value);
+            }
+        }
+    }
+    add(entry);
+    translations
+}
+
+/// Gettext template (`.pot`) of `messages`, for translators to start or
+/// update the catalog of their language from.
+pub(crate) fn pot(messages: &[String]) -> String {
+    let mut pot =
+        String::from("msgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n");
+    for msgid in messages {
+        pot.push_str(&format!(
+            "\n#: mdbook-ai-pocket-reference\nmsgid \"{}\"\nmsgstr \"\"\n",
+            escape(msgid)
+        ));
+    }
+    pot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    #[case("en", "Reading time")]
+    #[case("fr", "Temps de lecture")]
+    #[case("fr-CA", "Temps de lecture")]
+    #[case("pt", "Reading time")]
+    fn test_catalog_built_in(#[case] language: &str, #[case] expected: &str) -> Result<()> {
+        let catalog = Catalog::new(language, &[], None, &BTreeMap::new());
+        assert_eq!(catalog.get("Reading time"), expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_catalog_sources() -> Result<()> {
+        let messages = vec!["Reading time".to_string(), "Dataset".to_string()];
+        let po = "msgid \"Dataset\"\nmsgstr \"Conjunto de dados\"\n\n\
+                  msgid \"Some chapter prose\"\nmsgstr \"Texto do capítulo\"\n";
+        let translations = BTreeMap::from([(
+            "pt".to_string(),
+            BTreeMap::from([("Reading time".to_string(), "Tempo de leitura".to_string())]),
+        )]);
+
+        let catalog = Catalog::new("pt", &messages, Some(po), &translations);
+        assert_eq!(catalog.get("Reading time"), "Tempo de leitura");
+        assert_eq!(catalog.get("Dataset"), "Conjunto de dados");
+        assert_eq!(catalog.get("Suggest an Edit"), "Suggest an Edit");
+        // only messages of the preprocessor are kept from the catalog
+        assert_eq!(catalog.get("Some chapter prose"), "Some chapter prose");
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_parse_po() -> Result<()> {
+        let po = r#"# Translators
+msgid ""
+msgstr ""
+"Language: fr\n"
+
+#: src/lora.md:1
+msgid "Reading "
+"time"
+msgstr "Temps de "
+"lecture"
+
+#, fuzzy
+msgid "Difficulty"
+msgstr "Difficulté"
+
+msgid "Say \"hi\""
+msgstr "Dites \"salut\""
+
+msgctxt "button"
+msgid "Check answer"
+msgstr "Vérifier"
+
+msgid "Untranslated"
+msgstr ""
+"#;
+
+        assert_eq!(
+            parse_po(po),
+            BTreeMap::from([
+                ("Reading time".to_string(), "Temps de lecture".to_string()),
+                ("Say \"hi\"".to_string(), "Dites \"salut\"".to_string()),
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_pot() -> Result<()> {
+        let pot = pot(&["Say \"hi\"".to_string()]);
+        assert!(pot.starts_with("msgid \"\"\nmsgstr \"\"\n"));
+        assert!(pot.ends_with("msgid \"Say \\\"hi\\\"\"\nmsgstr \"\"\n"));
+
+        Ok(())
+    }
+}
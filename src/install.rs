@@ -0,0 +1,122 @@
+//! Wiring the preprocessor into a book's `book.toml`, for the `install`
+//! subcommand. Existing tables and keys are kept, so installing again only
+//! adds what's missing.
+
+use crate::ai_pocket_reference::AIPRPreprocessor;
+use crate::config::AIPRConfig;
+use anyhow::Context;
+use std::path::Path;
+use toml_edit::{value, Array, DocumentMut, Item, Table};
+
+/// Stylesheet of the helpers, installed next to `book.toml`.
+const CSS: &str = include_str!("bin/assets/mdbook-ai-pocket-reference.css");
+const CSS_FILE: &str = "mdbook-ai-pocket-reference.css";
+
+/// Adds the preprocessor and the config keys most books set to the
+/// `book.toml` in `dir` and, if `css` is set, writes the stylesheet next to it
+/// and adds it to `output.html.additional-css`. Returns what was changed.
+pub fn install(dir: &Path, css: bool) -> anyhow::Result<Vec<String>> {
+    let path = dir.join("book.toml");
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut doc = contents
+        .parse::<DocumentMut>()
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    let mut changes = vec![];
+
+    let name = AIPRPreprocessor::NAME;
+    let preprocessors = table(&mut doc, "preprocessor");
+    if !preprocessors.contains_key(name) {
+        changes.push(format!("Added `[preprocessor.{name}]`"));
+    }
+    let preprocessor = table(preprocessors, name);
+    let defaults = AIPRConfig::default();
+    for (key, default) in [
+        ("command", "mdbook-ai-pocket-reference"),
+        ("colab_repo", defaults.colab_repo.as_str()),
+        ("colab_branch", defaults.colab_branch.as_str()),
+        ("issue_repo", defaults.issue_repo.as_str()),
+    ] {
+        if !preprocessor.contains_key(key) {
+            preprocessor.insert(key, value(default));
+            changes.push(format!("Set `preprocessor.{name}.{key}`"));
+        }
+    }
+
+    if css {
+        let css_path = dir.join(CSS_FILE);
+        if std::fs::read_to_string(&css_path).ok().as_deref() != Some(CSS) {
+            std::fs::write(&css_path, CSS)
+                .with_context(|| format!("Failed to write {}", css_path.display()))?;
+            changes.push(format!("Wrote {CSS_FILE}"));
+        }
+        let html = table(table(&mut doc, "output"), "html");
+        let additional_css = html
+            .entry("additional-css")
+            .or_insert_with(|| value(Array::new()))
+            .as_array_mut()
+            .context("`output.html.additional-css` is not an array")?;
+        if !additional_css
+            .iter()
+            .any(|file| file.as_str() == Some(CSS_FILE))
+        {
+            additional_css.push(CSS_FILE);
+            changes.push(format!("Added {CSS_FILE} to `output.html.additional-css`"));
+        }
+    }
+
+    if !changes.is_empty() {
+        std::fs::write(&path, doc.to_string())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(changes)
+}
+
+/// The table at `key` of `parent`, added if missing.
+fn table<'a>(parent: &'a mut Table, key: &str) -> &'a mut Table {
+    let item = parent.entry(key).or_insert_with(|| {
+        let mut table = Table::new();
+        table.set_implicit(true);
+        Item::Table(table)
+    });
+    if !item.is_table() {
+        *item = Item::Table(Table::new());
+    }
+    item.as_table_mut().expect("item was made a table")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_install() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("aipr-install-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("book.toml"),
+            "[book]\ntitle = \"Test\" # kept\n\n\
+            [preprocessor.ai-pocket-reference]\ncolab_branch = \"dev\"\n\n\
+            [output.html]\nadditional-css = [\"custom.css\"]\n",
+        )?;
+
+        let changes = install(&dir, true)?;
+        assert_eq!(changes.len(), 5);
+        let book_toml = std::fs::read_to_string(dir.join("book.toml"))?;
+        assert!(book_toml.contains("title = \"Test\" # kept"));
+        assert!(book_toml.contains("command = \"mdbook-ai-pocket-reference\""));
+        assert!(book_toml.contains("colab_branch = \"dev\""));
+        assert!(book_toml
+            .contains("additional-css = [\"custom.css\", \"mdbook-ai-pocket-reference.css\"]"));
+        assert_eq!(std::fs::read_to_string(dir.join(CSS_FILE))?, CSS);
+
+        // installing again changes nothing
+        assert!(install(&dir, true)?.is_empty());
+        assert_eq!(std::fs::read_to_string(dir.join("book.toml"))?, book_toml);
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -20,8 +20,7 @@
 //! command = " mdbook-ai-pocket-reference"
 //! ```
 //!
-//! In order to add an author or list of authors in your chapter, there is currently
-//! one supported helper:
+//! The following helpers are supported:
 //!
 //! ```markdown
 //! <!-- for including ai-pocket-reference header (default) -->
@@ -30,10 +29,41 @@
 //! <!-- for including ai-pocket-reference header with colab link -->
 //! {{ #aipr_header colab=nlp/lora.ipynb}}
 //!
+//! <!-- for adding an author or list of authors in your chapter -->
+//! {{#aipr_authors authors=octocat,nerdai}}
+//!
 //! ```
 //!
 //! For more details see the project's [README](https://github.com/VectorInstitute/mdbook-ai-pocket-reference)
 
+mod abbreviations;
 pub mod ai_pocket_reference;
+mod arxiv;
+mod benchmark;
+mod bibliography;
+mod cards;
+pub mod citations;
+mod code;
+pub mod config;
+mod doi;
+mod feed;
+mod fetch;
+pub mod forge;
+mod generated;
+mod git;
+mod glossary;
+pub mod helpers;
+mod huggingface;
+mod i18n;
+mod install;
+mod link_check;
+mod notebooks;
+mod quiz;
+pub mod render;
+mod scaffold;
+mod tags;
+mod word_count;
 
-pub use ai_pocket_reference::AIPRPreprocessor;
+pub use ai_pocket_reference::{AIPRPreprocessor, ChapterStats, Problem};
+pub use install::install;
+pub use scaffold::init_chapter;
@@ -0,0 +1,218 @@
+//! Opt-in checking of the external links in chapters, enabled with
+//! `check_links = true`. URLs are requested with `HEAD` (falling back to a
+//! one-byte `GET` for servers rejecting `HEAD`) by shelling out to `curl`.
+//!
+//! URLs found alive are cached under `cache_dir` for `check_links_max_age`
+//! days, so rebuilds only recheck dead, new and stale links.
+
+use anyhow::Context;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Name, within the cache directory, of the cache of URLs found alive.
+const CACHE_FILE: &str = "link-check.json";
+
+/// Host of an `http(s)`, `ftp` or protocol-relative `url`, without userinfo
+/// or port.
+pub(crate) fn url_host(url: &str) -> Option<&str> {
+    let rest = ["https://", "http://", "ftp://", "//"]
+        .iter()
+        .find_map(|prefix| url.strip_prefix(prefix))?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    Some(host.split(':').next().unwrap_or(host))
+}
+
+/// Whether the host of `url` matches one of `patterns`. A pattern matches its
+/// domain and subdomains, and `*` matches any characters, so
+/// `vectorinstitute.ai` matches `www.vectorinstitute.ai` and
+/// `ai-pocket-reference.*` matches `ai-pocket-reference.github.io`.
+pub(crate) fn host_matches(url: &str, patterns: &[String]) -> bool {
+    let Some(host) = url_host(url) else {
+        return false;
+    };
+    let host = host.to_lowercase();
+    let domains = std::iter::once(host.as_str())
+        .chain(host.match_indices('.').map(|(i, _)| &host[i + 1..]))
+        .collect::<Vec<_>>();
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.trim().to_lowercase();
+        domains
+            .iter()
+            .any(|domain| wildcard_matches(&pattern, domain))
+    })
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any characters.
+fn wildcard_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*').collect::<Vec<_>>();
+    let Some(mut rest) = text.strip_prefix(parts.remove(0)) else {
+        return false;
+    };
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Outcome of requesting a URL.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum LinkStatus {
+    Alive,
+    /// Responded with an HTTP error status.
+    Dead(u16),
+    /// Couldn't be reached, e.g. an unknown host or a timeout.
+    Unreachable,
+}
+
+impl LinkStatus {
+    pub fn is_alive(&self) -> bool {
+        matches!(self, Self::Alive)
+    }
+}
+
+/// Requests `url`, giving up after `timeout_secs` seconds.
+pub(crate) fn check_url(url: &str, timeout_secs: u64) -> LinkStatus {
+    let status = match request_status(url, timeout_secs, true) {
+        // some servers don't support `HEAD`
+        Some(403 | 405 | 501) => request_status(url, timeout_secs, false),
+        status => status,
+    };
+    match status {
+        None | Some(0) => LinkStatus::Unreachable,
+        // rate limited, not known to be dead
+        Some(429) => LinkStatus::Alive,
+        Some(code) if code >= 400 => LinkStatus::Dead(code),
+        Some(_) => LinkStatus::Alive,
+    }
+}
+
+/// HTTP status of the final response to a `HEAD`, or one-byte `GET`, of `url`.
+fn request_status(url: &str, timeout_secs: u64, head: bool) -> Option<u16> {
+    let method = if head { "--head" } else { "--range=0-0" };
+    let output = Command::new("curl")
+        .args(["--silent", "--location", method])
+        .args(["--max-time", &timeout_secs.to_string()])
+        .args(["--write-out", "\n%{http_code}", url])
+        .output()
+        .map_err(|e| log::debug!("Failed to run curl for {url}: {e}"))
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().last()?.trim().parse().ok()
+}
+
+/// URLs found alive, mapped to the date they were last checked.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LinkCache {
+    path: PathBuf,
+    checked: BTreeMap<String, String>,
+}
+
+impl LinkCache {
+    /// Read the cache in `cache_dir`. A missing or unreadable cache is empty.
+    pub fn load(cache_dir: &Path) -> Self {
+        let path = cache_dir.join(CACHE_FILE);
+        let checked = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self { path, checked }
+    }
+
+    /// Whether `url` was found alive within `max_age_days` of `today`.
+    pub fn is_fresh(&self, url: &str, today: NaiveDate, max_age_days: i64) -> bool {
+        self.checked
+            .get(url)
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            .is_some_and(|date| (today - date).num_days() < max_age_days)
+    }
+
+    /// Record that `url` was found alive on `today`.
+    pub fn insert(&mut self, url: &str, today: NaiveDate) {
+        self.checked
+            .insert(url.to_string(), today.format("%Y-%m-%d").to_string());
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let contents = serde_json::to_string_pretty(&self.checked)?;
+        std::fs::write(&self.path, contents)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    #[case("https://vectorinstitute.ai/about", "vectorinstitute.ai", true)]
+    #[case("https://www.vectorinstitute.ai", "vectorinstitute.ai", true)]
+    #[case("https://notvectorinstitute.ai", "vectorinstitute.ai", false)]
+    #[case(
+        "http://user@ai-pocket-reference.github.io:8080/x",
+        "ai-pocket-reference.*",
+        true
+    )]
+    #[case("//www.vectorinstitute.ai/about", "vectorinstitute.ai", true)]
+    #[case("mailto:info@vectorinstitute.ai", "vectorinstitute.ai", false)]
+    #[case("https://docs.Example.com", "*.example.com", true)]
+    #[case("https://example.com", "*.example.com", false)]
+    #[case("https://cdn-eu.example.com", "cdn-*.example.com", true)]
+    #[case("https://cdn.example.org", "cdn*.example.com", false)]
+    #[case(
+        "https://github.com/ai-pocket-reference",
+        "ai-pocket-reference.*",
+        false
+    )]
+    fn test_host_matches(
+        #[case] url: &str,
+        #[case] pattern: &str,
+        #[case] expected: bool,
+    ) -> Result<()> {
+        assert_eq!(host_matches(url, &[pattern.to_string()]), expected);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_check_url_unreachable() -> Result<()> {
+        // nothing listens on the discard port
+        assert_eq!(check_url("http://127.0.0.1:9/", 2), LinkStatus::Unreachable);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_link_cache() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("aipr-link-cache-{}", std::process::id()));
+        let today = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+
+        let mut cache = LinkCache::load(&dir);
+        cache.insert("https://a.io", today - chrono::Duration::days(2));
+        cache.insert("https://b.io", today - chrono::Duration::days(9));
+        cache.save()?;
+
+        let cache = LinkCache::load(&dir);
+        assert!(cache.is_fresh("https://a.io", today, 7));
+        assert!(!cache.is_fresh("https://b.io", today, 7));
+        assert!(!cache.is_fresh("https://c.io", today, 7));
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+//! Validation of the notebook paths of headers (`colab=`, `kaggle=`, `binder=`
+//! and `sagemaker=`), so that broken notebook badges are caught at build time.
+//!
+//! Notebooks are looked up in the local checkout at `code_repo_path` when
+//! `colab_repo` is the `code_repo`, and through the API of the `forge`
+//! otherwise.
+
+use crate::config::AIPRConfig;
+use crate::fetch::{fetch_cached_within, HttpError};
+use std::path::{Path, PathBuf};
+
+/// Whether a notebook exists in `colab_repo`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum NotebookStatus {
+    Found,
+    Missing,
+    /// The notebook couldn't be checked, e.g. offline without a cached check.
+    Unknown(String),
+    /// The forge's API refused the check, e.g. when rate limited or lacking
+    /// authorization.
+    Denied(String),
+}
+
+/// Looks up the notebook at `path`, relative to `colab_root`. `root` is the
+/// book root that `code_repo_path` and the cache are relative to.
+pub(crate) fn check_notebook(config: &AIPRConfig, root: &Path, path: &str) -> NotebookStatus {
+    let repo_path = config.notebook_path(path);
+    if let Some(code_repo_path) = config
+        .code_repo_path
+        .as_ref()
+        .filter(|_| config.colab_repo.trim_matches('/') == config.code_repo.trim_matches('/'))
+    {
+        return match root.join(code_repo_path).join(&repo_path).is_file() {
+            true => NotebookStatus::Found,
+            false => NotebookStatus::Missing,
+        };
+    }
+
+    let cache_key = PathBuf::from(config.forge.host())
+        .join(config.colab_repo.trim_matches('/'))
+        .join(&config.colab_branch)
+        .join(format!("{repo_path}.json"));
+    let url = config.notebook_api_url(path);
+    let max_age = Some(config.validate_notebooks_max_age);
+    match fetch_cached_within(config, root, &url, &cache_key, max_age) {
+        Ok(_) => NotebookStatus::Found,
+        Err(e) => match e.downcast_ref::<HttpError>().map(|e| e.status) {
+            Some(404) => NotebookStatus::Missing,
+            Some(401 | 403 | 429) => NotebookStatus::Denied(format!("{e:#}")),
+            _ => NotebookStatus::Unknown(format!("{e:#}")),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_check_notebook() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("aipr-notebooks-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("code/notebooks/nlp"))?;
+        std::fs::write(root.join("code/notebooks/nlp/lora.ipynb"), "{}")?;
+        let config = AIPRConfig {
+            code_repo_path: Some(PathBuf::from("code")),
+            offline: true,
+            ..Default::default()
+        };
+
+        let status = check_notebook(&config, &root, "nlp/lora.ipynb");
+        assert_eq!(status, NotebookStatus::Found);
+        let status = check_notebook(&config, &root, "nlp/qlora.ipynb");
+        assert_eq!(status, NotebookStatus::Missing);
+
+        // another repo is checked through the API, which isn't cached
+        let config = AIPRConfig {
+            colab_repo: "my-org/notebooks".to_string(),
+            ..config
+        };
+        let status = check_notebook(&config, &root, "nlp/lora.ipynb");
+        assert!(matches!(status, NotebookStatus::Unknown(_)));
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    /// URL of a forge whose API answers a single request with `status`.
+    fn serve_status(status: u16) -> Result<String> {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let _ = stream.read(&mut [0; 4096]);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status} Status\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{{}}"
+                );
+            }
+        });
+        Ok(url)
+    }
+
+    #[rstest]
+    #[case(200, "found")]
+    #[case(404, "missing")]
+    #[case(403, "denied")]
+    #[case(429, "denied")]
+    #[case(500, "unknown")]
+    fn test_check_notebook_api(#[case] status: u16, #[case] expected: &str) -> Result<()> {
+        let root = std::env::temp_dir().join(format!(
+            "aipr-notebooks-api-{status}-{}",
+            std::process::id()
+        ));
+        let config = AIPRConfig {
+            forge: format!("gitlab:{}", serve_status(status)?).parse()?,
+            colab_repo: "my-org/notebooks".to_string(),
+            ..Default::default()
+        };
+
+        let status = check_notebook(&config, &root, "nlp/lora.ipynb");
+        let kind = match status {
+            NotebookStatus::Found => "found",
+            NotebookStatus::Missing => "missing",
+            NotebookStatus::Denied(_) => "denied",
+            NotebookStatus::Unknown(_) => "unknown",
+        };
+        assert_eq!(kind, expected);
+
+        if expected == "found" {
+            // found notebooks are cached, until they're too old
+            let status = check_notebook(&config, &root, "nlp/lora.ipynb");
+            assert_eq!(status, NotebookStatus::Found);
+            let config = AIPRConfig {
+                validate_notebooks_max_age: 0,
+                ..config
+            };
+            let status = check_notebook(&config, &root, "nlp/lora.ipynb");
+            assert!(matches!(status, NotebookStatus::Unknown(_)));
+            std::fs::remove_dir_all(&root)?;
+        }
+        Ok(())
+    }
+}
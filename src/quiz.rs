@@ -0,0 +1,102 @@
+//! Multiple-choice questions for `{{#aipr_quiz}}`, written in TOML either
+//! inline between `{{#aipr_quiz}}` and `{{/aipr_quiz}}`, or in a file:
+//!
+//! ```toml
+//! [[questions]]
+//! question = "Which weights does LoRA train?"
+//! choices = ["All of them", "Low-rank update matrices", "Only the biases"]
+//! answer = 2
+//! explanation = "The pre-trained weights are frozen."
+//! ```
+//!
+//! `answer` is the 1-based position of the correct choice.
+
+use anyhow::Context;
+use serde::Deserialize;
+use std::path::Path;
+
+/// A multiple-choice question.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct Question {
+    pub question: String,
+    pub choices: Vec<String>,
+    pub answer: usize,
+    #[serde(default)]
+    pub explanation: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct Quiz {
+    pub questions: Vec<Question>,
+}
+
+impl Quiz {
+    /// Read the quiz at `path`.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read quiz {}", path.display()))?;
+        Self::from_toml(&contents)
+            .with_context(|| format!("Failed to parse quiz {}", path.display()))
+    }
+
+    /// Parse a quiz from TOML, checking every answer is one of its choices.
+    pub fn from_toml(contents: &str) -> anyhow::Result<Self> {
+        let quiz: Self = toml::from_str(contents)?;
+        anyhow::ensure!(!quiz.questions.is_empty(), "Quiz has no questions");
+        for question in &quiz.questions {
+            anyhow::ensure!(
+                (1..=question.choices.len()).contains(&question.answer),
+                "Answer {} of `{}` is not one of its {} choices",
+                question.answer,
+                question.question,
+                question.choices.len()
+            );
+        }
+        Ok(quiz)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_quiz_from_toml() -> Result<()> {
+        let quiz = Quiz::from_toml(
+            r#"
+[[questions]]
+question = "Which weights does LoRA train?"
+choices = ["All of them", "Low-rank update matrices"]
+answer = 2
+
+[[questions]]
+question = "Is QLoRA quantized?"
+choices = ["Yes", "No"]
+answer = 1
+explanation = "The base model is quantized to 4 bits."
+"#,
+        )?;
+
+        assert_eq!(quiz.questions.len(), 2);
+        assert_eq!(quiz.questions[0].answer, 2);
+        assert_eq!(quiz.questions[0].explanation, None);
+        assert_eq!(
+            quiz.questions[1].explanation.as_deref(),
+            Some("The base model is quantized to 4 bits.")
+        );
+
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("[[questions]]\nquestion = \"Q\"\nchoices = [\"A\", \"B\"]\nanswer = 3\n")]
+    #[case("[[questions]]\nquestion = \"Q\"\nchoices = [\"A\", \"B\"]\nanswer = 0\n")]
+    fn test_quiz_from_toml_errors(#[case] contents: &str) -> Result<()> {
+        assert!(Quiz::from_toml(contents).is_err());
+
+        Ok(())
+    }
+}
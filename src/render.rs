@@ -0,0 +1,23 @@
+//! Rendering of helpers outside of mdBook, so that tooling such as the
+//! project's website generator produces the same HTML as the preprocessor.
+//!
+//! ```no_run
+//! use mdbook_ai_pocket_reference::config::AIPRConfig;
+//! use mdbook_ai_pocket_reference::render::{render_header, AIPRHeaderSettings};
+//! use mdbook_ai_pocket_reference::AIPRPreprocessor;
+//! use std::path::Path;
+//!
+//! let config = AIPRConfig::default();
+//! let settings = AIPRHeaderSettings::from_param_str("colab=nlp/lora.ipynb");
+//! for stats in AIPRPreprocessor::new().stats(Path::new("book"))? {
+//!     println!("{}", render_header(&settings, &stats, &config)?);
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+pub use crate::ai_pocket_reference::render_header;
+pub use crate::helpers::header::{
+    AIPRHeaderSettings, BinderNB, ColabNB, Difficulty, DownloadNB, EditPage, KaggleNB, ReadingTime,
+    Requirement, SageMakerNB, SubmitIssue, Updated,
+};
+pub use crate::helpers::last_updated::LastUpdated;
@@ -0,0 +1,99 @@
+//! Scaffolding of new chapters, for the `init-chapter` subcommand, so that
+//! contributors start from the same skeleton: front matter stubs, a title and
+//! the header helper.
+
+use anyhow::Context;
+use mdbook::Config;
+use std::path::{Path, PathBuf};
+
+/// Title of a chapter at `path` without an explicit one, e.g. `Mixture of
+/// experts` for `nlp/mixture-of-experts.md`.
+fn default_title(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().replace(['-', '_'], " "))
+        .unwrap_or_default();
+    let mut chars = stem.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Markdown of a new chapter at `path`, with its notebook at the same path
+/// under `colab_root`.
+fn chapter_skeleton(path: &Path, title: &str) -> String {
+    let colab = path.with_extension("ipynb");
+    format!(
+        "---\ntags: []\ncolab: {}\n---\n\n# {title}\n\n{{{{#aipr_header}}}}\n\n",
+        colab.to_string_lossy().replace('\\', "/")
+    )
+}
+
+/// Creates the chapter at `path`, relative to the `src` directory of the book
+/// in `dir`, and appends it to the book's `SUMMARY.md`. Returns the path of
+/// the created file.
+pub fn init_chapter(dir: &Path, path: &Path, title: Option<&str>) -> anyhow::Result<PathBuf> {
+    let config = Config::from_disk(dir.join("book.toml"))
+        .with_context(|| format!("Failed to read the book config in {}", dir.display()))?;
+    let src_dir = dir.join(&config.book.src);
+    let file = src_dir.join(path);
+    anyhow::ensure!(!file.exists(), "Chapter {} already exists", file.display());
+
+    let title = title.map_or_else(|| default_title(path), str::to_string);
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&file, chapter_skeleton(path, &title))
+        .with_context(|| format!("Failed to write {}", file.display()))?;
+
+    let summary_path = src_dir.join("SUMMARY.md");
+    let mut summary = std::fs::read_to_string(&summary_path)
+        .with_context(|| format!("Failed to read {}", summary_path.display()))?;
+    if !summary.is_empty() && !summary.ends_with('\n') {
+        summary.push('\n');
+    }
+    summary.push_str(&format!(
+        "- [{title}]({})\n",
+        path.to_string_lossy().replace('\\', "/")
+    ));
+    std::fs::write(&summary_path, summary)
+        .with_context(|| format!("Failed to write {}", summary_path.display()))?;
+
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_init_chapter() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("aipr-init-chapter-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src"))?;
+        std::fs::write(dir.join("book.toml"), "[book]\ntitle = \"Test\"\n")?;
+        std::fs::write(
+            dir.join("src/SUMMARY.md"),
+            "# Summary\n\n- [LoRA](nlp/lora.md)",
+        )?;
+
+        let file = init_chapter(&dir, Path::new("nlp/mixture-of-experts.md"), None)?;
+        assert_eq!(
+            std::fs::read_to_string(&file)?,
+            "---\ntags: []\ncolab: nlp/mixture-of-experts.ipynb\n---\n\n\
+            # Mixture of experts\n\n{{#aipr_header}}\n\n"
+        );
+        init_chapter(&dir, Path::new("nlp/qlora.md"), Some("QLoRA"))?;
+        assert_eq!(
+            std::fs::read_to_string(dir.join("src/SUMMARY.md"))?,
+            "# Summary\n\n- [LoRA](nlp/lora.md)\n\
+            - [Mixture of experts](nlp/mixture-of-experts.md)\n- [QLoRA](nlp/qlora.md)\n"
+        );
+        assert!(init_chapter(&dir, Path::new("nlp/qlora.md"), None).is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
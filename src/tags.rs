@@ -0,0 +1,121 @@
+//! Registry of chapter tags, used to generate a "Tags" chapter with one
+//! "Tag: X" sub-chapter per tag listing every chapter with that tag.
+
+use crate::generated::{generated_chapter, ChapterRef};
+use mdbook::book::{BookItem, Chapter};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Directory, relative to the book's `src`, of the generated tag chapters.
+const TAGS_DIR: &str = "tags";
+
+/// Path of the generated chapter listing the chapters tagged `tag`.
+pub(crate) fn tag_chapter_path(tag: &str) -> PathBuf {
+    let slug = mdbook::utils::normalize_id(tag);
+    Path::new(TAGS_DIR).join(format!("{slug}.md"))
+}
+
+/// A tag, as first spelled in the book, and the chapters carrying it.
+#[derive(Debug, Clone, PartialEq)]
+struct Tag {
+    name: String,
+    chapters: Vec<ChapterRef>,
+}
+
+/// Tags keyed by slug, so that `PEFT` and `peft` share a chapter, mapped to
+/// the chapters carrying them in book order.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TagRegistry {
+    tags: BTreeMap<String, Tag>,
+}
+
+impl TagRegistry {
+    /// Record that the chapter `name` at `path` is tagged `tag`.
+    pub fn insert(&mut self, tag: &str, name: &str, path: &Path) {
+        let chapters = &mut self
+            .tags
+            .entry(mdbook::utils::normalize_id(tag))
+            .or_insert_with(|| Tag {
+                name: tag.to_string(),
+                chapters: vec![],
+            })
+            .chapters;
+        if !chapters.iter().any(|ch| ch.path == path) {
+            chapters.push(ChapterRef::new(name, path));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// The generated "Tags" chapter, with a "Tag: X" sub-chapter per tag.
+    pub fn to_chapter(&self) -> Chapter {
+        let index_path = Path::new(TAGS_DIR).join("index.md");
+        let mut content = String::from("# Tags\n\n");
+        let mut sub_items = vec![];
+
+        for Tag {
+            name: tag,
+            chapters,
+        } in self.tags.values()
+        {
+            let path = tag_chapter_path(tag);
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            content.push_str(&format!("- [{tag}]({file_name}) ({})\n", chapters.len()));
+
+            let mut tag_content = format!("# Tag: {tag}\n\n");
+            for ch in chapters {
+                tag_content.push_str(&format!("- {}\n", ch.md_link(&path)));
+            }
+            sub_items.push(BookItem::Chapter(generated_chapter(
+                &format!("Tag: {tag}"),
+                tag_content,
+                path,
+                vec!["Tags".to_string()],
+            )));
+        }
+
+        let mut chapter = generated_chapter("Tags", content, index_path, vec![]);
+        chapter.sub_items = sub_items;
+        chapter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_tag_registry_chapters() -> Result<()> {
+        let mut registry = TagRegistry::default();
+        registry.insert("PEFT", "LoRA", Path::new("nlp/lora.md"));
+        registry.insert("PEFT", "QLoRA", Path::new("nlp/qlora.md"));
+        registry.insert("PEFT", "LoRA", Path::new("nlp/lora.md"));
+        registry.insert("nlp", "LoRA", Path::new("nlp/lora.md"));
+        registry.insert("peft", "DoRA", Path::new("nlp/dora.md"));
+
+        let chapter = registry.to_chapter();
+        assert_eq!(chapter.name, "Tags");
+        assert_eq!(chapter.path, Some(PathBuf::from("tags/index.md")));
+        assert_eq!(
+            chapter.content,
+            "# Tags\n\n- [nlp](nlp.md) (1)\n- [PEFT](peft.md) (3)\n"
+        );
+
+        let BookItem::Chapter(peft) = &chapter.sub_items[1] else {
+            panic!("expected a chapter");
+        };
+        assert_eq!(peft.name, "Tag: PEFT");
+        assert_eq!(peft.path, Some(PathBuf::from("tags/peft.md")));
+        assert_eq!(peft.source_path, None);
+        assert_eq!(
+            peft.content,
+            "# Tag: PEFT\n\n- [LoRA](../nlp/lora.md)\n- [QLoRA](../nlp/qlora.md)\n- [DoRA](../nlp/dora.md)\n"
+        );
+
+        Ok(())
+    }
+}
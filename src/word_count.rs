@@ -0,0 +1,229 @@
+//! Markdown-aware word counting, for reading times and the metadata export.
+//!
+//! Chapters are parsed the way mdBook renders them, so only the text readers
+//! read is counted as prose: not code blocks, HTML tags and comments, link
+//! URLs or image sources. Display math, `$$...$$` or `\\[...\\]`, is counted
+//! in blocks rather than words, and Chinese, Japanese and Korean text, which
+//! isn't split into words by spaces, in characters. Helper markup and front
+//! matter are left to the caller to exclude.
+
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::Serialize;
+use std::ops::Range;
+
+/// Options mdBook parses Markdown with.
+pub(crate) fn parser_options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_HEADING_ATTRIBUTES
+}
+
+/// Breakdown of what a chapter is made of.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub(crate) struct WordCount {
+    /// Words of prose, including inline code and text within HTML.
+    pub prose_words: usize,
+    /// Chinese, Japanese and Korean characters of prose.
+    pub cjk_chars: usize,
+    /// Words in code blocks.
+    pub code_words: usize,
+    /// Non-blank lines in code blocks.
+    pub code_lines: usize,
+    /// Display-math blocks.
+    pub equations: usize,
+    pub images: usize,
+}
+
+impl WordCount {
+    /// Words a reader reads, counting CJK characters as words, including
+    /// those in code blocks if `count_code` is set.
+    pub fn words(&self, count_code: bool) -> usize {
+        let prose = self.prose_words + self.cjk_chars;
+        if count_code {
+            prose + self.code_words
+        } else {
+            prose
+        }
+    }
+
+    /// Whether the prose is mostly Chinese, Japanese or Korean, so is read at
+    /// a pace of characters rather than words.
+    pub fn is_cjk(&self) -> bool {
+        self.cjk_chars > self.prose_words
+    }
+}
+
+/// Whether `c` is a Chinese, Japanese or Korean character.
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3000}'..='\u{303f}'       // CJK punctuation
+        | '\u{3040}'..='\u{30ff}'     // hiragana and katakana
+        | '\u{3400}'..='\u{4dbf}'     // CJK ideographs extension A
+        | '\u{4e00}'..='\u{9fff}'     // CJK ideographs
+        | '\u{ac00}'..='\u{d7af}'     // hangul syllables
+        | '\u{f900}'..='\u{faff}'     // CJK compatibility ideographs
+        | '\u{ff00}'..='\u{ffef}'     // fullwidth forms
+        | '\u{20000}'..='\u{2ebef}'   // CJK ideographs extensions B to F
+    )
+}
+
+/// Text of HTML, without its tags and comments.
+fn html_text(html: &str) -> String {
+    static HTML_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"(?xs)
+            <!--.*?-->      # html comment
+            |               # or
+            </?[a-zA-Z][^>]*>   # html tag
+            ",
+        )
+        .unwrap()
+    });
+
+    HTML_RE.replace_all(html, " ").into_owned()
+}
+
+/// Display math of the MathJax syntax mdBook supports, as text reaches the
+/// prose once Markdown escapes are resolved.
+static DISPLAY_MATH_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)\$\$.+?\$\$|\\\[.+?\\\]").unwrap());
+
+/// Counts the words, code, equations and images of the Markdown `content`,
+/// skipping the `excluded` byte ranges.
+pub(crate) fn count(content: &str, excluded: &[Range<usize>]) -> WordCount {
+    let mut excluded = excluded.to_vec();
+    excluded.sort_by_key(|range| range.start);
+    let mut markdown = String::with_capacity(content.len());
+    let mut previous_end_index = 0;
+    for range in excluded {
+        if range.start > previous_end_index {
+            markdown.push_str(&content[previous_end_index..range.start]);
+            markdown.push('\n');
+        }
+        previous_end_index = previous_end_index.max(range.end);
+    }
+    markdown.push_str(&content[previous_end_index..]);
+
+    let mut counts = WordCount::default();
+    let mut prose = String::with_capacity(markdown.len());
+    let mut code = String::new();
+    // consecutive HTML events, as comments can span several
+    let mut html = String::new();
+    let mut in_code_block = false;
+    let mut image_depth = 0;
+    for event in Parser::new_ext(&markdown, parser_options()) {
+        if !matches!(event, Event::Html(_) | Event::InlineHtml(_)) && !html.is_empty() {
+            prose.push_str(&html_text(&html));
+            html.clear();
+        }
+        match event {
+            Event::Start(Tag::Image { .. }) => {
+                counts.images += 1;
+                image_depth += 1;
+            }
+            Event::End(TagEnd::Image) => image_depth -= 1,
+            _ if image_depth > 0 => {}
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(text) if in_code_block => {
+                code.push_str(&text);
+                counts.code_lines += text.lines().filter(|line| !line.trim().is_empty()).count();
+            }
+            Event::Text(text) | Event::Code(text) => prose.push_str(&text),
+            Event::Html(text) | Event::InlineHtml(text) => html.push_str(&text),
+            Event::Start(Tag::Emphasis | Tag::Strong | Tag::Strikethrough | Tag::Link { .. })
+            | Event::End(
+                TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link,
+            ) => {}
+            // line breaks and block boundaries separate words
+            Event::SoftBreak | Event::HardBreak | Event::Rule => prose.push('\n'),
+            Event::Start(_) | Event::End(_) => prose.push('\n'),
+            _ => {}
+        }
+    }
+    prose.push_str(&html_text(&html));
+
+    counts.equations = DISPLAY_MATH_RE.find_iter(&prose).count();
+    let prose = DISPLAY_MATH_RE.replace_all(&prose, " ");
+    counts.cjk_chars = prose.chars().filter(|&c| is_cjk(c)).count();
+    counts.prose_words = words_count::count(prose.replace(is_cjk, " ")).words;
+    counts.code_words = words_count::count(code).words;
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rstest::*;
+
+    #[rstest]
+    fn test_count() -> Result<()> {
+        let content = "# Rank adapters\n\n\
+            Read [the paper](https://arxiv.org/abs/2106.09685) or `peft` docs.\n\n\
+            ![A diagram of adapters](lora.png)\n\n\
+            <div class=\"note\">Two words</div>\n<!--\na hidden comment\n-->\n\n\
+            ```python\nimport peft\n\nprint model\n```\n";
+
+        let counts = count(content, &[]);
+        assert_eq!(
+            counts,
+            WordCount {
+                prose_words: 10,
+                cjk_chars: 0,
+                code_words: 4,
+                code_lines: 2,
+                equations: 0,
+                images: 1,
+            }
+        );
+        assert_eq!(counts.words(false), 10);
+        assert_eq!(counts.words(true), 14);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_count_equations() -> Result<()> {
+        let content = "The loss is\n\n$$\nL = -\\sum_i y_i \\log p_i\n$$\n\n\
+            and its gradient \\\\[ \\nabla L = p - y \\\\] is simple.";
+
+        let counts = count(content, &[]);
+        assert_eq!(counts.equations, 2);
+        assert_eq!(counts.prose_words, 8);
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_count_cjk() -> Result<()> {
+        let counts = count("# 低秩适配\n\nLoRA 冻结预训练模型的权重。", &[]);
+        assert_eq!(counts.cjk_chars, 15);
+        assert_eq!(counts.prose_words, 1);
+        assert_eq!(counts.words(false), 16);
+        assert!(counts.is_cjk());
+
+        assert!(!count("LoRA (低秩适配) adapts large models", &[]).is_cjk());
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_count_excluded() -> Result<()> {
+        let content = "Some {{#aipr_helper}} words";
+        let start = content.find('{').unwrap();
+        let end = content.rfind('}').unwrap() + 1;
+        let helper = start..end;
+        assert_eq!(count(content, &[helper]).prose_words, 2);
+
+        // excluded ranges don't join the words around them
+        let separator = 3..4;
+        assert_eq!(count("twoXwords", &[separator]).prose_words, 2);
+
+        Ok(())
+    }
+}